@@ -0,0 +1,129 @@
+//! `git-qwen absorb`: turns staged hunks into `--fixup` commits for the
+//! recent commits they most plausibly belong to, found with `git blame`.
+
+use crate::config::Config;
+use crate::git;
+use crate::hunks::{self, Hunk};
+use std::collections::HashMap;
+
+/// Default number of recent commits a hunk can be absorbed into.
+const DEFAULT_DEPTH: usize = 10;
+
+/// Assigns each staged hunk to a recent commit and, after confirmation
+/// (or with `yes`), commits each group with `git commit --fixup`. Hunks
+/// that match no recent commit stay staged. With `dry_run` the plan is only
+/// printed.
+pub fn run(yes: bool, dry_run: bool) -> Result<(), String> {
+    let config = Config::load();
+    let candidates = candidates(config.get_usize("absorbDepth").unwrap_or(DEFAULT_DEPTH))?;
+    if candidates.is_empty() {
+        return Err("No recent commits to absorb changes into".to_string());
+    }
+
+    let files = hunks::staged()?;
+    if files.iter().all(|file| file.hunks.is_empty()) {
+        return Err("No staged changes to absorb".to_string());
+    }
+
+    // Target commit of each hunk, by (file, hunk) index
+    let mut targets: HashMap<(usize, usize), String> = HashMap::new();
+    for (file_index, file) in files.iter().enumerate() {
+        let Some(path) = &file.old_path else { continue };
+        for (hunk_index, hunk) in file.hunks.iter().enumerate() {
+            if let Some(target) = blame_target(path, hunk, &candidates) {
+                targets.insert((file_index, hunk_index), target);
+            }
+        }
+    }
+
+    // Oldest target first, so the fixups follow the history they amend
+    let groups: Vec<&String> = candidates
+        .iter()
+        .rev()
+        .filter(|candidate| targets.values().any(|target| target == *candidate))
+        .collect();
+    if groups.is_empty() {
+        println!("No staged hunk could be matched to one of the last {} commits.", candidates.len());
+        return Ok(());
+    }
+
+    for target in &groups {
+        let subject = git::output(&["log", "-1", "--format=%h %s", target])?;
+        println!("fixup! {}", subject.trim());
+        for (file_index, file) in files.iter().enumerate() {
+            let count = (0..file.hunks.len())
+                .filter(|hunk_index| targets.get(&(file_index, *hunk_index)) == Some(*target))
+                .count();
+            if count > 0 {
+                println!("    {} ({} hunk(s))", file.path, count);
+            }
+        }
+    }
+    let left = files.iter().map(|file| file.hunks.len()).sum::<usize>() - targets.len();
+    if left > 0 {
+        println!("{} hunk(s) match no recent commit and stay staged.", left);
+    }
+
+    if dry_run || !(yes || hunks::confirm("Create these fixup commits?")) {
+        return Ok(());
+    }
+
+    hunks::commit_groups(
+        &files,
+        |file, hunk| {
+            let target = targets.get(&(file, hunk))?;
+            groups.iter().position(|group| *group == target)
+        },
+        groups.len(),
+        |group| git::output(&["commit", "-q", &format!("--fixup={}", groups[group])]).map(|_| ()),
+    )
+}
+
+/// The commits hunks may be absorbed into: the most recent `depth`
+/// non-merge commits that are not on the upstream branch yet.
+fn candidates(depth: usize) -> Result<Vec<String>, String> {
+    let depth = depth.to_string();
+    let mut args = vec!["rev-list", "--no-merges", "-n", depth.as_str(), "HEAD"];
+    if git::output(&["rev-parse", "--verify", "--quiet", "@{upstream}"]).is_ok() {
+        args.extend(["--not", "@{upstream}"]);
+    }
+    Ok(git::output(&args)?.split_whitespace().map(String::from).collect())
+}
+
+/// The candidate commit that last touched most of the lines the hunk
+/// replaces, or for a pure addition, the lines around it.
+fn blame_target(path: &str, hunk: &Hunk, candidates: &[String]) -> Option<String> {
+    let (start, count) = if hunk.old_count > 0 {
+        (hunk.old_start, hunk.old_count)
+    } else {
+        // Lines are added after old_start; look at it and the next line
+        (hunk.old_start.max(1), 2)
+    };
+
+    let range = format!("{},+{}", start, count);
+    let blame = git::output(&["blame", "--porcelain", "-L", &range, "HEAD", "--", path])
+        .or_else(|_| git::output(&["blame", "--porcelain", "-L", &format!("{},+1", start), "HEAD", "--", path]))
+        .ok()?;
+
+    let mut votes: HashMap<&str, usize> = HashMap::new();
+    for line in blame.lines() {
+        let mut fields = line.split(' ');
+        if let (Some(sha), Some(_), Some(_)) = (fields.next(), fields.next(), fields.next())
+            && sha.len() >= 40
+            && sha.chars().all(|c| c.is_ascii_hexdigit())
+            && candidates.iter().any(|candidate| candidate == sha)
+        {
+            *votes.entry(sha).or_default() += 1;
+        }
+    }
+
+    // Ties go to the most recent commit
+    candidates
+        .iter()
+        .filter_map(|candidate| votes.get(candidate.as_str()).map(|count| (candidate, *count)))
+        .fold(None, |best: Option<(&String, usize)>, (candidate, count)| match best {
+            Some((_, best_count)) if best_count >= count => best,
+            _ => Some((candidate, count)),
+        })
+        .map(|(candidate, _)| candidate.clone())
+}
@@ -0,0 +1,118 @@
+//! Context for `--amend`: the message of the commit being amended and the
+//! changes being added to it.
+
+use crate::config::Config;
+use crate::diff;
+use crate::git::{GitClient, SystemGit};
+use crate::prompt;
+use crate::trailers;
+
+/// What happens to the original message, from `qwen.amendMode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// The model revises the original message to cover the whole change
+    Revise,
+    /// The original message is kept and a paragraph about the added
+    /// changes is appended to it
+    Append,
+}
+
+impl Mode {
+    pub fn from_config(config: &Config) -> Self {
+        match config.get("amendMode").map(str::to_lowercase).as_deref() {
+            None | Some("revise") => Mode::Revise,
+            Some("append") => Mode::Append,
+            Some(other) => {
+                eprintln!("Warning: ignoring invalid qwen.amendMode '{}', expected revise or append", other);
+                Mode::Revise
+            }
+        }
+    }
+}
+
+/// The commit being amended.
+#[derive(Debug)]
+pub struct Amend {
+    /// Its message, without the final trailer block
+    pub message: String,
+    /// Its trailers, kept on the amended commit
+    pub trailers: Vec<String>,
+    /// Diff of the changes being added to it
+    pub added: String,
+    pub mode: Mode,
+}
+
+impl Amend {
+    /// Reads the HEAD commit's message and the changes that `--amend` will
+    /// add to it: the staged ones, and with `-a` the unstaged ones too.
+    pub fn load(include_all: bool, pathspecs: &[String], config: &Config) -> Result<Self, String> {
+        Self::load_with(&SystemGit, include_all, pathspecs, config)
+    }
+
+    /// Like `load`, running `git` through the given client.
+    pub fn load_with(git: &dyn GitClient, include_all: bool, pathspecs: &[String], config: &Config) -> Result<Self, String> {
+        let full_message = git.output(&["log", "-1", "--format=%B", "HEAD"])?;
+        let (message, trailers) = trailers::split(&full_message);
+
+        let options = crate::diff::options(git);
+        let mut unstaged_args = vec!["diff"];
+        unstaged_args.extend(options.iter().map(String::as_str));
+        let mut staged_args = unstaged_args.clone();
+        staged_args.push("--cached");
+        if !pathspecs.is_empty() {
+            for args in [&mut staged_args, &mut unstaged_args] {
+                args.push("--");
+                args.extend(pathspecs.iter().map(String::as_str));
+            }
+        }
+        let mut added = git.output(&staged_args)?;
+        if include_all {
+            added.push_str(&git.output(&unstaged_args)?);
+        }
+
+        // With nothing added there is nothing to append
+        let mode = match Mode::from_config(config) {
+            Mode::Append if added.trim().is_empty() => Mode::Revise,
+            mode => mode,
+        };
+
+        Ok(Amend { message, trailers, added, mode })
+    }
+
+    /// The prompt section describing the original message and what changed
+    /// since it was written.
+    pub fn prompt_section(&self) -> String {
+        let mut section = match self.mode {
+            Mode::Revise => "\nThis change amends an existing commit. Its current message is below. Revise that message so it describes the whole change, keeping its wording where it is still accurate:\n".to_string(),
+            Mode::Append => "\nThese changes are being added to an existing commit whose message is below. That message is kept as it is. Write a message describing only the added changes; its body will be appended to the existing message, so don't repeat what it already says:\n".to_string(),
+        };
+        section.push_str(&prompt::fence(&self.message));
+
+        let files = diff::split_files(&self.added);
+        if self.mode == Mode::Revise && !files.is_empty() {
+            section.push_str("Since that message was written, these changes were added:\n");
+            let listing: Vec<String> =
+                files.iter().map(|file| format!("- {} ({})", file.path, file.change_kind())).collect();
+            section.push_str(&prompt::fence(&listing.join("\n")));
+        }
+        section
+    }
+
+    /// The message for the amended commit given the generated one: in
+    /// append mode, the original message followed by the generated body (or
+    /// its subject, if it has no body).
+    pub fn combine(&self, generated: &str) -> String {
+        if self.mode == Mode::Revise || generated.trim().is_empty() {
+            return generated.to_string();
+        }
+
+        let addition = match generated.split_once("\n\n") {
+            Some((_, body)) if !body.trim().is_empty() => body.trim().to_string(),
+            _ => {
+                let subject = generated.lines().next().unwrap_or("").trim();
+                format!("{}.", subject.trim_end_matches('.'))
+            }
+        };
+        format!("{}\n\n{}", self.message, addition)
+    }
+}
@@ -0,0 +1,761 @@
+use crate::cancel;
+use crate::capabilities;
+use crate::config::Config;
+use crate::daemon;
+use crate::jobs;
+use crate::keyring;
+use crate::mock;
+use crate::spinner;
+use serde_json::{Value, json};
+use std::env;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Executable of the model backend.
+pub const PROGRAM: &str = "qwen";
+
+/// Executable of the ollama backend.
+const OLLAMA_PROGRAM: &str = "ollama";
+
+/// Executable the openai backend sends its requests with.
+const CURL_PROGRAM: &str = "curl";
+
+/// Executables of the backends the daemon keeps a process of. Each curl
+/// request is a new connection anyway, so the openai backend runs its own.
+pub const PROGRAMS: &[&str] = &[PROGRAM, OLLAMA_PROGRAM];
+
+/// API the openai backend talks to unless `OPENAI_BASE_URL` names another.
+const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Key the openai backend sends, if set.
+const OPENAI_API_KEY_VAR: &str = "OPENAI_API_KEY";
+
+/// Model ollama runs unless `qwen.ollamaModel` says otherwise.
+const DEFAULT_OLLAMA_MODEL: &str = "qwen2.5-coder";
+
+/// Seconds to wait for an answer unless `qwen.timeout` says otherwise.
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Times a failed call is tried again unless `qwen.retries` says otherwise.
+const DEFAULT_RETRIES: usize = 2;
+
+/// Milliseconds before the first retry unless `qwen.retryDelay` says
+/// otherwise; each later one waits twice as long.
+const DEFAULT_RETRY_DELAY_MS: u64 = 1000;
+
+/// Longest wait between retries.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Set to 1 to use the mock backend, like `--backend mock`.
+pub const MOCK_VAR: &str = "GIT_QWEN_MOCK";
+
+/// Names the backends to try, like `qwen.backends`.
+pub const BACKENDS_VAR: &str = "GIT_QWEN_BACKENDS";
+
+/// Names the model to use, like `--model`.
+pub const MODEL_VAR: &str = "GIT_QWEN_MODEL";
+
+/// Sets the sampling temperature, like `--temperature`.
+pub const TEMPERATURE_VAR: &str = "GIT_QWEN_TEMPERATURE";
+
+/// Sets the sampling seed, like `--seed`.
+pub const SEED_VAR: &str = "GIT_QWEN_SEED";
+
+/// Base URL of the OpenAI-compatible API the qwen CLI uses.
+const OPENAI_BASE_URL_VAR: &str = "OPENAI_BASE_URL";
+
+/// Host ollama talks to.
+const OLLAMA_HOST_VAR: &str = "OLLAMA_HOST";
+
+/// Variables the backends take their server, proxy, and TLS settings
+/// from. They are passed on to the backends, also when the daemon runs
+/// them, so a prompt goes where `is_local` judged it would.
+pub const NETWORK_VARS: &[&str] = &[
+    OPENAI_BASE_URL_VAR,
+    OLLAMA_HOST_VAR,
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+    "NO_PROXY",
+    "no_proxy",
+    "NODE_EXTRA_CA_CERTS",
+    "NODE_TLS_REJECT_UNAUTHORIZED",
+    "SSL_CERT_FILE",
+    "CURL_CA_BUNDLE",
+];
+
+/// Most changed lines a diff can have for `qwen.smallModel`, unless
+/// `qwen.smallDiffLines` says otherwise.
+const DEFAULT_SMALL_DIFF_LINES: usize = 50;
+
+/// Fewest changed lines a diff needs for `qwen.largeModel`, unless
+/// `qwen.largeDiffLines` says otherwise.
+const DEFAULT_LARGE_DIFF_LINES: usize = 1000;
+
+/// What answers the prompts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// The qwen CLI
+    Qwen,
+    /// A local model run with `ollama run`
+    Ollama,
+    /// An OpenAI-compatible chat completions API, called with curl
+    OpenAi,
+    /// A canned message derived from the diff, without calling a model
+    Mock,
+}
+
+impl Backend {
+    /// The backend called `name`, as given with `--backend` or in
+    /// `qwen.backends`.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "qwen" | "qwen-cli" => Ok(Backend::Qwen),
+            "ollama" => Ok(Backend::Ollama),
+            "openai" => Ok(Backend::OpenAi),
+            "mock" => Ok(Backend::Mock),
+            other => Err(format!("Unknown backend '{}', expected qwen, ollama, openai, or mock", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Qwen => "qwen",
+            Backend::Ollama => "ollama",
+            Backend::OpenAi => "openai",
+            Backend::Mock => "mock",
+        }
+    }
+
+    /// The executable it runs, if any.
+    pub fn program(self) -> Option<&'static str> {
+        match self {
+            Backend::Qwen => Some(PROGRAM),
+            Backend::Ollama => Some(OLLAMA_PROGRAM),
+            Backend::OpenAi => Some(CURL_PROGRAM),
+            Backend::Mock => None,
+        }
+    }
+
+    /// Whether its executable, if it needs one, is in PATH.
+    pub fn is_installed(self) -> bool {
+        self.program().is_none_or(|program| capabilities::find_program(program).is_some())
+    }
+
+    /// Whether prompts stay on this machine: ollama unless `OLLAMA_HOST`
+    /// names another host, qwen and openai only when `OPENAI_BASE_URL`
+    /// points them at a server on this one, and the mock backend always.
+    pub fn is_local(self) -> bool {
+        match self {
+            Backend::Qwen | Backend::OpenAi => env::var(OPENAI_BASE_URL_VAR).is_ok_and(|url| is_loopback(&url)),
+            Backend::Ollama => env::var(OLLAMA_HOST_VAR).map_or(true, |host| host.is_empty() || is_loopback(&host)),
+            Backend::Mock => true,
+        }
+    }
+
+    /// The `host:port` its requests connect to: its server, or the proxy
+    /// set for it when the server is not on this machine. None when its
+    /// program picks the server, as qwen does without `OPENAI_BASE_URL`.
+    pub fn server(self) -> Option<String> {
+        let (url, default_port) = match self {
+            Backend::Qwen => (env::var(OPENAI_BASE_URL_VAR).ok().filter(|url| !url.is_empty())?, 443),
+            Backend::Ollama => (
+                env::var(OLLAMA_HOST_VAR)
+                    .ok()
+                    .filter(|host| !host.is_empty())
+                    .unwrap_or_else(|| DEFAULT_OLLAMA_HOST.to_string()),
+                11434,
+            ),
+            Backend::OpenAi => (openai_base_url(), 443),
+            Backend::Mock => return None,
+        };
+        if is_loopback(&url) {
+            return Some(host_port(&url, default_port));
+        }
+        let proxy = config()
+            .get(&format!("{}.proxy", self.name()))
+            .or_else(|| config().get("proxy"))
+            .map(String::from)
+            .or_else(|| ["HTTPS_PROXY", "https_proxy"].iter().find_map(|name| env::var(name).ok()))
+            .filter(|proxy| !proxy.is_empty());
+        Some(match proxy {
+            Some(proxy) => host_port(&proxy, 80),
+            None => host_port(&url, default_port),
+        })
+    }
+
+    /// Where its prompts go, for telling the user.
+    pub fn destination(self) -> String {
+        match self {
+            Backend::Qwen => match env::var(OPENAI_BASE_URL_VAR) {
+                Ok(url) if !url.is_empty() => format!("the qwen CLI, which sends them to {}", url),
+                _ => "the qwen CLI, which sends them to the model API it is signed in to".to_string(),
+            },
+            Backend::Ollama => match env::var(OLLAMA_HOST_VAR) {
+                Ok(host) if !host.is_empty() => format!("ollama at {}", host),
+                _ => "ollama on this machine".to_string(),
+            },
+            Backend::OpenAi => format!("the API at {}", openai_base_url()),
+            Backend::Mock => "the mock backend, on this machine".to_string(),
+        }
+    }
+}
+
+/// Server ollama talks to unless `OLLAMA_HOST` names another.
+const DEFAULT_OLLAMA_HOST: &str = "127.0.0.1:11434";
+
+/// The `host:port` of `url`, a URL or a `host` with or without a port,
+/// with the scheme's port or else `default_port` filled in.
+fn host_port(url: &str, default_port: u16) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let authority = rest.split('/').next().unwrap_or("");
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let has_port = match authority.rsplit_once(']') {
+        Some((_, after)) => after.starts_with(':'),
+        None => authority.contains(':'),
+    };
+    if has_port {
+        return authority.to_string();
+    }
+    let port = match scheme {
+        "https" => 443,
+        "http" => 80,
+        _ => default_port,
+    };
+    format!("{}:{}", authority, port)
+}
+
+/// Whether `url`, a URL or a `host:port`, names this machine.
+fn is_loopback(url: &str) -> bool {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or("");
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or(""),
+        None => host.split(':').next().unwrap_or(""),
+    };
+    matches!(host.to_lowercase().as_str(), "localhost" | "::1" | "0.0.0.0") || host.starts_with("127.")
+}
+
+static SELECTED: OnceLock<Vec<Backend>> = OnceLock::new();
+
+/// The backend that gave the last answer.
+static ANSWERED_BY: Mutex<Option<Backend>> = Mutex::new(None);
+
+/// Why a backend gave no answer.
+enum Failure {
+    /// The program ran and failed, which may not happen again
+    Transient(String),
+    /// Trying again would not help: the program is missing, timed out, or
+    /// was cancelled
+    Permanent(String),
+}
+
+static MODEL: OnceLock<String> = OnceLock::new();
+
+static TEMPERATURE: OnceLock<f32> = OnceLock::new();
+
+static SEED: OnceLock<u64> = OnceLock::new();
+
+/// Set once a backend was found to ignore the sampling settings, so that
+/// is only said once.
+static SAMPLING_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// How qwen samples its answer; unset values are left to qwen.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sampling {
+    pub temperature: Option<f32>,
+    pub seed: Option<u64>,
+}
+
+/// Chooses the backend by name, as given with `--backend`.
+pub fn select(name: &str) -> Result<(), String> {
+    let backend = Backend::parse(name)?;
+    let _ = SELECTED.set(vec![backend]);
+    Ok(())
+}
+
+/// The backends to try in order: the one chosen with `--backend`, else the
+/// mock one when `GIT_QWEN_MOCK=1`, else those listed in
+/// `GIT_QWEN_BACKENDS` or `qwen.backends`, else qwen.
+pub fn chain() -> &'static [Backend] {
+    SELECTED.get_or_init(|| {
+        if env::var(MOCK_VAR).is_ok_and(|value| value == "1") {
+            return vec![Backend::Mock];
+        }
+
+        let names = env::var(BACKENDS_VAR)
+            .ok()
+            .or_else(|| config().get("backends").map(String::from))
+            .unwrap_or_default();
+        let mut backends = Vec::new();
+        for name in names.split(',').filter(|name| !name.trim().is_empty()) {
+            match Backend::parse(name) {
+                Ok(backend) if !backends.contains(&backend) => backends.push(backend),
+                Ok(_) => {}
+                Err(e) => eprintln!("Warning: {} in qwen.backends", e),
+            }
+        }
+        if backends.is_empty() {
+            backends.push(Backend::Qwen);
+        }
+        backends
+    })
+}
+
+/// The backend that gave the last answer, if any did.
+pub fn answered_by() -> Option<Backend> {
+    ANSWERED_BY.lock().ok().and_then(|answered_by| *answered_by)
+}
+
+/// Uses the model `name` for every prompt, as given with `--model`.
+pub fn select_model(name: &str) {
+    let _ = MODEL.set(name.to_string());
+}
+
+/// The model given with `--model` or `GIT_QWEN_MODEL`, if any.
+pub fn selected_model() -> Option<String> {
+    MODEL.get().cloned().or_else(|| env::var(MODEL_VAR).ok().filter(|name| !name.is_empty()))
+}
+
+/// Uses `temperature` and `seed` for every prompt, as given with
+/// `--temperature` and `--seed`.
+pub fn select_sampling(temperature: Option<f32>, seed: Option<u64>) {
+    if let Some(temperature) = temperature {
+        let _ = TEMPERATURE.set(temperature);
+    }
+    if let Some(seed) = seed {
+        let _ = SEED.set(seed);
+    }
+}
+
+/// The temperature and seed given with `--temperature` and `--seed` or
+/// their environment variables, else `qwen.temperature` and `qwen.seed`.
+/// With `qwen.deterministic`, unset ones default to 0, so the same prompt
+/// gets the same answer.
+pub fn sampling() -> Sampling {
+    let config = config();
+    let temperature = TEMPERATURE
+        .get()
+        .copied()
+        .or_else(|| env::var(TEMPERATURE_VAR).ok()?.parse().ok())
+        .or_else(|| config.get("temperature")?.parse().ok());
+    let seed = SEED
+        .get()
+        .copied()
+        .or_else(|| env::var(SEED_VAR).ok()?.parse().ok())
+        .or_else(|| config.get("seed")?.parse().ok());
+
+    if config.get_bool("deterministic").unwrap_or(false) {
+        Sampling {
+            temperature: temperature.or(Some(0.0)),
+            seed: seed.or(Some(0)),
+        }
+    } else {
+        Sampling { temperature, seed }
+    }
+}
+
+/// The model that answers `prompt`: the one given with `--model`, else
+/// `qwen.smallModel` or `qwen.largeModel` when the prompt's diff is small
+/// or large enough, else `qwen.modelName`. None leaves the choice to qwen.
+pub fn model_for(prompt: &str) -> Option<String> {
+    routed_model(prompt).or_else(|| config().get("modelName").map(String::from))
+}
+
+/// The model `backend` runs for `prompt`. ollama takes `--model` and the
+/// size routing like the others, but its default is `qwen.ollamaModel`,
+/// as models are named differently there.
+pub fn backend_model(backend: Backend, prompt: &str) -> Option<String> {
+    match backend {
+        Backend::Qwen | Backend::OpenAi => model_for(prompt),
+        Backend::Ollama => Some(
+            routed_model(prompt)
+                .or_else(|| config().get("ollamaModel").map(String::from))
+                .or_else(|| config().get("modelName").map(String::from))
+                .unwrap_or_else(|| DEFAULT_OLLAMA_MODEL.to_string()),
+        ),
+        Backend::Mock => None,
+    }
+}
+
+/// The model given with `--model`, else the one `qwen.smallModel` or
+/// `qwen.largeModel` route the prompt's diff to.
+fn routed_model(prompt: &str) -> Option<String> {
+    if let Some(model) = selected_model() {
+        return Some(model);
+    }
+
+    let config = config();
+    if prompt.contains("diff --git ") {
+        let changed = changed_lines(prompt);
+        let small = config.get_usize("smallDiffLines").unwrap_or(DEFAULT_SMALL_DIFF_LINES);
+        let large = config.get_usize("largeDiffLines").unwrap_or(DEFAULT_LARGE_DIFF_LINES);
+        let routed = if changed <= small {
+            config.get("smallModel")
+        } else if changed >= large {
+            config.get("largeModel")
+        } else {
+            None
+        };
+        if let Some(model) = routed {
+            return Some(model.to_string());
+        }
+    }
+    None
+}
+
+/// The model that answered `prompt`, or would have when the answer came
+/// from the cache: the backend's model, or the backend's name when it
+/// picks the model itself.
+pub fn answering_model(prompt: &str) -> String {
+    let backend = answered_by().unwrap_or_else(|| chain()[0]);
+    backend_model(backend, prompt).unwrap_or_else(|| backend.name().to_string())
+}
+
+/// The configuration, read once for every prompt.
+fn config() -> &'static Config {
+    static CONFIG: OnceLock<Config> = OnceLock::new();
+    CONFIG.get_or_init(Config::load)
+}
+
+/// Added and removed lines in the diffs in `text`.
+fn changed_lines(text: &str) -> usize {
+    text.lines()
+        .filter(|line| {
+            (line.starts_with('+') && !line.starts_with("+++")) || (line.starts_with('-') && !line.starts_with("---"))
+        })
+        .count()
+}
+
+/// Sends `prompt` to the first backend of the chain and returns its raw
+/// output. When it fails or times out, the next one is tried, with a note
+/// on stderr saying which one answered.
+pub fn generate(prompt: &str) -> Result<String, String> {
+    let chain = allowed_chain()?;
+    let chain = chain.as_slice();
+    let mut failures = Vec::new();
+    for (i, &backend) in chain.iter().enumerate() {
+        match ask_with_retries(backend, prompt) {
+            Ok(answer) => {
+                if i > 0 {
+                    spinner::message(&format!("Answer written by {}.", backend.name()));
+                }
+                if let Ok(mut answered_by) = ANSWERED_BY.lock() {
+                    *answered_by = Some(backend);
+                }
+                return Ok(answer);
+            }
+            // A cancellation stops the whole chain
+            Err(e) if cancel::was_cancelled() || chain.len() == 1 => return Err(e),
+            Err(e) => {
+                let e = e.trim_end().to_string();
+                if let Some(next) = chain.get(i + 1) {
+                    spinner::message(&format!("{} failed ({}); trying {}...", backend.name(), e, next.name()));
+                }
+                failures.push(format!("{}: {}", backend.name(), e));
+            }
+        }
+    }
+    Err(format!("Every backend failed ({})", failures.join("; ")))
+}
+
+/// The backends of the chain that may be sent prompts: with
+/// `qwen.localOnly`, only those that run on this machine.
+pub fn allowed_chain() -> Result<Vec<Backend>, String> {
+    if !config().get_bool("localOnly").unwrap_or(false) {
+        return Ok(chain().to_vec());
+    }
+    let local: Vec<Backend> = chain().iter().copied().filter(|backend| backend.is_local()).collect();
+    if local.is_empty() {
+        let names: Vec<&str> = chain().iter().map(|backend| backend.name()).collect();
+        return Err(format!(
+            "qwen.localOnly is set and no backend runs locally ({}); use ollama, or point qwen at a local server with OPENAI_BASE_URL",
+            names.join(", ")
+        ));
+    }
+    Ok(local)
+}
+
+/// Asks `backend`, trying again after transient failures up to
+/// `qwen.retries` times and waiting twice as long before each retry. At
+/// most `qwen.<backend>.jobs` calls run on the backend at once.
+fn ask_with_retries(backend: Backend, prompt: &str) -> Result<String, String> {
+    let retries = config().get_usize("retries").unwrap_or(DEFAULT_RETRIES);
+    let delay = config().get_usize("retryDelay").map_or(DEFAULT_RETRY_DELAY_MS, |ms| ms as u64);
+    let mut delay = Duration::from_millis(delay);
+    let limit = config().get_usize(&format!("{}.jobs", backend.name()));
+
+    for attempt in 0.. {
+        let answer = {
+            let _slot = jobs::backend_slot(backend.name(), limit);
+            match backend {
+                Backend::Qwen => generate_with_qwen(prompt),
+                Backend::Ollama => generate_with_ollama(prompt),
+                Backend::OpenAi => generate_with_openai(prompt),
+                Backend::Mock => Ok(mock::generate(prompt)),
+            }
+        };
+        match answer {
+            Ok(answer) => return Ok(answer),
+            Err(Failure::Transient(e)) if attempt < retries && !cancel::was_cancelled() => {
+                spinner::message(&format!(
+                    "{} failed ({}); retrying in {:.1}s...",
+                    backend.name(),
+                    e.trim_end(),
+                    delay.as_secs_f32()
+                ));
+                cancel::sleep(delay)?;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+            Err(Failure::Transient(e) | Failure::Permanent(e)) => return Err(e),
+        }
+    }
+    unreachable!("the retries end by returning")
+}
+
+/// A command running `program`. On Windows, npm installs qwen as a
+/// `qwen.cmd` script, which `Command` only finds by its full path.
+fn command(program: &str) -> Command {
+    if cfg!(windows)
+        && let Some(path) = crate::capabilities::find_program(program)
+    {
+        return Command::new(path);
+    }
+    Command::new(program)
+}
+
+/// The proxy and TLS environment for `backend`: the proxy variables set
+/// here, overridden by `qwen.proxy`, `qwen.noProxy`, `qwen.caBundle`, and
+/// `qwen.sslVerify`. Each can be set for one backend alone, as in
+/// `qwen.ollama.caBundle`.
+fn network_env(backend: Backend) -> Result<Vec<(String, String)>, Failure> {
+    let setting = |key: &str| config().get(&format!("{}.{}", backend.name(), key)).or_else(|| config().get(key));
+    let mut vars: Vec<(String, String)> = NETWORK_VARS
+        .iter()
+        .filter_map(|&name| Some((name.to_string(), env::var(name).ok()?)))
+        .collect();
+    let mut set = |names: &[&str], value: &str| {
+        for &name in names {
+            vars.retain(|(set_name, _)| set_name != name);
+            vars.push((name.to_string(), value.to_string()));
+        }
+    };
+
+    if let Some(proxy) = setting("proxy") {
+        set(&["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"], proxy);
+    }
+    if let Some(no_proxy) = setting("noProxy") {
+        set(&["NO_PROXY", "no_proxy"], no_proxy);
+    }
+    // Node, which runs qwen, Go, which ollama is written in, and curl each
+    // read their own variable
+    if let Some(bundle) = setting("caBundle") {
+        set(&["NODE_EXTRA_CA_CERTS", "SSL_CERT_FILE", "CURL_CA_BUNDLE"], bundle);
+    }
+    if !ssl_verify(backend) {
+        match backend {
+            Backend::Qwen => set(&["NODE_TLS_REJECT_UNAUTHORIZED"], "0"),
+            // curl is told with --insecure instead
+            Backend::OpenAi => {}
+            Backend::Ollama => {
+                return Err(Failure::Permanent(
+                    "ollama cannot skip TLS verification; set qwen.ollama.caBundle to the proxy's CA certificate instead"
+                        .to_string(),
+                ));
+            }
+            Backend::Mock => {}
+        }
+    }
+    Ok(vars)
+}
+
+/// Whether `backend` checks TLS certificates, from `qwen.sslVerify` or
+/// `qwen.<backend>.sslVerify`.
+fn ssl_verify(backend: Backend) -> bool {
+    config()
+        .get_bool(&format!("{}.sslVerify", backend.name()))
+        .or_else(|| config().get_bool("sslVerify"))
+        .unwrap_or(true)
+}
+
+/// The API the openai backend talks to, without a trailing slash.
+fn openai_base_url() -> String {
+    env::var(OPENAI_BASE_URL_VAR)
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| DEFAULT_OPENAI_BASE_URL.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn generate_with_ollama(prompt: &str) -> Result<String, Failure> {
+    let model = backend_model(Backend::Ollama, prompt).unwrap_or_else(|| DEFAULT_OLLAMA_MODEL.to_string());
+    if sampling() != Sampling::default() {
+        warn_sampling_ignored("ollama run takes no sampling options; set PARAMETER temperature and seed in the model's Modelfile instead");
+    }
+    let mut command = command(OLLAMA_PROGRAM);
+    command.args(["run", &model]);
+    command.envs(network_env(Backend::Ollama)?);
+    run_program(OLLAMA_PROGRAM, &mut command, prompt)
+}
+
+fn generate_with_qwen(prompt: &str) -> Result<String, Failure> {
+    let mut command = command(PROGRAM);
+    command.envs(network_env(Backend::Qwen)?);
+    command.arg("-y");
+    if let Some(model) = model_for(prompt) {
+        command.args(["-m", &model]);
+    }
+    let sampling = sampling();
+    let options = [
+        ("--temperature", sampling.temperature.map(|temperature| temperature.to_string())),
+        ("--seed", sampling.seed.map(|seed| seed.to_string())),
+    ];
+    for (flag, value) in options {
+        let Some(value) = value else { continue };
+        if qwen_accepts(flag) {
+            command.args([flag, &value]);
+        } else {
+            warn_sampling_ignored(&format!("this qwen does not accept {}", flag));
+        }
+    }
+    run_program(PROGRAM, &mut command, prompt)
+}
+
+/// Posts `prompt` to the chat completions endpoint of an OpenAI-compatible
+/// API with curl. The request, with the API key, goes to curl's stdin as
+/// its config, so neither shows up in the process list.
+fn generate_with_openai(prompt: &str) -> Result<String, Failure> {
+    let model = backend_model(Backend::OpenAi, prompt).ok_or_else(|| {
+        Failure::Permanent("The openai backend needs a model; set qwen.modelName or pass --model".to_string())
+    })?;
+    let sampling = sampling();
+    let mut body = json!({
+        "model": model,
+        "messages": [{ "role": "user", "content": prompt }],
+    });
+    if let Some(temperature) = sampling.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(seed) = sampling.seed {
+        body["seed"] = json!(seed);
+    }
+
+    let quote = |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut request = format!(
+        "url = {}\nheader = \"Content-Type: application/json\"\n",
+        quote(&format!("{}/chat/completions", openai_base_url()))
+    );
+    let key = env::var(OPENAI_API_KEY_VAR)
+        .map(|key| key.trim().to_string())
+        .ok()
+        .filter(|key| !key.is_empty())
+        .or_else(|| keyring::secret(Backend::OpenAi.name()));
+    if let Some(key) = key {
+        request.push_str(&format!("header = {}\n", quote(&format!("Authorization: Bearer {}", key))));
+    }
+    request.push_str(&format!("data-binary = {}\n", quote(&body.to_string())));
+
+    let mut command = command(CURL_PROGRAM);
+    command.envs(network_env(Backend::OpenAi)?);
+    command.args(["--silent", "--show-error", "--fail-with-body", "--config", "-"]);
+    if !ssl_verify(Backend::OpenAi) {
+        command.arg("--insecure");
+    }
+    let reply = run_program(CURL_PROGRAM, &mut command, &request)?;
+
+    let reply: Value = serde_json::from_str(&reply)
+        .map_err(|e| Failure::Permanent(format!("The API's answer is not JSON: {}", e)))?;
+    match reply["choices"][0]["message"]["content"].as_str() {
+        Some(content) => Ok(content.to_string()),
+        None => Err(Failure::Permanent(format!(
+            "The API answered without a message: {}",
+            reply["error"]["message"].as_str().unwrap_or("no reason given")
+        ))),
+    }
+}
+
+/// Whether the installed qwen lists `flag` in its `--help`, which is read
+/// once and only when a sampling option is set.
+fn qwen_accepts(flag: &str) -> bool {
+    static HELP: OnceLock<String> = OnceLock::new();
+    let help = HELP.get_or_init(|| {
+        command(PROGRAM)
+            .arg("--help")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .unwrap_or_default()
+    });
+    help.split(|c: char| c.is_whitespace() || c == ',').any(|word| word == flag)
+}
+
+/// Says, once, that the sampling settings were not applied and why.
+fn warn_sampling_ignored(reason: &str) {
+    if !SAMPLING_WARNED.swap(true, Ordering::SeqCst) {
+        spinner::message(&format!("Warning: ignoring the temperature and seed: {}", reason));
+    }
+}
+
+/// Runs `command`, a backend's `program`, with `prompt` on its stdin and
+/// returns its stdout. A running daemon runs it when `qwen.daemon` allows.
+fn run_program(program: &str, command: &mut Command, prompt: &str) -> Result<String, Failure> {
+    // Only the call the spinner belongs to streams, not parallel ones
+    let on_output: Option<fn(&[u8])> = spinner::streams_here().then_some(spinner::stream);
+
+    let args: Vec<String> = command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+    let envs: Vec<(String, String)> = command
+        .get_envs()
+        .filter_map(|(name, value)| Some((name.to_string_lossy().into_owned(), value?.to_string_lossy().into_owned())))
+        .collect();
+    let from_daemon = if PROGRAMS.contains(&program) && config().get_bool("daemon").unwrap_or(true) {
+        daemon::generate(program, &args, &envs, prompt, timeout(), on_output)
+    } else {
+        None
+    };
+    let (success, stdout, stderr) = match from_daemon {
+        Some(reply) => {
+            let reply = reply.map_err(Failure::Permanent)?;
+            (reply.success, reply.stdout, reply.stderr)
+        }
+        None => {
+            let child = cancel::isolate(command)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| Failure::Permanent(format!("Failed to spawn {}: {}", program, e)))?;
+
+            // Write the prompt to the program's stdin and wait, allowing
+            // Ctrl+C to cancel
+            let output = cancel::wait_with_output(child, prompt.as_bytes().to_vec(), timeout(), on_output)
+                .map_err(Failure::Permanent)?;
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            (output.status.success(), output.stdout, stderr)
+        }
+    };
+
+    if !success {
+        return Err(Failure::Transient(format!("{} command failed: {}", program, stderr)));
+    }
+
+    String::from_utf8(stdout)
+        .map_err(|e| Failure::Permanent(format!("Invalid UTF-8 in {} output: {}", program, e)))
+}
+
+/// How long to wait for qwen, from `qwen.timeout` in seconds; 0 waits
+/// forever.
+fn timeout() -> Option<Duration> {
+    static TIMEOUT: OnceLock<Option<Duration>> = OnceLock::new();
+    *TIMEOUT.get_or_init(|| {
+        let secs = config().get_usize("timeout").map_or(DEFAULT_TIMEOUT_SECS, |secs| secs as u64);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    })
+}
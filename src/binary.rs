@@ -0,0 +1,137 @@
+//! Notes for binary files and Git LFS pointers, whose diffs tell the model
+//! nothing: `Binary files differ`, or an object ID and a size. The note
+//! says what kind of file it is and how its size changed, so an added
+//! image or asset can still be described.
+
+use crate::diff::{self, FileDiff};
+use crate::git;
+
+/// First line of a Git LFS pointer file.
+const LFS_VERSION: &str = "version https://git-lfs.github.com/spec/";
+
+/// File types by extension.
+const FILE_TYPES: &[(&str, &[&str])] = &[
+    ("image", &["png", "jpg", "jpeg", "gif", "webp", "bmp", "ico", "icns", "tif", "tiff", "psd", "avif", "heic"]),
+    ("video", &["mp4", "mov", "webm", "avi", "mkv"]),
+    ("audio", &["mp3", "wav", "ogg", "flac", "m4a", "aac"]),
+    ("font", &["ttf", "otf", "woff", "woff2", "eot"]),
+    ("archive", &["zip", "gz", "tgz", "tar", "xz", "bz2", "7z", "rar", "jar", "whl"]),
+    ("document", &["pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt"]),
+    ("compiled binary", &["exe", "dll", "so", "dylib", "a", "o", "lib", "wasm", "class", "pyc"]),
+    ("database", &["db", "sqlite", "sqlite3"]),
+    ("model", &["onnx", "pt", "pth", "safetensors", "gguf", "h5"]),
+];
+
+/// Replaces the sections of binary files and Git LFS pointers in `diff`
+/// with a one-line note, keeping their headers.
+pub fn summarize(diff: &str) -> String {
+    let files = diff::split_files(diff);
+    if files.is_empty() {
+        return diff.to_string();
+    }
+
+    files
+        .iter()
+        .map(|file| match note(file) {
+            Some(note) => {
+                let header = file.text.lines().next().unwrap_or_default();
+                format!("{}\n{}\n", header, note)
+            }
+            None => file.text.to_string(),
+        })
+        .collect()
+}
+
+/// The note for `file`, if it is binary or a Git LFS pointer.
+fn note(file: &FileDiff) -> Option<String> {
+    let is_binary = file
+        .text
+        .lines()
+        .any(|line| (line.starts_with("Binary files ") && line.ends_with(" differ")) || line == "GIT binary patch");
+    let (what, sizes) = if is_binary {
+        let (old, new) = blob_ids(file.text);
+        ("Binary", (old.and_then(blob_size), new.and_then(blob_size)))
+    } else {
+        ("Git LFS", lfs_sizes(file.text)?)
+    };
+
+    let sizes = match sizes {
+        (None, None) => String::new(),
+        (None, Some(new)) => format!(" ({})", human_size(new)),
+        (Some(old), None) => format!(" (was {})", human_size(old)),
+        (Some(old), Some(new)) if old == new => format!(" ({}, same size)", human_size(new)),
+        (Some(old), Some(new)) => format!(" ({} -> {})", human_size(old), human_size(new)),
+    };
+    Some(format!("{} {} {}{}", what, file_type(&file.path), file.change_kind(), sizes))
+}
+
+/// The old and new blob IDs from the `index` line, None for a side that
+/// does not exist.
+fn blob_ids(text: &str) -> (Option<&str>, Option<&str>) {
+    let Some(ids) = text.lines().find_map(|line| line.strip_prefix("index ")) else {
+        return (None, None);
+    };
+    let ids = ids.split(' ').next().unwrap_or_default();
+    let (old, new) = ids.split_once("..").unwrap_or((ids, ids));
+    let exists = |id: &&str| !id.is_empty() && !id.bytes().all(|b| b == b'0');
+    (Some(old).filter(exists), Some(new).filter(exists))
+}
+
+/// Size of the blob `id`, if it is in the object database. A file that is
+/// only in the work tree has none.
+fn blob_size(id: &str) -> Option<u64> {
+    git::output(&["cat-file", "-s", id]).ok()?.trim().parse().ok()
+}
+
+/// The sizes of the real file before and after, from the `size` lines of
+/// a changed Git LFS pointer; None if the section is not a pointer.
+fn lfs_sizes(text: &str) -> Option<(Option<u64>, Option<u64>)> {
+    let lines: Vec<&str> = text
+        .lines()
+        .skip_while(|line| !line.starts_with("@@"))
+        .filter(|line| !line.starts_with("@@"))
+        .collect();
+    if !lines.iter().any(|line| line.get(1..).is_some_and(|content| content.starts_with(LFS_VERSION))) {
+        return None;
+    }
+
+    let size = |sides: &[char]| {
+        lines.iter().find_map(|line| {
+            let mut chars = line.chars();
+            let side = chars.next()?;
+            if !sides.contains(&side) {
+                return None;
+            }
+            chars.as_str().strip_prefix("size ")?.trim().parse().ok()
+        })
+    };
+    Some((size(&['-', ' ']), size(&['+', ' '])))
+}
+
+/// What kind of file `path` is, from its extension.
+fn file_type(path: &str) -> &'static str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let Some((_, extension)) = name.rsplit_once('.') else {
+        return "file";
+    };
+    let extension = extension.to_lowercase();
+    FILE_TYPES
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&extension.as_str()))
+        .map_or("file", |(file_type, _)| file_type)
+}
+
+/// `bytes` in the largest unit that keeps it at least 1, like `12.3 KB`.
+fn human_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} bytes", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
@@ -0,0 +1,162 @@
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use crate::prompt;
+use regex::Regex;
+
+/// Matches Jira-style keys such as `JIRA-1234`.
+const DEFAULT_TICKET_PATTERN: &str = r"[A-Z][A-Z0-9]+-[0-9]+";
+
+/// Branch names that say nothing about the change being made.
+const MAINLINE_BRANCHES: &[&str] = &["main", "master", "trunk", "develop", "development"];
+
+const BRANCH_PROMPT: &str = "Suggest a name for a git branch for the work described below. Use the form <type>/<summary>, where type is one of feat, fix, docs, refactor, perf, test, or chore, and summary is two to five lowercase words joined by hyphens, such as feat/add-retry-backoff. Answer with only the branch name.
+
+";
+
+/// The checked-out branch, or None when HEAD is detached.
+pub fn current() -> Option<String> {
+    git::output(&["symbolic-ref", "--short", "-q", "HEAD"])
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Describes the current branch for the prompt, e.g. for
+/// `feature/JIRA-1234-add-retry`. Mainline branches are left out.
+pub fn context(config: &Config) -> Option<String> {
+    let branch = current().filter(|name| !MAINLINE_BRANCHES.contains(&name.as_str()))?;
+
+    let mut context = "The change was made on the branch below, whose name may hint at its purpose.\n".to_string();
+    context.push_str(&prompt::fence(&branch));
+    if let Some(ticket) = ticket(&branch, config) {
+        if footer(config).is_some() {
+            context.push_str(&format!(
+                "It belongs to ticket {}; a reference to it is added automatically, so do not mention it.\n",
+                ticket
+            ));
+        } else {
+            context.push_str(&format!("It belongs to ticket {}.\n", ticket));
+        }
+    }
+    Some(context)
+}
+
+/// The ticket referenced by a branch name, found with the regular
+/// expression in `qwen.ticketPattern`. If the pattern has a capture group,
+/// the first group is the ticket; otherwise the whole match is.
+pub fn ticket(branch: &str, config: &Config) -> Option<String> {
+    let pattern = config.get("ticketPattern").unwrap_or(DEFAULT_TICKET_PATTERN);
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(e) => {
+            eprintln!("Warning: ignoring invalid qwen.ticketPattern '{}': {}", pattern, e);
+            return None;
+        }
+    };
+
+    let captures = regex.captures(branch)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map(|m| m.as_str().to_string())
+}
+
+/// The footer for the current branch's ticket, built from the
+/// `qwen.ticketFooter` template (e.g. `Refs: {ticket}` or
+/// `Closes #{ticket}`). None unless the template is set and the branch
+/// names a ticket.
+pub fn ticket_footer(config: &Config) -> Option<String> {
+    let template = footer(config)?;
+    let ticket = ticket(&current()?, config)?;
+    Some(template.replace("{ticket}", &ticket))
+}
+
+fn footer(config: &Config) -> Option<&str> {
+    config.get("ticketFooter").filter(|template| !template.trim().is_empty())
+}
+
+/// Suggests a branch name for `intent` and the uncommitted changes, and
+/// with `create` switches to a new branch of that name, keeping the
+/// changes.
+pub fn run(intent: &str, create: bool) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+
+    let diff = match git::output(&["diff", "HEAD"]) {
+        Ok(diff) => diff,
+        // No commits yet: everything is staged or untracked
+        Err(_) => git::output(&["diff", "--cached"])?,
+    };
+    if intent.trim().is_empty() && diff.trim().is_empty() {
+        return Err("No changes to name a branch after; describe the work instead".to_string());
+    }
+
+    let mut prompt = format!("{}{}", prompt::DATA_NOTICE.trim_start(), BRANCH_PROMPT);
+    if !intent.trim().is_empty() {
+        prompt.push_str(&format!("The work: {}\n\n", intent.trim()));
+    }
+    let ticket = ticket(intent, &config);
+    if let Some(ticket) = &ticket {
+        prompt.push_str(&format!("Include the ticket {} after the slash, as in feat/{}-add-retry.\n\n", ticket, ticket));
+    }
+    if !diff.trim().is_empty() {
+        let diff = prompt::prepare_diff(&diff, &config);
+        prompt.push_str("The changes so far:\n");
+        prompt.push_str(&prompt::fenced_fit(&diff, prompt.len(), &config));
+    }
+
+    let answer = backend::generate(&prompt).map_err(|e| format!("Failed to suggest a branch name: {}", e))?;
+    let name = unused(&sanitize(&answer, ticket.as_deref()).ok_or("qwen did not suggest a usable branch name")?);
+
+    if !create {
+        println!("{}", name);
+        return Ok(());
+    }
+    git::output(&["switch", "-c", &name])?;
+    eprintln!("Switched to a new branch '{}'", name);
+    Ok(())
+}
+
+/// Turns the model's answer into a valid branch name: the text in
+/// backticks, or the word with a `/`, or the whole first line, with spaces
+/// and anything other than letters, digits, `/`, `-`, `_`, and `.` turned
+/// into hyphens. The name is lowercased, except for the `ticket`.
+fn sanitize(answer: &str, ticket: Option<&str>) -> Option<String> {
+    let text = crate::output::clean_document(answer);
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let quoted = line.split('`').nth(1).filter(|quoted| !quoted.trim().is_empty());
+    let candidate = match quoted.or_else(|| line.split_whitespace().find(|word| word.contains('/'))) {
+        Some(candidate) => candidate,
+        None => line,
+    };
+    let candidate = candidate.trim().trim_matches(|c: char| "'\"*.:!".contains(c));
+
+    let mut name = String::new();
+    for c in candidate.to_lowercase().chars() {
+        let c = if c.is_alphanumeric() || "/-_.".contains(c) { c } else { '-' };
+        if !(c == '-' && name.ends_with('-')) {
+            name.push(c);
+        }
+    }
+    let mut name = name.trim_matches(['-', '/', '.']).to_string();
+    if let Some(ticket) = ticket {
+        name = name.replace(&ticket.to_lowercase(), ticket);
+    }
+
+    git::output(&["check-ref-format", "--branch", &name])
+        .ok()
+        .map(|name| name.trim().to_string())
+}
+
+/// `name`, or if a branch of that name exists, `name-2`, `name-3`, ...
+fn unused(name: &str) -> String {
+    let exists = |name: &str| git::output(&["rev-parse", "--verify", "--quiet", &format!("refs/heads/{}", name)]).is_ok();
+    if !exists(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", name, n))
+        .find(|candidate| !exists(candidate))
+        .expect("some suffix is unused")
+}
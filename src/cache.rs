@@ -0,0 +1,57 @@
+//! Generated messages kept in `.git/qwen-cache/messages/`, keyed by a hash
+//! of the prompt, so running the same command again on the same changes,
+//! such as after aborting the editor or a rejected hook, reuses the
+//! message instead of asking the model again.
+
+use crate::backend;
+use crate::git;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Messages kept; older ones are removed when a new one is stored.
+const MAX_ENTRIES: usize = 50;
+
+/// The message generated earlier for `prompt`, if there is one.
+pub fn get(prompt: &str) -> Option<String> {
+    fs::read_to_string(path(prompt)?).ok().filter(|message| !message.trim().is_empty())
+}
+
+/// Stores `message` as the answer to `prompt`. Failures are ignored; the
+/// cache only saves time.
+pub fn put(prompt: &str, message: &str) {
+    let Some(path) = path(prompt) else {
+        return;
+    };
+    if let Some(dir) = path.parent()
+        && fs::create_dir_all(dir).is_ok()
+        && fs::write(&path, message).is_ok()
+    {
+        prune(dir);
+    }
+}
+
+/// The cache file for `prompt`. The backend, model, and sampling are part
+/// of the key, so mock answers are never mistaken for real ones, nor one
+/// model's for another's.
+fn path(prompt: &str) -> Option<PathBuf> {
+    let model = backend::model_for(prompt).unwrap_or_default();
+    let key = format!("{:?}\n{}\n{:?}\n{}", backend::chain(), model, backend::sampling(), prompt);
+    let hash = git::output_with_input(&["hash-object", "--stdin"], &key).ok()?;
+    Some(git::cache_dir().ok()?.join("messages").join(hash.trim()))
+}
+
+/// Removes all but the newest `MAX_ENTRIES` messages.
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in files.into_iter().skip(MAX_ENTRIES) {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,252 @@
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Number of model calls in flight; while non-zero, Ctrl+C cancels them
+/// instead of killing git-qwen.
+static GENERATING: AtomicUsize = AtomicUsize::new(0);
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Files and directories removed if git-qwen is interrupted, such as the
+/// message file.
+static REMOVE_ON_INTERRUPT: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Exit code used by shells for a process interrupted by SIGINT.
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// What to do after the user cancelled a generation.
+pub enum AfterCancel {
+    Retry,
+    EditEmpty,
+    Abort,
+}
+
+/// Installs the Ctrl+C / SIGTERM handler. Outside of generation it exits
+/// like the default handler would.
+pub fn install_handler() {
+    let result = ctrlc::set_handler(|| {
+        if GENERATING.load(Ordering::SeqCst) > 0 {
+            CANCELLED.store(true, Ordering::SeqCst);
+        } else {
+            exit_interrupted();
+        }
+    });
+
+    if let Err(e) = result {
+        eprintln!("Warning: Failed to install Ctrl+C handler: {}", e);
+    }
+}
+
+/// Removes `path`, a file or a directory, if git-qwen is interrupted from
+/// now on.
+pub fn remove_on_interrupt(path: &Path) {
+    if let Ok(mut paths) = REMOVE_ON_INTERRUPT.lock() {
+        paths.push(path.to_path_buf());
+    }
+}
+
+/// Stops removing `path` on interrupt, once it has been removed already.
+pub fn keep_on_interrupt(path: &Path) {
+    if let Ok(mut paths) = REMOVE_ON_INTERRUPT.lock() {
+        paths.retain(|registered| registered != path);
+    }
+}
+
+/// Exits as interrupted, removing what was registered with
+/// `remove_on_interrupt`.
+pub fn exit_interrupted() -> ! {
+    let paths = REMOVE_ON_INTERRUPT.lock().map(|mut paths| std::mem::take(&mut *paths)).unwrap_or_default();
+    for path in paths {
+        if path.is_dir() {
+            let _ = fs::remove_dir_all(path);
+        } else {
+            let _ = fs::remove_file(path);
+        }
+    }
+    std::process::exit(INTERRUPTED_EXIT_CODE);
+}
+
+/// Whether the last generation ended because the user cancelled it.
+pub fn was_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Waits for `duration`, or fails as soon as the user cancels.
+pub fn sleep(duration: Duration) -> Result<(), String> {
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        if was_cancelled() {
+            return Err("generation cancelled".to_string());
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now())));
+    }
+    Ok(())
+}
+
+/// Starts the child in its own process group, so terminal signals reach
+/// only git-qwen and cancelling can take down everything the child spawned.
+pub fn isolate(command: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command
+}
+
+/// Feeds `input` to the child and waits for it to exit, killing and
+/// reaping it if the user cancels in the meantime or it runs longer than
+/// `timeout`. Its stdout is also passed to `on_output` as it arrives.
+/// Several children may be waited on at once; a cancellation stops all of
+/// them.
+pub fn wait_with_output(
+    mut child: Child,
+    input: Vec<u8>,
+    timeout: Option<Duration>,
+    on_output: Option<fn(&[u8])>,
+) -> Result<Output, String> {
+    cancellable(|| wait_cancellable(&mut child, input, timeout, on_output))
+}
+
+/// Runs `wait`, during which Ctrl+C cancels the generation instead of
+/// killing git-qwen; `wait` polls `was_cancelled` to notice.
+pub fn cancellable<T>(wait: impl FnOnce() -> T) -> T {
+    if GENERATING.fetch_add(1, Ordering::SeqCst) == 0 {
+        CANCELLED.store(false, Ordering::SeqCst);
+    }
+    let result = wait();
+    GENERATING.fetch_sub(1, Ordering::SeqCst);
+    result
+}
+
+fn wait_cancellable(
+    child: &mut Child,
+    input: Vec<u8>,
+    timeout: Option<Duration>,
+    on_output: Option<fn(&[u8])>,
+) -> Result<Output, String> {
+    let started = Instant::now();
+    // Feed stdin and drain stdout/stderr on threads so a large prompt or a
+    // chatty child can never fill a pipe and block the polling loop
+    let stdin = child.stdin.take();
+    let writer = thread::spawn(move || match stdin {
+        Some(mut stdin) => stdin.write_all(&input),
+        None => Ok(()),
+    });
+    let stdout = read_in_background(child.stdout.take(), on_output);
+    let stderr = read_in_background(child.stderr.take(), None);
+
+    let status = loop {
+        if was_cancelled() {
+            // Kill and reap the child so it does not linger as a zombie
+            kill_tree(child);
+            let _ = child.wait();
+            return Err("generation cancelled".to_string());
+        }
+        if let Some(timeout) = timeout
+            && started.elapsed() >= timeout
+        {
+            kill_tree(child);
+            let _ = child.wait();
+            return Err(format!("timed out after {} seconds (see qwen.timeout)", timeout.as_secs()));
+        }
+
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(format!("Failed to wait for child process: {}", e)),
+        }
+    };
+
+    let write_result = writer.join().unwrap_or(Ok(()));
+    let output = Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    };
+
+    // A child that exits early closes its stdin; that only matters if it
+    // also failed without saying why
+    if let Err(e) = write_result
+        && !output.status.success()
+        && output.stderr.is_empty()
+    {
+        return Err(format!("Failed to write to child stdin: {}", e));
+    }
+
+    Ok(output)
+}
+
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        // The child leads its own process group (see `isolate`), so this
+        // also stops any helpers it started
+        // SAFETY: kill has no memory safety requirements
+        unsafe {
+            libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+        }
+    }
+    let _ = child.kill();
+}
+
+fn read_in_background<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    on_output: Option<fn(&[u8])>,
+) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let Some(mut pipe) = pipe else {
+            return buffer;
+        };
+        let mut chunk = [0; 4096];
+        loop {
+            match pipe.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => {
+                    if let Some(on_output) = on_output {
+                        on_output(&chunk[..read]);
+                    }
+                    buffer.extend_from_slice(&chunk[..read]);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+        buffer
+    })
+}
+
+/// Asks the user how to continue after cancelling. Without a terminal
+/// there is nobody to ask, so the commit is aborted.
+pub fn ask_after_cancel() -> AfterCancel {
+    CANCELLED.store(false, Ordering::SeqCst);
+
+    if !io::stdin().is_terminal() {
+        return AfterCancel::Abort;
+    }
+
+    loop {
+        eprint!("Generation cancelled. [r]etry, [e]dit an empty message, or [a]bort? ");
+        let _ = io::stderr().flush();
+
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer).unwrap_or(0) == 0 {
+            return AfterCancel::Abort;
+        }
+
+        match answer.trim().to_lowercase().as_str() {
+            "r" | "retry" => return AfterCancel::Retry,
+            "e" | "edit" => return AfterCancel::EditEmpty,
+            "a" | "abort" | "" => return AfterCancel::Abort,
+            _ => {}
+        }
+    }
+}
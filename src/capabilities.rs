@@ -0,0 +1,192 @@
+//! Detection of optional capabilities, done up front so features can be
+//! turned off with a clear message instead of failing halfway through.
+//! Nothing is sent anywhere: the network check only opens a connection to
+//! each backend's server and closes it again, and the clipboard and
+//! keyring tools are only looked up, not run.
+
+use crate::backend::{self, Backend};
+use crate::error::Error;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long the network check waits for a server to accept a connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// What the current environment supports.
+pub struct Capabilities {
+    /// Standard input and error are both terminals, so prompts can be asked
+    pub interactive: bool,
+    /// Running inside an SSH session
+    pub ssh: bool,
+    /// Tool used to copy text to the clipboard, or "osc52" when the
+    /// terminal escape sequence is the only option
+    pub clipboard: Option<&'static str>,
+    /// Tool used to read credentials from the system keyring
+    pub keyring: Option<&'static str>,
+    /// The configured backends in the order they are tried, each with the
+    /// path of its executable if it needs one and it is installed
+    pub backends: Vec<(Backend, Option<PathBuf>)>,
+    /// The servers the backends connect to, and whether each accepted a
+    /// connection; backends whose program picks its server are left out
+    pub network: Vec<Server>,
+}
+
+/// A backend's server, as found by the network check.
+pub struct Server {
+    pub backend: Backend,
+    /// `host:port`, of the proxy when the backend goes through one
+    pub address: String,
+    pub reachable: bool,
+}
+
+impl Capabilities {
+    pub fn detect() -> Self {
+        let network = backend::chain()
+            .iter()
+            .filter_map(|&backend| {
+                let address = backend.server()?;
+                let reachable = reachable(&address);
+                Some(Server { backend, address, reachable })
+            })
+            .collect();
+
+        Capabilities {
+            interactive: is_interactive(),
+            ssh: is_ssh(),
+            clipboard: clipboard(),
+            keyring: keyring(),
+            backends: backend::chain()
+                .iter()
+                .map(|&backend| (backend, backend.program().and_then(find_program)))
+                .collect(),
+            network,
+        }
+    }
+
+    /// Human-readable notes about features that fall back or are disabled.
+    pub fn notes(&self) -> Vec<String> {
+        let mut notes = Vec::new();
+        if !self.interactive {
+            notes.push("no terminal; cancelled generations abort instead of asking".to_string());
+        }
+        match self.clipboard {
+            Some("osc52") if self.ssh => notes.push("clipboard unavailable over SSH; using OSC52".to_string()),
+            Some("osc52") => notes.push("no clipboard tool found; using OSC52".to_string()),
+            None => notes.push("clipboard unavailable; pr --copy cannot be used".to_string()),
+            Some(_) => {}
+        }
+        if self.keyring.is_none() && self.backends.iter().any(|(backend, _)| *backend == Backend::OpenAi) {
+            notes.push("no system keyring tool found; the openai backend reads its key only from OPENAI_API_KEY".to_string());
+        }
+        notes
+    }
+}
+
+fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+fn is_ssh() -> bool {
+    env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some()
+}
+
+/// Whether `address`, a `host:port`, accepts a TCP connection.
+fn reachable(address: &str) -> bool {
+    let Ok(addresses) = address.to_socket_addrs() else {
+        return false;
+    };
+    addresses
+        .into_iter()
+        .any(|address| TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).is_ok())
+}
+
+/// Fails early, before any prompt is built, when the backend is missing or
+/// the changes may not be sent where it would send them.
+pub fn require_backend() -> Result<(), Error> {
+    let chain = backend::chain();
+    if !chain.iter().any(|backend| backend.is_installed()) {
+        let program = chain[0].program().unwrap_or(backend::PROGRAM);
+        return Err(Error::ModelUnavailable(format!(
+            "{} CLI not found in PATH; install it or run `git qwen doctor` for details",
+            program
+        )));
+    }
+    crate::remote::confirm()
+}
+
+/// The tool used to copy text to the clipboard, or "osc52" when only the
+/// terminal escape sequence can reach it.
+pub fn clipboard() -> Option<&'static str> {
+    // A local clipboard tool would copy on the remote machine, which is
+    // never what an SSH user wants
+    if !is_ssh() {
+        let candidates: &[&'static str] = if cfg!(target_os = "macos") {
+            &["pbcopy"]
+        } else if cfg!(windows) {
+            &["clip"]
+        } else if env::var_os("WAYLAND_DISPLAY").is_some() {
+            &["wl-copy", "xclip", "xsel"]
+        } else if env::var_os("DISPLAY").is_some() {
+            &["xclip", "xsel"]
+        } else {
+            &[]
+        };
+
+        if let Some(tool) = candidates.iter().find(|tool| find_program(tool).is_some()) {
+            return Some(tool);
+        }
+    }
+
+    // Most terminal emulators forward OSC 52 to the local clipboard
+    is_interactive().then_some("osc52")
+}
+
+/// The tool that reads secrets from the system keyring. Windows has none
+/// that prints a stored password, so it is only looked for elsewhere.
+pub fn keyring() -> Option<&'static str> {
+    let candidates: &[&'static str] = if cfg!(target_os = "macos") {
+        &["security"]
+    } else if cfg!(windows) {
+        &[]
+    } else {
+        &["secret-tool"]
+    };
+    candidates.iter().copied().find(|tool| find_program(tool).is_some())
+}
+
+/// Looks up an executable in PATH without running it.
+pub fn find_program(name: &str) -> Option<PathBuf> {
+    let extensions: Vec<String> = if cfg!(windows) {
+        env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string())
+            .split(';')
+            .map(|ext| ext.to_string())
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+
+    env::split_paths(&env::var_os("PATH")?).find_map(|dir| {
+        extensions.iter().find_map(|ext| {
+            let path = dir.join(format!("{}{}", name, ext));
+            is_executable(&path).then_some(path)
+        })
+    })
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
@@ -0,0 +1,209 @@
+//! `git-qwen changelog [RANGE]`: writes a CHANGELOG section for a range of
+//! commits, grouped by the kind of change. Ranges too long for one prompt
+//! are first summarized in batches, several at once.
+
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use crate::history::{self, Commit};
+use crate::jobs::{self, Journal};
+use crate::output;
+use crate::prompt;
+use std::fs;
+use std::path::Path;
+
+/// File `--write` inserts the section into, at the repository root.
+const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+const CHANGELOG_PROMPT: &str = "Write the body of a CHANGELOG section for a release made of the commits listed below. Group the changes under the headings given here, leaving out headings with nothing under them. Write one bullet per user-visible change, starting with \"- \", as a short sentence for people using the project; merge commits that belong to the same change, and leave out changes users will not notice, such as refactoring, tests, and CI. Start directly with the first heading, and answer with only the markdown.
+
+";
+
+const BATCH_PROMPT: &str = "List the user-visible changes made by the commits below, one short bullet per change starting with \"- \", keeping any type and scope prefixes such as feat(parser):. Merge commits that belong to the same change, and leave out changes users will not notice, such as refactoring, tests, and CI. Answer with only the bullets, or with nothing if no change is visible to users.
+
+";
+
+/// Journal of the notes written for batches of commits, by the names of
+/// the commits in them.
+const JOURNAL: &str = "changelog";
+
+/// Layout of the generated section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// keepachangelog.com: Added, Changed, Fixed, ... headings
+    KeepAChangelog,
+    /// conventional-changelog: Features, Bug Fixes, ... with bold scopes
+    Conventional,
+}
+
+impl Style {
+    /// Reads a style name as given to `--style`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "keep-a-changelog" | "keepachangelog" => Some(Style::KeepAChangelog),
+            "conventional" => Some(Style::Conventional),
+            _ => None,
+        }
+    }
+
+    /// Reads `qwen.changelogStyle`, defaulting to keep-a-changelog.
+    pub fn from_config(config: &Config) -> Self {
+        match config.get("changelogStyle") {
+            None => Style::KeepAChangelog,
+            Some(name) => Style::from_name(name).unwrap_or_else(|| {
+                eprintln!(
+                    "Warning: ignoring invalid qwen.changelogStyle '{}', expected keep-a-changelog or conventional",
+                    name
+                );
+                Style::KeepAChangelog
+            }),
+        }
+    }
+
+    fn headings(self) -> &'static str {
+        match self {
+            Style::KeepAChangelog => {
+                "### Added, ### Changed, ### Deprecated, ### Removed, ### Fixed, ### Security (in that order). Start a bullet with the component in bold, like \"- **parser:** ...\", only when the commit names one.\n"
+            }
+            Style::Conventional => {
+                "### ⚠ BREAKING CHANGES, ### Features, ### Bug Fixes, ### Performance Improvements, ### Reverts (in that order). Take the type and scope from prefixes such as feat(parser): where commits have them, and start each bullet with its scope in bold, like \"- **parser:** ...\".\n"
+            }
+        }
+    }
+
+    /// The heading of the section for `version` released on `date`.
+    fn heading(self, version: Option<&str>, date: &str) -> String {
+        match (self, version) {
+            (Style::KeepAChangelog, Some(version)) => format!("## [{}] - {}", version, date),
+            (Style::KeepAChangelog, None) => "## [Unreleased]".to_string(),
+            (Style::Conventional, Some(version)) => format!("## {} ({})", version, date),
+            (Style::Conventional, None) => format!("## Unreleased ({})", date),
+        }
+    }
+}
+
+/// Prints the section for `range` (`A..B`, or `A` for `A..HEAD`; by default
+/// the commits since the latest tag), headed by `version` or "Unreleased".
+/// With `write`, the section is inserted into CHANGELOG.md instead.
+pub fn run(range: Option<&str>, version: Option<&str>, style: Option<&str>, write: bool) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+    let style = match style {
+        Some(name) => Style::from_name(name)
+            .ok_or_else(|| format!("Unknown changelog style '{}', expected keep-a-changelog or conventional", name))?,
+        None => Style::from_config(&config),
+    };
+
+    let (log_range, end) = match range {
+        Some(range) => {
+            let (base, end) = history::split_range(range);
+            (format!("{}..{}", base, end), end.to_string())
+        }
+        None => match history::latest_tag("HEAD") {
+            Some(tag) => (format!("{}..HEAD", tag), "HEAD".to_string()),
+            None => ("HEAD".to_string(), "HEAD".to_string()),
+        },
+    };
+    let commits = history::commits(&log_range)?;
+    if commits.is_empty() {
+        return Err(format!("No commits in {} to describe", log_range));
+    }
+    eprintln!("Summarizing {} commit(s) in {}...", commits.len(), log_range);
+
+    let mut prompt = format!("{}{}Headings: {}\n", prompt::DATA_NOTICE.trim_start(), CHANGELOG_PROMPT, style.headings());
+    let budget = prompt::max_chars(&config)
+        .map(|max| max.saturating_sub(prompt.len() + prompt::DATA_NOTICE.len() + BATCH_PROMPT.len() + prompt::FENCE_CHARS));
+    let listing = history::prompt_listing(&commits, None);
+    match budget {
+        Some(budget) if listing.len() > budget => {
+            let notes = batch_notes(&commits, budget, &config)?;
+            prompt.push_str("There are too many commits to list, so here are notes on their changes, oldest first:\n");
+            prompt.push_str(&prompt::fence(&notes));
+        }
+        _ => {
+            prompt.push_str("Commits, oldest first:\n");
+            prompt.push_str(&prompt::fence(&listing));
+        }
+    }
+
+    let answer = backend::generate(&prompt).map_err(|e| format!("Failed to generate changelog: {}", e))?;
+    let body = output::clean_document(&answer);
+    if body.is_empty() {
+        return Err("qwen returned an empty changelog".to_string());
+    }
+
+    let date = git::output(&["log", "-1", "--format=%cs", &end])?;
+    let section = format!("{}\n\n{}\n", style.heading(version, date.trim()), body);
+
+    if !write {
+        print!("{}", section);
+        return Ok(());
+    }
+
+    let root = git::output(&["rev-parse", "--show-toplevel"])?;
+    let path = Path::new(root.trim()).join(CHANGELOG_FILE);
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    fs::write(&path, insert(&existing, &section))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    eprintln!("Added the section to {}", path.display());
+    Ok(())
+}
+
+/// Notes on the user-visible changes in `commits`, written for batches of
+/// commits whose listing fits `budget`, up to `qwen.jobs` batches at once.
+fn batch_notes(commits: &[Commit], budget: usize, config: &Config) -> Result<String, String> {
+    let mut batches: Vec<&[Commit]> = Vec::new();
+    let mut start = 0;
+    let mut size = 0;
+    for (index, commit) in commits.iter().enumerate() {
+        let entry = history::prompt_listing(std::slice::from_ref(commit), None).len();
+        if index > start && size + entry > budget {
+            batches.push(&commits[start..index]);
+            start = index;
+            size = 0;
+        }
+        size += entry;
+    }
+    batches.push(&commits[start..]);
+
+    let journal = Journal::open(JOURNAL)?;
+    let limit = config.get_usize("jobs").unwrap_or(jobs::DEFAULT_JOBS);
+    let notes = jobs::run_resumable(
+        &journal,
+        &batches,
+        limit,
+        "Summarizing commits",
+        |batch| batch.iter().map(|commit| commit.id.as_str()).collect::<Vec<_>>().join(" "),
+        |batch| {
+            let prompt = format!(
+                "{}{}{}",
+                prompt::DATA_NOTICE.trim_start(),
+                BATCH_PROMPT,
+                prompt::fence(&history::prompt_listing(batch, Some(budget)))
+            );
+            let answer = backend::generate(&prompt).map_err(|e| format!("Failed to summarize commits: {}", e))?;
+            Ok(output::clean_document(&answer))
+        },
+    )?;
+    journal.remove();
+
+    let notes: Vec<String> = notes.into_iter().filter(|notes| !notes.is_empty()).collect();
+    Ok(format!("{}\n", notes.join("\n")))
+}
+
+/// Puts `section` before the first release heading of `changelog`, below
+/// its title and introduction, or starts a new changelog.
+fn insert(changelog: &str, section: &str) -> String {
+    if changelog.trim().is_empty() {
+        return format!("# Changelog\n\n{}", section);
+    }
+
+    let mut offset = 0;
+    for line in changelog.split_inclusive('\n') {
+        if line.starts_with("## ") {
+            return format!("{}{}\n{}", &changelog[..offset], section, &changelog[offset..]);
+        }
+        offset += line.len();
+    }
+    format!("{}\n\n{}", changelog.trim_end(), section)
+}
@@ -0,0 +1,192 @@
+//! How the edited message is cleaned up, following git's `commit.cleanup`
+//! modes and comment character.
+
+use crate::git;
+
+/// The line above which the message ends in scissors mode.
+pub const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// Characters git tries, in order, when `core.commentChar` is `auto`.
+const AUTO_COMMENT_CHARS: &str = "#;@!$%^&|:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Remove comments and surplus whitespace
+    Strip,
+    /// Remove surplus whitespace only
+    Whitespace,
+    /// Keep the message as written
+    Verbatim,
+    /// Like whitespace, and drop everything below the scissors line
+    Scissors,
+}
+
+impl Mode {
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Strip => "strip",
+            Mode::Whitespace => "whitespace",
+            Mode::Verbatim => "verbatim",
+            Mode::Scissors => "scissors",
+        }
+    }
+}
+
+/// The cleanup mode and comment string in effect for one commit.
+pub struct Cleanup {
+    pub mode: Mode,
+    pub comment: String,
+    /// The editor shows the diff below a scissors line (`-v`)
+    pub verbose: bool,
+    /// Whether the comment string was picked because it was set to `auto`,
+    /// in which case git has to be told which one was used
+    auto_comment: bool,
+}
+
+impl Cleanup {
+    /// Resolves the mode from `--cleanup` (`option`), then `commit.cleanup`,
+    /// and the comment string from `core.commentString` or
+    /// `core.commentChar`. With `auto`, the first of git's candidates that
+    /// starts no line of `message` is used.
+    pub fn resolve(option: Option<&str>, verbose: bool, message: &str) -> Result<Self, String> {
+        let configured = option.map(String::from).or_else(|| git_config("commit.cleanup"));
+        let mode = match configured.as_deref().unwrap_or("default") {
+            // The message is always edited, and git strips edited messages
+            "default" | "strip" => Mode::Strip,
+            "whitespace" => Mode::Whitespace,
+            "verbatim" => Mode::Verbatim,
+            "scissors" => Mode::Scissors,
+            other => return Err(format!("Invalid cleanup mode {}", other)),
+        };
+
+        let comment = git_config("core.commentString")
+            .or_else(|| git_config("core.commentChar"))
+            .unwrap_or_else(|| "#".to_string());
+        let (comment, auto_comment) = if comment == "auto" {
+            (auto_comment_char(message), true)
+        } else {
+            (comment, false)
+        };
+
+        Ok(Cleanup {
+            mode,
+            comment,
+            verbose,
+            auto_comment,
+        })
+    }
+
+    /// Formats `text` as a comment line, like git's template comments.
+    pub fn comment_line(&self, text: &str) -> String {
+        if text.is_empty() {
+            self.comment.clone()
+        } else {
+            format!("{} {}", self.comment, text)
+        }
+    }
+
+    /// The lines that open the part of the file git ignores.
+    pub fn scissors_lines() -> Vec<String> {
+        vec![
+            SCISSORS.to_string(),
+            "Do not modify or remove the line above.".to_string(),
+            "Everything below it will be ignored.".to_string(),
+        ]
+    }
+
+    /// The instructions git shows above the status in the editor.
+    pub fn help_lines(&self) -> Vec<String> {
+        if self.mode == Mode::Scissors {
+            return Self::scissors_lines();
+        }
+
+        let mut lines = Vec::new();
+        lines.push("Please enter the commit message for your changes. Lines starting".to_string());
+        if self.mode == Mode::Strip {
+            lines.push(format!(
+                "with '{}' will be ignored, and an empty message aborts the commit.",
+                self.comment
+            ));
+        } else {
+            lines.push(format!(
+                "with '{}' will be kept; you may remove them yourself if you want to.",
+                self.comment
+            ));
+            lines.push("An empty message aborts the commit.".to_string());
+        }
+        lines
+    }
+
+    /// Cleans up the edited message as git would. Everything below the
+    /// scissors line is dropped first in verbose mode, whatever the mode.
+    pub fn clean(&self, edited: &str) -> Result<String, String> {
+        let edited = if self.verbose {
+            cut_at_scissors(edited, &self.comment)
+        } else {
+            edited
+        };
+
+        let comment_config = self.comment_config();
+        let mut args: Vec<&str> = Vec::new();
+        if let Some(config) = &comment_config {
+            args.extend(["-c", config]);
+        }
+        args.push("stripspace");
+
+        match self.mode {
+            Mode::Verbatim => Ok(edited.to_string()),
+            Mode::Whitespace => git::output_with_input(&args, edited),
+            Mode::Scissors => git::output_with_input(&args, cut_at_scissors(edited, &self.comment)),
+            Mode::Strip => {
+                args.push("--strip-comments");
+                git::output_with_input(&args, edited)
+            }
+        }
+    }
+
+    /// Options for `git commit` that make it clean up the message the same
+    /// way. The message is already clean, so this only matters if git opens
+    /// the editor again (`-e`).
+    pub fn commit_option(&self) -> String {
+        format!("--cleanup={}", self.mode.name())
+    }
+
+    /// A `-c` setting passing the comment string picked for `auto` on to
+    /// git, which only picks one itself when it opens the editor.
+    pub fn comment_config(&self) -> Option<String> {
+        self.auto_comment
+            .then(|| format!("core.commentChar={}", self.comment))
+    }
+}
+
+/// Drops the scissors line and everything below it.
+pub fn cut_at_scissors<'a>(message: &'a str, comment: &str) -> &'a str {
+    let scissors = format!("{} {}", comment, SCISSORS);
+    let mut offset = 0;
+    for line in message.split_inclusive('\n') {
+        if line.trim_end() == scissors {
+            return &message[..offset];
+        }
+        offset += line.len();
+    }
+    message
+}
+
+fn auto_comment_char(message: &str) -> String {
+    let used: Vec<char> = message
+        .lines()
+        .filter_map(|line| line.trim_start().chars().next())
+        .collect();
+    AUTO_COMMENT_CHARS
+        .chars()
+        .find(|c| !used.contains(c))
+        .unwrap_or('#')
+        .to_string()
+}
+
+fn git_config(key: &str) -> Option<String> {
+    git::output(&["config", "--get", key])
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
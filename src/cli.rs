@@ -0,0 +1,365 @@
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Generate git commit messages with qwen.
+///
+/// Without a subcommand, git-qwen behaves like `git commit`: any arguments
+/// after git-qwen's own options are passed through to git.
+#[derive(Parser, Debug)]
+#[command(name = "git-qwen", version, about, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub commit: CommitOpts,
+
+    /// Backend that writes the messages: qwen, or mock for a canned message
+    /// derived from the diff, without calling a model
+    #[arg(long, global = true, value_name = "NAME")]
+    pub backend: Option<String>,
+
+    /// Model qwen uses, overriding qwen.modelName and the size-based
+    /// qwen.smallModel and qwen.largeModel
+    #[arg(long, global = true, value_name = "NAME")]
+    pub model: Option<String>,
+
+    /// Sampling temperature for qwen, overriding qwen.temperature; 0 gives
+    /// the most predictable messages
+    #[arg(long, global = true, value_name = "T")]
+    pub temperature: Option<f32>,
+
+    /// Sampling seed for qwen, overriding qwen.seed, so the same diff gets
+    /// the same message
+    #[arg(long, global = true, value_name = "N")]
+    pub seed: Option<u64>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate a message for the staged changes and commit (the default)
+    Commit(CommitOpts),
+    /// Regenerate the message of the HEAD commit and amend it
+    Amend(CommitOpts),
+    /// Squash the commits after BASE into one with a message summarizing them
+    Squash {
+        /// Commit to squash onto; the commits after it are replaced
+        #[arg(value_name = "BASE")]
+        base: String,
+
+        #[command(flatten)]
+        opts: CommitOpts,
+    },
+    /// Regenerate the messages of a range of commits, reviewing each one
+    Reword {
+        /// Commits to reword: A..B, or A for A..HEAD
+        #[arg(value_name = "RANGE", required_unless_present_any = ["todo", "message"])]
+        range: Option<String>,
+
+        /// Rebase todo file to edit, when invoked as the sequence editor
+        #[arg(long, value_name = "FILE", hide = true, conflicts_with = "message")]
+        todo: Option<PathBuf>,
+
+        /// Commit message file to edit, when invoked as the message editor
+        #[arg(long, value_name = "FILE", hide = true)]
+        message: Option<PathBuf>,
+    },
+    /// Turn staged hunks into fixup commits for the recent commits they belong to
+    Absorb {
+        /// Create the fixup commits without asking
+        #[arg(long, short)]
+        yes: bool,
+
+        /// Only show which commit each hunk would be absorbed into
+        #[arg(long, short = 'n')]
+        dry_run: bool,
+    },
+    /// Split the staged changes into several logical commits
+    Split {
+        /// Create the commits without asking
+        #[arg(long, short)]
+        yes: bool,
+
+        /// Only show the proposed commits
+        #[arg(long, short = 'n')]
+        dry_run: bool,
+
+        /// Review each message in the editor before committing it
+        #[arg(long, short)]
+        edit: bool,
+    },
+    /// Create an annotated tag with a message summarizing the commits since the last tag
+    Tag {
+        /// Name of the tag to create
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// Commits to summarize (A..B, or A for A..HEAD); the tag goes on the
+        /// last one. Defaults to the commits since the latest tag
+        #[arg(value_name = "RANGE")]
+        range: Option<String>,
+
+        /// Make a GPG-signed tag
+        #[arg(long, short)]
+        sign: bool,
+    },
+    /// Write a CHANGELOG section for a range of commits
+    Changelog {
+        /// Commits to describe (A..B, or A for A..HEAD). Defaults to the
+        /// commits since the latest tag
+        #[arg(value_name = "RANGE")]
+        range: Option<String>,
+
+        /// Version for the section heading, instead of "Unreleased"
+        #[arg(long, value_name = "VERSION")]
+        version: Option<String>,
+
+        /// keep-a-changelog or conventional, overriding qwen.changelogStyle
+        #[arg(long, value_name = "STYLE")]
+        style: Option<String>,
+
+        /// Insert the section into CHANGELOG.md instead of printing it
+        #[arg(long, short)]
+        write: bool,
+    },
+    /// Write release notes for the commits since the previous tag
+    ReleaseNotes {
+        /// The release's tag; if it does not exist yet, the notes cover the
+        /// commits since the latest tag
+        #[arg(value_name = "TAG")]
+        tag: String,
+    },
+    /// Write a pull request title and description for the current branch
+    Pr {
+        /// Branch the pull request will be merged into; defaults to the
+        /// remote's default branch
+        #[arg(long, value_name = "BRANCH")]
+        base: Option<String>,
+
+        /// Review the text in the editor, then open the pull request with
+        /// gh (or glab for GitLab remotes)
+        #[arg(long)]
+        create: bool,
+
+        /// Open the pull request as a draft
+        #[arg(long, requires = "create")]
+        draft: bool,
+
+        /// Also copy the title and description to the clipboard
+        #[arg(long, conflicts_with = "create")]
+        copy: bool,
+    },
+    /// Suggest a branch name for the uncommitted changes or a description of the work
+    Branch {
+        /// What the branch is for, in your own words
+        #[arg(value_name = "DESCRIPTION", num_args = 0..)]
+        intent: Vec<String>,
+
+        /// Create the branch and switch to it, keeping the changes
+        #[arg(long, short)]
+        create: bool,
+    },
+    /// Explain a commit, a range, or the staged changes in plain language
+    Explain {
+        /// Commit or range (A..B) to explain; defaults to the staged changes
+        #[arg(value_name = "COMMIT|RANGE")]
+        target: Option<String>,
+    },
+    /// Review the staged changes for bugs, missing tests, and style issues
+    Review {
+        /// Review all changes to tracked files, not only the staged ones
+        #[arg(long, short)]
+        all: bool,
+    },
+    /// Stash the changes with a message describing the work in progress
+    Stash {
+        /// Arguments passed through to `git stash push`
+        #[arg(value_name = "GIT_STASH_ARGS", allow_hyphen_values = true, num_args = 0..)]
+        git_args: Vec<String>,
+    },
+    /// Attach a detailed explanation of a commit as a git note
+    Note {
+        /// Commit to explain
+        #[arg(value_name = "COMMIT", default_value = "HEAD")]
+        rev: String,
+
+        /// Replace the commit's existing note
+        #[arg(long, short)]
+        force: bool,
+    },
+    /// Find commits related to a query by meaning, not only by keyword
+    Search {
+        /// What to look for, in your own words
+        #[arg(value_name = "QUERY", required = true, num_args = 1..)]
+        query: Vec<String>,
+
+        /// Number of recent commits to search, overriding qwen.searchDepth
+        #[arg(long, short = 'n', value_name = "N")]
+        max_count: Option<usize>,
+    },
+    /// Learn the repository's commit style from its history for later prompts
+    Learn,
+    /// Show the messages git-qwen generated and what became of them
+    History {
+        /// Number of entries to show, newest first (default 20)
+        #[arg(long, short = 'n', value_name = "N")]
+        max_count: Option<usize>,
+
+        /// Also show each generated message and the one committed
+        #[arg(long, short)]
+        verbose: bool,
+
+        /// Print the raw entries, prompts included, as JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+    /// Manage the prepare-commit-msg hook that lets plain `git commit` use qwen
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+    /// Read or change git-qwen settings stored in git config
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Check that git-qwen's dependencies are available
+    Doctor {
+        /// Print the results, including detected capabilities, as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run generations for git-qwen, keeping the next qwen process started
+    /// and waiting, so commits don't pay for its startup
+    Daemon {
+        /// Stop the running daemon
+        #[arg(long)]
+        stop: bool,
+    },
+    /// Draft a message in the background whenever the staged changes change
+    Watch,
+    /// Print a plain-language summary of a diff, grouped by area with risk notes
+    DiffSummary {
+        /// One ref to compare the working tree with, two refs, or a range
+        /// like main..HEAD
+        #[arg(value_name = "REF", num_args = 0..=2, conflicts_with_all = ["staged", "stdin"])]
+        refs: Vec<String>,
+
+        /// Summarize the staged changes (the default when no refs are given)
+        #[arg(long, conflicts_with = "stdin")]
+        staged: bool,
+
+        /// Read the diff to summarize from standard input
+        #[arg(long)]
+        stdin: bool,
+    },
+}
+
+#[derive(Args, Debug, Default)]
+pub struct CommitOpts {
+    /// Start the subject with this prefix instead of one inferred from history
+    #[arg(long, value_name = "PREFIX", conflicts_with = "no_prefix")]
+    pub prefix: Option<String>,
+
+    /// Don't reuse the subject prefix of recent commits touching the same files
+    #[arg(long)]
+    pub no_prefix: bool,
+
+    /// Choose the hunks to stage first, each described by qwen, like `git add -p`
+    #[arg(long, short = 'p')]
+    pub patch: bool,
+
+    /// Reopen the editor on the last message instead of generating a new
+    /// one, after an aborted or failed commit
+    #[arg(long)]
+    pub resume: bool,
+
+    /// Describe the changes in this .patch or .diff file (- for standard
+    /// input) and print the message, instead of the staged changes
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["patch", "resume"])]
+    pub patch_file: Option<PathBuf>,
+
+    /// Print the generated message instead of opening the editor and
+    /// committing
+    #[arg(long)]
+    pub print: bool,
+
+    /// With --print (which it implies): text, or json for an object with
+    /// the subject, body, trailers, model, estimated tokens, and duration
+    #[arg(long, value_name = "FORMAT")]
+    pub output: Option<String>,
+
+    /// Write the message in this language (e.g. German or Japanese)
+    #[arg(long, value_name = "LANGUAGE")]
+    pub lang: Option<String>,
+
+    /// Add a footer that closes this issue (e.g. 42 or owner/repo#42); repeatable
+    #[arg(long, value_name = "ISSUE")]
+    pub closes: Vec<String>,
+
+    /// Add a Co-authored-by trailer for "Name <email>"; repeatable
+    #[arg(long, value_name = "NAME <EMAIL>")]
+    pub co_author: Vec<String>,
+
+    /// Add a trailer given as key=value; repeatable
+    #[arg(long, value_name = "KEY=VALUE")]
+    pub trailer: Vec<String>,
+
+    /// Commit without a signature, even with commit.gpgSign set or -S given
+    #[arg(long)]
+    pub no_sign: bool,
+
+    /// Arguments passed through to `git commit`
+    #[arg(value_name = "GIT_COMMIT_ARGS", allow_hyphen_values = true, num_args = 0..)]
+    pub git_args: Vec<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HookAction {
+    /// Install the prepare-commit-msg hook in the current repository
+    Install {
+        /// Overwrite an existing hook that was not installed by git-qwen
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the hook installed by `git-qwen hook install`
+    Uninstall,
+    /// Entry point invoked by git from the installed hook
+    #[command(hide = true)]
+    Run {
+        /// Path to the commit message file
+        file: PathBuf,
+        /// Source of the commit message (message, template, merge, ...)
+        source: Option<String>,
+        /// Commit object name when amending or reusing a message
+        sha: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// List all qwen.* settings
+    List,
+    /// Print the value of a setting
+    Get {
+        /// Setting name, with or without the `qwen.` prefix
+        key: String,
+    },
+    /// Change a setting
+    Set {
+        /// Setting name, with or without the `qwen.` prefix
+        key: String,
+        value: String,
+        /// Write to the global git config instead of the repository
+        #[arg(long)]
+        global: bool,
+    },
+    /// Remove a setting
+    Unset {
+        /// Setting name, with or without the `qwen.` prefix
+        key: String,
+        /// Remove from the global git config instead of the repository
+        #[arg(long)]
+        global: bool,
+    },
+}
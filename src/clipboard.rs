@@ -0,0 +1,83 @@
+//! Copying generated text to the clipboard with the tool found by
+//! `capabilities::clipboard`, or with the OSC 52 escape sequence, which
+//! most terminal emulators pass on to the local clipboard, even over SSH.
+
+use crate::capabilities;
+use crate::error::Error;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The clipboard to copy to, checked before any work is done so a missing
+/// one is reported up front.
+pub fn require() -> Result<&'static str, Error> {
+    capabilities::clipboard().ok_or_else(|| {
+        Error::Other(
+            "No clipboard to copy to: install wl-copy, xclip, or xsel, or run git-qwen in a terminal that supports OSC 52"
+                .to_string(),
+        )
+    })
+}
+
+/// Copies `text` with `tool`, as returned by `require`.
+pub fn copy(tool: &str, text: &str) -> Result<(), String> {
+    if tool == "osc52" {
+        let mut stderr = io::stderr();
+        write!(stderr, "\x1b]52;c;{}\x07", base64(text.as_bytes()))
+            .and_then(|()| stderr.flush())
+            .map_err(|e| format!("Failed to copy to the clipboard: {}", e))?;
+        return Ok(());
+    }
+
+    let args: &[&str] = match tool {
+        "xclip" => &["-selection", "clipboard"],
+        "xsel" => &["--clipboard", "--input"],
+        _ => &[],
+    };
+    let mut child = Command::new(tool)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", tool, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to copy to the clipboard with {}: {}", tool, e))?;
+    }
+    let status = child.wait().map_err(|e| format!("Failed to run {}: {}", tool, e))?;
+    if !status.success() {
+        return Err(format!("{} could not copy to the clipboard", tool));
+    }
+    Ok(())
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &byte)| n | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_pads_partial_chunks() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64("Add a feature\n".as_bytes()), "QWRkIGEgZmVhdHVyZQo=");
+    }
+}
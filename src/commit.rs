@@ -0,0 +1,630 @@
+//! The commit flow behind `git-qwen` and `git-qwen commit`: reads the
+//! changes, generates a message, lets the user edit it, and commits.
+
+use crate::amend;
+use crate::backend;
+use crate::branch;
+use crate::cancel;
+use crate::capabilities;
+use crate::cleanup::{self, Cleanup};
+use crate::cli::CommitOpts;
+use crate::commit_args::GitCommitArgs;
+use crate::config::Config;
+use crate::editor;
+use crate::error::Error;
+use crate::exclude;
+use crate::git::{self, GitClient, SystemGit};
+use crate::history_log::{self, Outcome};
+use crate::lock::RepoLock;
+use crate::pipeline::{CommitExecutor, DiffSource, MessageGenerator};
+use crate::probe;
+use crate::prompt::PromptOptions;
+use crate::signing::Signing;
+use crate::spinner::Spinner;
+use crate::squash;
+use crate::state;
+use crate::stage;
+use crate::template;
+use crate::trailers;
+use crate::untracked;
+use crate::truncate;
+use crate::verify;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// File in the git directory holding the last message, like git's own
+/// COMMIT_EDITMSG.
+const LAST_MESSAGE_FILE: &str = "QWEN_LASTMSG";
+
+pub fn run(opts: &CommitOpts) -> Result<(), Error> {
+    let config = Config::load();
+    let args = &git_args(opts);
+
+    match generate_and_commit(opts, &config, None) {
+        // In fail-safe mode a failure of the backend or of reading the
+        // changes degrades to a plain git commit, so the user still gets
+        // git's own editor flow. The user's own aborts stay aborts.
+        Err(e @ (Error::ModelUnavailable(_) | Error::ModelFailed(_) | Error::GitFailed(_)))
+            if config.get_bool("failSafe").unwrap_or(false) =>
+        {
+            eprintln!("Warning: {}", e);
+            eprintln!("Falling back to plain git commit (qwen.failSafe is enabled).");
+            exit_with(CommitExecutor::new(args).commit());
+        }
+        result => result,
+    }
+}
+
+/// Generates a message and commits. With `squash`, the commits after its
+/// base are replaced by the new one.
+pub fn generate_and_commit(opts: &CommitOpts, config: &Config, squash: Option<&squash::Squash>) -> Result<(), Error> {
+    let args = &git_args(opts);
+    let parsed = GitCommitArgs::parse(args);
+
+    if squash.is_some() && parsed.amend {
+        return Err(Error::Other("--amend cannot be used when squashing".to_string()));
+    }
+
+    // A patch file is described without looking at the repository
+    if opts.patch_file.is_none() {
+        state::check_can_commit()?;
+    }
+
+    // --patch decides what is staged before anything looks at the index
+    if opts.patch {
+        if parsed.all || !parsed.pathspecs.is_empty() {
+            return Err(Error::Other("--patch cannot be combined with -a or paths".to_string()));
+        }
+        // The hunks are sent to the backend to be described
+        capabilities::require_backend()?;
+        stage::run(config)?;
+    }
+
+    if parsed.skips_generation() {
+        // If user is providing their own message, just pass through to git commit
+        if let Some(squash) = squash {
+            squash.reset()?;
+        }
+        exit_with(CommitExecutor::new(args).commit());
+    }
+
+    // A patch file is only described, since it is not what is staged
+    let describe_only = opts.print || opts.output.is_some() || opts.patch_file.is_some();
+    let lock = if describe_only { None } else { Some(RepoLock::acquire()?) };
+
+    // A commit that cannot be signed fails before its message is generated
+    let signing = if describe_only { None } else { Signing::resolve(parsed.sign, parsed.signing_key.as_deref()) };
+    if let Some(signing) = &signing {
+        signing.check()?;
+    }
+
+    let print_json = match opts.output.as_deref() {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => return Err(Error::Other(format!("Unknown output format '{}', expected text or json", other))),
+    };
+    if !opts.resume && !opts.patch {
+        capabilities::require_backend()?;
+    }
+    let user_trailers = trailers::user_trailers(&opts.co_author, &opts.trailer)?;
+
+    // --amend regenerates the message for the amended commit
+    let is_amend = parsed.amend;
+
+    // -a/--all commits all tracked modified files
+    let include_all = parsed.all;
+
+    // -s/--signoff adds a Signed-off-by line
+    let include_signoff = parsed.signoff;
+
+    // Get git diff to generate commit message
+    let diff_output = match (squash, &opts.patch_file) {
+        (_, Some(path)) => DiffSource::Text(read_patch_file(path)?).read(&[]),
+        (Some(squash), None) => squash.diff(include_all),
+        (None, None) => DiffSource::for_commit(include_all, is_amend).read(&parsed.pathspecs),
+    }
+    .map_err(|e| Error::GitFailed(format!("Failed to get git diff: {}", e)))?;
+
+    // New files are easily forgotten, since even -a leaves them out
+    let untracked = if squash.is_none() && opts.patch_file.is_none() { untracked::files() } else { Vec::new() };
+    if include_all && !untracked.is_empty() {
+        let mut names = untracked.iter().take(5).cloned().collect::<Vec<_>>().join(", ");
+        if untracked.len() > 5 {
+            names.push_str(&format!(" and {} more", untracked.len() - 5));
+        }
+        eprintln!("Note: -a does not include untracked files ({}); stage them with 'git add' to commit them.", names);
+    }
+
+    if diff_output.trim().is_empty() {
+        let message = if let Some(path) = &opts.patch_file {
+            &format!("{} has no changes to describe.", path.display())
+        } else if squash.is_some() {
+            "The commits to squash make no changes when combined."
+        } else if let Some(operation) = state::Operation::in_progress().filter(|_| !is_amend && !include_all) {
+            &operation.nothing_to_commit_hint()
+        } else if is_amend {
+            "No changes found in HEAD commit.\n\
+                Cannot generate commit message for an empty commit."
+        } else if include_all {
+            "No changes to commit.\n\
+                Nothing to commit (no modified tracked files)."
+        } else {
+            "No changes staged for commit.\n\
+                Use 'git add' to stage changes, or use '-a' to commit all modified tracked files."
+        };
+        return Err(Error::NoChanges(message.to_string()));
+    }
+
+    // Generate commit message using qwen
+    let template = template::load(parsed.template.as_deref());
+    let amend = if is_amend {
+        Some(amend::Amend::load(include_all, &parsed.pathspecs, config).map_err(Error::GitFailed)?)
+    } else {
+        None
+    };
+    let prompt_options = PromptOptions {
+        prefix: opts.prefix.clone(),
+        no_prefix: opts.no_prefix,
+        language: opts.lang.clone(),
+        amend,
+        squashed: squash.map(|squash| squash.messages.clone()).unwrap_or_default(),
+        template: template.clone(),
+        untracked,
+    };
+
+    // In append mode only the added changes are described
+    let generation_diff = match &prompt_options.amend {
+        Some(amend) if amend.mode == amend::Mode::Append => amend.added.as_str(),
+        _ => diff_output.as_str(),
+    };
+
+    // The amended commit's own trailers, footers, and probes go before the
+    // signoff, trailers given on the command line after it
+    let mut footers: Vec<String> = prompt_options
+        .amend
+        .as_ref()
+        .map(|amend| amend.trailers.clone())
+        .unwrap_or_default();
+    footers.extend(branch::ticket_footer(config));
+    footers.extend(trailers::closing_footers(&opts.closes, config));
+    footers.extend(probe::collect_trailers(config));
+    // The id is of the commit made here, which --print and --patch-file
+    // don't make
+    if !describe_only {
+        footers.extend(trailers::change_id(&footers, &diff_output, config)?);
+    }
+    let signoff = if include_signoff { Some(get_signoff_line()?) } else { None };
+
+    // With qwen.verifyHooks, the repository's hooks see the message before
+    // the user does, so a rejected one can be regenerated
+    let verify_hooks = config.get_bool("verifyHooks").unwrap_or(false) && !parsed.no_verify;
+    let mut reuse_cached = true;
+    let started = Instant::now();
+    // The prompt and full message of a new generation, for the history log
+    let (commit_msg, trailer_block, generated) = if opts.resume {
+        // The saved message already has its trailers
+        (load_last_message()?, String::new(), None)
+    } else {
+        loop {
+            let (prompt, mut commit_msg) = generate_with_retry(generation_diff, config, &prompt_options, reuse_cached)
+                .map_err(Error::ModelFailed)?;
+            if let Some(amend) = &prompt_options.amend {
+                commit_msg = amend.combine(&commit_msg);
+            }
+            // An empty message means the generation was given up on
+            let assisted_by = (!commit_msg.is_empty()).then(|| trailers::assisted_by(&prompt, config)).flatten();
+            let before: Vec<String> = footers.iter().cloned().chain(assisted_by).collect();
+            let trailer_block = trailers::block(&commit_msg, &before, signoff.as_deref(), &user_trailers);
+            let full_message = if trailer_block.is_empty() {
+                format!("{}\n", commit_msg)
+            } else {
+                format!("{}\n\n{}", commit_msg, trailer_block)
+            };
+            if !verify_hooks || commit_msg.is_empty() {
+                break (commit_msg, trailer_block, Some((prompt, full_message)));
+            }
+
+            match verify::run_hooks(&full_message) {
+                Ok(()) => break (commit_msg, trailer_block, Some((prompt, full_message))),
+                Err(report) => {
+                    eprintln!("{}", report);
+                    match verify::ask_after_rejection() {
+                        verify::AfterRejection::Regenerate => {
+                            history_log::record(config, &prompt, &full_message, None, Outcome::Rejected);
+                            reuse_cached = false;
+                            continue;
+                        }
+                        verify::AfterRejection::Edit => {
+                            break (commit_msg, trailer_block, Some((prompt, full_message)));
+                        }
+                        verify::AfterRejection::Abort => {
+                            history_log::record(config, &prompt, &full_message, None, Outcome::Rejected);
+                            return Err(Error::Other("Aborting commit.".to_string()));
+                        }
+                    }
+                }
+            }
+        }
+    };
+    if describe_only {
+        let prompt = generated.as_ref().map(|(prompt, _)| prompt.as_str());
+        print_message(&commit_msg, &trailer_block, prompt, started.elapsed(), print_json);
+        return Ok(());
+    }
+
+    let record = |committed: Option<&str>, outcome: Outcome| {
+        if let Some((prompt, full_message)) = &generated {
+            history_log::record(config, prompt, full_message, committed, outcome);
+        }
+    };
+    save_last_message(&commit_msg, &trailer_block);
+
+    // Create temporary file with the generated message
+    let cleanup = Cleanup::resolve(
+        parsed.cleanup.as_deref(),
+        parsed.verbose > 0,
+        &format!("{}\n{}", commit_msg, trailer_block),
+    )?;
+
+    // -v shows the diff being committed below the comments, and -vv also
+    // the changes that stay out of the commit
+    let verbose_diff = match parsed.verbose {
+        0 => None,
+        1 => Some(diff_output.clone()),
+        _ => {
+            let unstaged = if include_all { String::new() } else { git::output(&["diff"])? };
+            Some(verbose_diff_with_unstaged(&diff_output, &unstaged, &cleanup))
+        }
+    };
+
+    let message_file = MessageFile::create()?;
+    write_commit_msg_file(
+        &message_file.path,
+        &commit_msg,
+        &trailer_block,
+        &cleanup,
+        template.as_deref(),
+        verbose_diff.as_deref(),
+        config,
+    )
+        .map_err(|e| format!("Failed to create commit message file: {}", e))?;
+
+    if let Err(e) = editor::open(&editor::command(), &message_file.path) {
+        return Err(Error::EditorFailed(format!("Failed to open editor: {}", e)));
+    }
+
+    // Read the edited message and clean it up the way git does, so blank
+    // lines, trailers, and paragraphs come out as git would keep them
+    let message = editor::read(&message_file.path).and_then(|edited| cleanup.clean(&edited))?;
+
+    if message.trim().is_empty() {
+        record(None, Outcome::Rejected);
+        return Err(Error::EmptyMessage);
+    }
+
+    let message = match &signoff {
+        Some(signoff) => trailers::place_signoff(&message, signoff),
+        None => message,
+    };
+
+    fs::write(&message_file.path, format!("{}\n", message.trim_end()))
+        .map_err(|e| format!("Failed to write commit message file: {}", e))?;
+    save_last_message(&message, "");
+
+    // The signoff is already in the message, and git would add another one
+    // when it is not the last trailer
+    let cleanup_option = cleanup.commit_option();
+    let mut options = vec![cleanup_option.as_str()];
+    if include_signoff {
+        options.push("--no-signoff");
+    }
+    let args = parsed.with_options(args, &options);
+
+    if let Some(squash) = squash
+        && let Err(e) = squash.reset()
+    {
+        return Err(e.into());
+    }
+
+    // Commit with the message file and any additional arguments
+    let executor = CommitExecutor::new(&args).with_config(cleanup.comment_config());
+    let result = executor.commit_with_file(&message_file.path);
+    if result == Ok(0) {
+        let generated = generated.as_ref().map(|(_, full_message)| full_message.as_str()).unwrap_or("");
+        record(Some(message.trim_end()), Outcome::of_commit(generated, &message));
+        if let Ok(path) = last_message_path() {
+            let _ = fs::remove_file(path);
+        }
+    } else {
+        record(Some(message.trim_end()), Outcome::Rejected);
+        if let (Ok(_), Some(signing)) = (&result, &signing) {
+            eprintln!("{}", signing.failure_hint());
+        }
+    }
+    // git has kept the message in its own COMMIT_EDITMSG
+    drop(message_file);
+    drop(lock);
+    exit_with(result);
+}
+
+/// The arguments for `git commit`: those given, and with --no-sign, the
+/// option that turns signing off, placed where it overrides `-S`.
+fn git_args(opts: &CommitOpts) -> Vec<String> {
+    if opts.no_sign {
+        GitCommitArgs::parse(&opts.git_args).with_options(&opts.git_args, &["--no-gpg-sign"])
+    } else {
+        opts.git_args.clone()
+    }
+}
+
+/// The diff in the patch file at `path`, or standard input for `-`,
+/// without the mail headers and message `git format-patch` puts before it.
+fn read_patch_file(path: &Path) -> Result<String, Error> {
+    let text = if path == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("Failed to read the patch from standard input: {}", e))?;
+        text
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+    };
+
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.starts_with("diff ") || line.starts_with("--- ") || line.starts_with("Index: ") {
+            return Ok(text[offset..].to_string());
+        }
+        offset += line.len();
+    }
+    Ok(text)
+}
+
+/// Prints the message for `--print`, or with `json` as an object with its
+/// parts and how it was generated from `prompt`. Token counts are
+/// estimated, since qwen does not report them.
+fn print_message(message: &str, trailer_block: &str, prompt: Option<&str>, duration: Duration, json: bool) {
+    if !json {
+        if trailer_block.is_empty() {
+            println!("{}", message);
+        } else {
+            println!("{}\n\n{}", message, trailer_block.trim_end());
+        }
+        return;
+    }
+
+    let (subject, body) = message.split_once('\n').unwrap_or((message, ""));
+    let backend = prompt.and(backend::answered_by());
+    let model = prompt.zip(backend).and_then(|(prompt, backend)| backend::backend_model(backend, prompt));
+    let tokens = prompt.map(|prompt| {
+        serde_json::json!({
+            "prompt": prompt.len().div_ceil(truncate::CHARS_PER_TOKEN),
+            "completion": message.len().div_ceil(truncate::CHARS_PER_TOKEN),
+        })
+    });
+    let output = serde_json::json!({
+        "subject": subject.trim(),
+        "body": body.trim(),
+        "trailers": trailer_block.lines().filter(|line| !line.trim().is_empty()).collect::<Vec<_>>(),
+        "backend": backend.map(|backend| backend.name()),
+        "model": model,
+        "tokens": tokens,
+        "duration_ms": duration.as_millis() as u64,
+    });
+    println!("{}", output);
+}
+
+/// Generates a message for `diff` and returns it with the prompt, letting
+/// the user retry, continue with an empty message, or abort when they
+/// cancel the generation. With `reuse_cached`, a message generated earlier
+/// for the same prompt is reused.
+pub fn generate_with_retry(
+    diff: &str,
+    config: &Config,
+    options: &PromptOptions,
+    reuse_cached: bool,
+) -> Result<(String, String), String> {
+    let generator = MessageGenerator::new(config, options).reuse_cached(reuse_cached);
+    loop {
+        let generated = {
+            let stream = config.get_bool("stream").unwrap_or(true);
+            let _spinner = Spinner::start("Generating commit message with qwen…", stream);
+            generator
+                .prompt(diff)
+                .and_then(|prompt| generator.answer(&prompt).map(|message| (prompt, message)))
+        };
+        match generated {
+            Ok(generated) => return Ok(generated),
+            Err(_) if cancel::was_cancelled() => match cancel::ask_after_cancel() {
+                cancel::AfterCancel::Retry => continue,
+                cancel::AfterCancel::EditEmpty => return Ok((String::new(), String::new())),
+                cancel::AfterCancel::Abort => {
+                    eprintln!("Aborting commit.");
+                    cancel::exit_interrupted();
+                }
+            },
+            Err(e) => {
+                return Err(format!(
+                    "Failed to generate commit message: {}\nMake sure 'qwen' is installed and available in PATH.",
+                    e
+                ));
+            }
+        }
+    }
+}
+
+/// Where the last generated or edited message is kept until it is
+/// committed, for `--resume`.
+fn last_message_path() -> Result<PathBuf, String> {
+    Ok(SystemGit.git_dir()?.join(LAST_MESSAGE_FILE))
+}
+
+/// Keeps `message` and its trailers for `--resume`. Failing to is not worth
+/// stopping the commit for.
+fn save_last_message(message: &str, trailer_block: &str) {
+    let text = if trailer_block.is_empty() {
+        format!("{}\n", message.trim_end())
+    } else {
+        format!("{}\n\n{}", message.trim_end(), trailer_block)
+    };
+    if !message.trim().is_empty()
+        && let Ok(path) = last_message_path()
+    {
+        let _ = fs::write(path, text);
+    }
+}
+
+fn load_last_message() -> Result<String, String> {
+    let path = last_message_path()?;
+    match fs::read_to_string(&path) {
+        Ok(message) if !message.trim().is_empty() => Ok(message.trim_end().to_string()),
+        _ => Err("No message to resume; it is kept only until a commit with it succeeds".to_string()),
+    }
+}
+
+fn get_signoff_line() -> Result<String, String> {
+    let git = SystemGit;
+    match (git.config("user.name"), git.config("user.email")) {
+        (Some(name), Some(email)) => Ok(format!("Signed-off-by: {} <{}>\n", name, email)),
+        _ => Err("Failed to get git user config".to_string()),
+    }
+}
+
+/// The diff shown by `-vv`: the changes to be committed, then those left
+/// out, each under a comment heading as git shows them.
+fn verbose_diff_with_unstaged(staged: &str, unstaged: &str, cleanup: &Cleanup) -> String {
+    let mut text = format!(
+        "{}\n{}\n{}",
+        cleanup.comment_line(""),
+        cleanup.comment_line("Changes to be committed:"),
+        staged
+    );
+    if !unstaged.is_empty() {
+        text.push_str(&format!(
+            "{}\n{}\n{}",
+            cleanup.comment_line(&"-".repeat(50)),
+            cleanup.comment_line("Changes not staged for commit:"),
+            unstaged
+        ));
+    }
+    text
+}
+
+/// The file the editor opens: `COMMIT_EDITMSG` in a directory of its own in
+/// the git directory, so editors still recognize it as a commit message
+/// while git's own COMMIT_EDITMSG is left to git. Removed when dropped.
+struct MessageFile {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl MessageFile {
+    fn create() -> Result<Self, String> {
+        let name = format!("qwen-edit-{}", std::process::id());
+        let dir = git::path(&name)?;
+        // Left behind by a git-qwen that was killed and had the same ID
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        cancel::remove_on_interrupt(&dir);
+        Ok(MessageFile { path: dir.join("COMMIT_EDITMSG"), dir })
+    }
+}
+
+impl Drop for MessageFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+        cancel::keep_on_interrupt(&self.dir);
+    }
+}
+
+fn write_commit_msg_file(
+    commit_msg_path: &Path,
+    message: &str,
+    trailer_block: &str,
+    cleanup: &Cleanup,
+    template: Option<&str>,
+    verbose_diff: Option<&str>,
+    config: &Config,
+) -> Result<(), String> {
+    let git = SystemGit;
+    let mut file = fs::File::create(commit_msg_path)
+        .map_err(|e| format!("Failed to create commit message file: {}", e))?;
+
+    // Write the generated message
+    file.write_all(message.as_bytes())
+        .map_err(|e| format!("Failed to write to commit message file: {}", e))?;
+
+    if !trailer_block.is_empty() {
+        write!(file, "\n\n{}", trailer_block)
+            .map_err(|e| format!("Failed to write trailers: {}", e))?;
+    } else {
+        writeln!(file).map_err(|e| format!("Failed to write to file: {}", e))?;
+    }
+
+    // Add git commit template comments
+    if let Ok(status) = git.output(&["status", "--porcelain"]) {
+        // Get current branch name
+        let branch_name = git
+            .output(&["branch", "--show-current"])
+            .map(|branch| branch.trim().to_string())
+            .unwrap_or_else(|_| "detached HEAD".to_string());
+
+        let mut comments = cleanup.help_lines();
+        if let Some(template) = template {
+            // Shown as a reminder of the structure the team expects; lines
+            // that are already comments are kept as they are
+            comments.push(String::new());
+            comments.push("Commit template:".to_string());
+            for line in template.lines() {
+                let line = line.strip_prefix(cleanup.comment.as_str()).map_or(line, str::trim_start);
+                comments.push(format!("  {}", line).trim_end().to_string());
+            }
+        }
+        comments.push(String::new());
+        comments.push(format!("On branch {}", branch_name));
+        comments.push("Changes to be committed:".to_string());
+        let (untracked, changed): (Vec<&str>, Vec<&str>) = status.lines().partition(|line| line.starts_with("?? "));
+        comments.extend(changed.into_iter().map(String::from));
+        // Files kept out of the prompt are not named here either
+        let patterns = exclude::patterns(config);
+        let untracked: Vec<&str> = untracked.into_iter().filter(|line| !exclude::is_excluded(&patterns, &line[3..])).collect();
+        if !untracked.is_empty() {
+            comments.push(String::new());
+            comments.push("Untracked files, not included in this commit:".to_string());
+            comments.extend(untracked.into_iter().map(|line| format!("   {}", &line[3..])));
+        }
+
+        writeln!(file).map_err(|e| format!("Failed to write to file: {}", e))?;
+        for line in comments {
+            writeln!(file, "{}", cleanup.comment_line(&line))
+                .map_err(|e| format!("Failed to write to file: {}", e))?;
+        }
+    }
+
+    if let Some(diff) = verbose_diff {
+        // Scissors mode already opened the ignored part with its own line
+        if cleanup.mode != cleanup::Mode::Scissors {
+            for line in Cleanup::scissors_lines() {
+                writeln!(file, "{}", cleanup.comment_line(&line))
+                    .map_err(|e| format!("Failed to write to file: {}", e))?;
+            }
+        }
+        write!(file, "{}", diff).map_err(|e| format!("Failed to write diff: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Exits with git's exit code, as if git had been run directly.
+fn exit_with(result: Result<i32, String>) -> ! {
+    match result {
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            let error = Error::GitFailed(e);
+            eprintln!("Error: {}", error);
+            std::process::exit(error.exit_code());
+        }
+    }
+}
@@ -0,0 +1,268 @@
+/// Short options of `git commit` that require a value, either attached
+/// (`-mfoo`) or as the next argument (`-m foo`).
+const SHORT_WITH_VALUE: &[char] = &['m', 'F', 'C', 'c', 't'];
+
+/// Short options whose value is optional and can only be attached (`-Skeyid`).
+const SHORT_WITH_OPTIONAL_VALUE: &[char] = &['u', 'S'];
+
+/// Long options of `git commit` that require a value, either as
+/// `--opt=value` or as the next argument.
+const LONG_WITH_VALUE: &[&str] = &[
+    "message",
+    "file",
+    "reuse-message",
+    "reedit-message",
+    "fixup",
+    "squash",
+    "author",
+    "date",
+    "template",
+    "cleanup",
+    "pathspec-from-file",
+    "trailer",
+];
+
+/// Options that provide the commit message, so there is nothing to generate.
+const MESSAGE_SHORT: &[char] = &['m', 'F', 'C', 'c'];
+const MESSAGE_LONG: &[&str] = &[
+    "message",
+    "file",
+    "reuse-message",
+    "reedit-message",
+    "fixup",
+    "squash",
+];
+
+/// The parts of a `git commit` command line that change what git-qwen does.
+#[derive(Debug, Default)]
+pub struct GitCommitArgs {
+    /// `-a`/`--all`: commit all modified tracked files
+    pub all: bool,
+    /// `-s`/`--signoff`: add a Signed-off-by trailer
+    pub signoff: bool,
+    /// `--amend`: replace the HEAD commit
+    pub amend: bool,
+    /// The message is supplied by the user (`-m`, `-F`, `-C`, `--fixup`, ...)
+    pub has_message: bool,
+    /// `--no-edit`: keep the existing message, e.g. with `--amend`
+    pub no_edit: bool,
+    /// `-h`/`--help`/`--version`: git prints information and exits
+    pub informational: bool,
+    /// Paths given on the command line, which limit what gets committed
+    pub pathspecs: Vec<String>,
+    /// Index of the `--` that ends the options, if any
+    pub separator: Option<usize>,
+    /// `--cleanup=<mode>`: how git cleans up the message
+    pub cleanup: Option<String>,
+    /// `-n`/`--no-verify`: skip the pre-commit and commit-msg hooks
+    pub no_verify: bool,
+    /// `-t`/`--template`: the commit template file
+    pub template: Option<String>,
+    /// Number of `-v`/`--verbose`: show the diff in the editor, and with
+    /// two, the unstaged changes too
+    pub verbose: usize,
+    /// `-S`/`--gpg-sign` or `--no-gpg-sign`: whether to sign, overriding
+    /// `commit.gpgSign`
+    pub sign: Option<bool>,
+    /// The key in `-S<keyid>`/`--gpg-sign=<keyid>`, overriding
+    /// `user.signingKey`
+    pub signing_key: Option<String>,
+}
+
+impl GitCommitArgs {
+    /// Parses git commit arguments the way git does, including clustered
+    /// short options such as `-am "msg"` or `-sam "msg"`.
+    pub fn parse(args: &[String]) -> Self {
+        let mut parsed = GitCommitArgs::default();
+        let mut iter = args.iter().enumerate();
+
+        while let Some((index, arg)) = iter.next() {
+            if arg == "--" {
+                parsed.separator = Some(index);
+                parsed.pathspecs.extend(iter.by_ref().map(|(_, arg)| arg.clone()));
+                break;
+            }
+
+            if let Some(long) = arg.strip_prefix("--") {
+                let (name, inline_value) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value)),
+                    None => (long, None),
+                };
+
+                // The value is the next argument unless given as --opt=value
+                let value = match inline_value {
+                    Some(value) => Some(value.to_string()),
+                    None if LONG_WITH_VALUE.contains(&name) => iter.next().map(|(_, v)| v.clone()),
+                    None => None,
+                };
+
+                parsed.apply_long(name, value);
+                continue;
+            }
+
+            let Some(cluster) = arg.strip_prefix('-').filter(|c| !c.is_empty()) else {
+                parsed.pathspecs.push(arg.clone());
+                continue;
+            };
+
+            for (pos, flag) in cluster.char_indices() {
+                parsed.apply_short(flag);
+
+                if SHORT_WITH_VALUE.contains(&flag) {
+                    // The rest of the cluster is the value; if there is
+                    // none, the value is the next argument
+                    let rest = &cluster[pos + flag.len_utf8()..];
+                    let value = if rest.is_empty() {
+                        iter.next().map(|(_, v)| v.clone())
+                    } else {
+                        Some(rest.to_string())
+                    };
+                    if flag == 't' {
+                        parsed.template = value;
+                    }
+                    break;
+                }
+
+                if SHORT_WITH_OPTIONAL_VALUE.contains(&flag) {
+                    if flag == 'S' {
+                        let rest = &cluster[pos + flag.len_utf8()..];
+                        parsed.signing_key = Some(rest.to_string()).filter(|key| !key.is_empty());
+                    }
+                    break;
+                }
+            }
+        }
+
+        parsed
+    }
+
+    /// Appends `options` to `args` where git still reads them as options:
+    /// before the `--` separator, or at the end when there is none. git lets
+    /// later options override earlier ones, so these win over the user's.
+    pub fn with_options(&self, args: &[String], options: &[&str]) -> Vec<String> {
+        let index = self.separator.unwrap_or(args.len());
+        let mut result = args[..index].to_vec();
+        result.extend(options.iter().map(|option| option.to_string()));
+        result.extend_from_slice(&args[index..]);
+        result
+    }
+
+    /// Whether the command should go straight to `git commit` without
+    /// generating a message. `--no-edit` asks git to reuse the message it
+    /// already has (of the amended commit or the merge), so there is
+    /// nothing to generate either.
+    pub fn skips_generation(&self) -> bool {
+        self.has_message || self.informational || self.no_edit
+    }
+
+    fn apply_short(&mut self, flag: char) {
+        match flag {
+            'a' => self.all = true,
+            's' => self.signoff = true,
+            'v' => self.verbose += 1,
+            'n' => self.no_verify = true,
+            'e' => self.no_edit = false,
+            'h' => self.informational = true,
+            'S' => self.sign = Some(true),
+            _ if MESSAGE_SHORT.contains(&flag) => self.has_message = true,
+            _ => {}
+        }
+    }
+
+    fn apply_long(&mut self, name: &str, value: Option<String>) {
+        match name {
+            "cleanup" => self.cleanup = value,
+            "template" => self.template = value,
+            "all" => self.all = true,
+            "signoff" => self.signoff = true,
+            "verbose" => self.verbose += 1,
+            "no-verify" => self.no_verify = true,
+            "verify" => self.no_verify = false,
+            "no-verbose" => self.verbose = 0,
+            "amend" => self.amend = true,
+            "no-edit" => self.no_edit = true,
+            "gpg-sign" => {
+                self.sign = Some(true);
+                self.signing_key = value.filter(|key| !key.is_empty());
+            }
+            "no-gpg-sign" => self.sign = Some(false),
+            "edit" => self.no_edit = false,
+            "help" | "version" => self.informational = true,
+            _ if MESSAGE_LONG.contains(&name) => self.has_message = true,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> GitCommitArgs {
+        GitCommitArgs::parse(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn clustered_flags_end_at_an_attached_signing_key() {
+        let parsed = parse(&["-asSABCD1234", "file.txt"]);
+        assert!(parsed.all);
+        assert!(parsed.signoff);
+        assert_eq!(parsed.sign, Some(true));
+        assert_eq!(parsed.signing_key.as_deref(), Some("ABCD1234"));
+        assert!(!parsed.has_message);
+        assert_eq!(parsed.pathspecs, vec!["file.txt"]);
+    }
+
+    #[test]
+    fn signing_key_is_reset_only_by_a_later_plain_sign_flag() {
+        let parsed = parse(&["-SABCD1234", "-as"]);
+        assert_eq!(parsed.sign, Some(true));
+        assert_eq!(parsed.signing_key.as_deref(), Some("ABCD1234"));
+
+        let parsed = parse(&["-SABCD1234", "-S"]);
+        assert_eq!(parsed.signing_key, None);
+
+        let parsed = parse(&["--gpg-sign=ABCD1234"]);
+        assert_eq!(parsed.signing_key.as_deref(), Some("ABCD1234"));
+    }
+
+    #[test]
+    fn glued_value_does_not_take_the_next_argument() {
+        let parsed = parse(&["-mFix the -a typo", "file.txt"]);
+        assert!(parsed.has_message);
+        assert!(!parsed.all, "the value is not parsed as flags");
+        assert_eq!(parsed.pathspecs, vec!["file.txt"]);
+
+        let parsed = parse(&["-vtmsg.txt"]);
+        assert_eq!(parsed.verbose, 1);
+        assert_eq!(parsed.template.as_deref(), Some("msg.txt"));
+    }
+
+    #[test]
+    fn clustered_value_flag_takes_the_next_argument() {
+        let parsed = parse(&["-am", "Fix typo", "file.txt"]);
+        assert!(parsed.all);
+        assert!(parsed.has_message);
+        assert_eq!(parsed.pathspecs, vec!["file.txt"]);
+    }
+
+    #[test]
+    fn trailing_value_flag_without_a_value() {
+        let parsed = parse(&["file.txt", "-t"]);
+        assert_eq!(parsed.template, None);
+        assert_eq!(parsed.pathspecs, vec!["file.txt"]);
+
+        let parsed = parse(&["-a", "--cleanup"]);
+        assert!(parsed.all);
+        assert_eq!(parsed.cleanup, None);
+        assert!(parsed.pathspecs.is_empty());
+    }
+
+    #[test]
+    fn arguments_after_the_separator_are_paths() {
+        let parsed = parse(&["-a", "--", "-m", "file.txt"]);
+        assert_eq!(parsed.separator, Some(1));
+        assert!(!parsed.has_message);
+        assert_eq!(parsed.pathspecs, vec!["-m", "file.txt"]);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::cli::ConfigAction;
+use std::process::Command;
+
+/// Section in git config that holds all git-qwen settings.
+const SECTION: &str = "qwen";
+
+pub fn run(action: ConfigAction) -> Result<(), String> {
+    match action {
+        ConfigAction::List => {
+            let output = Command::new("git")
+                .args(["config", "--get-regexp", &format!("^{}\\.", SECTION)])
+                .output()
+                .map_err(|e| format!("Failed to execute git config: {}", e))?;
+
+            // git config exits with 1 when nothing matches, which is not an error here
+            if !output.status.success() && output.status.code() != Some(1) {
+                return Err("git config command failed".to_string());
+            }
+
+            print!("{}", String::from_utf8_lossy(&output.stdout));
+            Ok(())
+        }
+        ConfigAction::Get { key } => {
+            let value = crate::git::output(&["config", "--get", &qualify(&key)])
+                .map_err(|_| format!("{} is not set", qualify(&key)))?;
+            print!("{}", value);
+            Ok(())
+        }
+        ConfigAction::Set { key, value, global } => {
+            let key = qualify(&key);
+            let mut args = vec!["config"];
+            if global {
+                args.push("--global");
+            }
+            args.extend([key.as_str(), value.as_str()]);
+            crate::git::output(&args).map(|_| ())
+        }
+        ConfigAction::Unset { key, global } => {
+            let key = qualify(&key);
+            let mut args = vec!["config"];
+            if global {
+                args.push("--global");
+            }
+            args.extend(["--unset", key.as_str()]);
+            crate::git::output(&args).map(|_| ())
+        }
+    }
+}
+
+/// Adds the `qwen.` prefix to a setting name unless it is already present.
+fn qualify(key: &str) -> String {
+    let prefix = format!("{}.", SECTION);
+    if key.to_lowercase().starts_with(&prefix) {
+        key.to_string()
+    } else {
+        format!("{}{}", prefix, key)
+    }
+}
+
+/// git-qwen settings read from the `qwen.` section of git config.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Entries in the order git reports them; keys are lowercase without
+    /// the section prefix, and multi-valued keys appear once per value
+    entries: Vec<(String, String)>,
+}
+
+impl Config {
+    /// Loads the settings visible from the current directory. A missing
+    /// repository or an empty section simply yields the defaults.
+    pub fn load() -> Config {
+        let output = Command::new("git")
+            .args(["config", "-z", "--get-regexp", &format!("^{}\\.", SECTION)])
+            .output();
+
+        let Ok(output) = output else {
+            return Config::default();
+        };
+
+        let prefix = format!("{}.", SECTION);
+        let entries = String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter_map(|entry| {
+                // With -z each entry is "key\nvalue"; a key without a value
+                // is a boolean set to true
+                let (key, value) = entry.split_once('\n').unwrap_or((entry, "true"));
+                let key = key.strip_prefix(&prefix)?;
+                Some((key.to_lowercase(), value.to_string()))
+            })
+            .collect();
+
+        Config { entries }
+    }
+
+    /// Builds settings from `(key, value)` pairs, as if read from git config.
+    #[cfg(test)]
+    pub(crate) fn from_entries(entries: &[(&str, &str)]) -> Config {
+        Config {
+            entries: entries
+                .iter()
+                .map(|(key, value)| (key.to_lowercase(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Returns the last value of `key`, matching git's "last one wins" rule.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let key = key.to_lowercase();
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns `key` as a non-negative number, warning about invalid values.
+    pub fn get_usize(&self, key: &str) -> Option<usize> {
+        let value = self.get(key)?;
+        match value.trim().parse() {
+            Ok(n) => Some(n),
+            Err(_) => {
+                eprintln!("Warning: ignoring qwen.{}: expected a number, got '{}'", key, value);
+                None
+            }
+        }
+    }
+
+    /// Returns `key` as a boolean using git's spellings (true/yes/on/1 and
+    /// false/no/off/0), warning about invalid values.
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        let value = self.get(key)?;
+        match value.trim().to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Some(true),
+            "false" | "no" | "off" | "0" | "" => Some(false),
+            _ => {
+                eprintln!("Warning: ignoring qwen.{}: expected a boolean, got '{}'", key, value);
+                None
+            }
+        }
+    }
+
+    /// Returns every value of a multi-valued `key`.
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        let key = key.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+}
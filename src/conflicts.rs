@@ -0,0 +1,88 @@
+//! Context for commits that conclude a merge, cherry-pick, revert, or
+//! rebase step in which files had conflicts.
+
+use crate::config::Config;
+use crate::git;
+use crate::prompt;
+use crate::truncate;
+use std::fs;
+
+/// The ref git records for each operation that can stop on conflicts, with
+/// a name for the prompt.
+const OPERATIONS: &[(&str, &str)] = &[
+    ("MERGE_HEAD", "merge"),
+    ("CHERRY_PICK_HEAD", "cherry-pick"),
+    ("REVERT_HEAD", "revert"),
+    ("REBASE_HEAD", "rebase"),
+];
+
+/// Describes the conflicts resolved in the commit being made, if any: which
+/// files had them and how their resolution differs from the incoming
+/// version, so the model can explain the resolution. The diff goes
+/// through the same exclusion and masking as the change itself and is
+/// trimmed to `max_chars` if set.
+pub fn prompt_section(max_chars: Option<usize>, config: &Config) -> Option<String> {
+    let (head, operation) = OPERATIONS
+        .iter()
+        .find(|(head, _)| git::output(&["rev-parse", "--verify", "--quiet", head]).is_ok())?;
+
+    let paths = resolved_paths();
+    if paths.is_empty() {
+        return None;
+    }
+
+    let mut section = format!(
+        "\nThis commit concludes a {} in which these files had conflicts:\n",
+        operation
+    );
+    let listing: Vec<String> = paths.iter().map(|path| format!("- {}", path)).collect();
+    section.push_str(&prompt::fence(&listing.join("\n")));
+    section.push_str(
+        "In the body, describe how each conflict was resolved: which side's changes were kept, combined, or dropped, and why if it is evident.\n",
+    );
+
+    let mut args = vec!["diff", "--cached", head, "--"];
+    args.extend(paths.iter().map(String::as_str));
+    if let Ok(diff) = git::output(&args)
+        && !diff.trim().is_empty()
+    {
+        let diff = prompt::prepare_diff(&diff, config);
+        let diff = match max_chars {
+            Some(max) => truncate::fit(&diff, max),
+            None => diff,
+        };
+        section.push_str(&format!(
+            "This is how the resolved files differ from the incoming version ({}):\n{}",
+            head,
+            prompt::fence(&diff)
+        ));
+    }
+    Some(section)
+}
+
+/// The paths git listed as conflicted in `MERGE_MSG`, under a `Conflicts:`
+/// line that may be commented out, one per tab-indented line.
+fn resolved_paths() -> Vec<String> {
+    let Ok(path) = git::path("MERGE_MSG") else {
+        return Vec::new();
+    };
+    let Ok(message) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    let mut paths = Vec::new();
+    let mut in_conflicts = false;
+    for line in message.lines() {
+        let uncommented = line.trim_start_matches(|c: char| !c.is_alphanumeric() && c != '\t');
+        if uncommented.trim() == "Conflicts:" {
+            in_conflicts = true;
+        } else if in_conflicts {
+            match uncommented.strip_prefix('\t') {
+                Some(path) if !path.trim().is_empty() => paths.push(path.trim().to_string()),
+                _ if uncommented.trim().is_empty() => {}
+                _ => break,
+            }
+        }
+    }
+    paths
+}
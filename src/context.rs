@@ -0,0 +1,175 @@
+//! Whole-function context for small hunks. A one-line change with two
+//! lines of context around it leaves the model guessing what the code
+//! does; with `qwen.functionContext`, hunks that change few lines are
+//! widened to the whole function or type they are in, like `git diff -W`
+//! does for every hunk.
+
+use crate::config::Config;
+use crate::diff;
+use crate::symbols::{self, Definitions};
+
+/// Functions longer than this are not shown whole; the hunk stays as it is.
+const MAX_FUNCTION_LINES: usize = 200;
+
+/// Widens the hunks of `diff` that change at most `qwen.functionContext`
+/// lines (default `0`, off) to the definitions enclosing them.
+pub fn expand_small_hunks(diff: &str, config: &Config) -> String {
+    let max_changed = config.get_usize("functionContext").unwrap_or(0);
+    if max_changed == 0 {
+        return diff.to_string();
+    }
+
+    let files = diff::split_files(diff);
+    if files.is_empty() {
+        return diff.to_string();
+    }
+    files
+        .iter()
+        .map(|file| {
+            let expanded = Definitions::for_path(&file.path).and_then(|definitions| {
+                let content = symbols::new_content(file)?;
+                let lines: Vec<&str> = content.lines().collect();
+                Some(expand_file(file.text, &lines, &definitions, max_changed))
+            });
+            expanded.unwrap_or_else(|| file.text.to_string())
+        })
+        .collect()
+}
+
+/// One hunk: its header's numbers and the rest of the header line, and its
+/// lines.
+struct Hunk<'a> {
+    old_start: usize,
+    old_len: usize,
+    new_start: usize,
+    new_len: usize,
+    tail: &'a str,
+    lines: Vec<&'a str>,
+}
+
+impl Hunk<'_> {
+    fn changed(&self) -> usize {
+        self.lines.iter().filter(|line| line.starts_with(['+', '-'])).count()
+    }
+
+    /// The 0-based new-file line ranges the hunk covers.
+    fn new_range(&self) -> (usize, usize) {
+        let start = self.new_start.saturating_sub(1);
+        (start, start + self.new_len)
+    }
+}
+
+/// Rewrites the section `text` with its small hunks widened, using `lines`,
+/// the file as changed.
+fn expand_file(text: &str, lines: &[&str], definitions: &Definitions, max_changed: usize) -> String {
+    let Some(first_hunk) = text.find("\n@@").map(|at| at + 1) else {
+        return text.to_string();
+    };
+    let (head, body) = text.split_at(first_hunk);
+    let Some(hunks) = parse_hunks(body) else {
+        return text.to_string();
+    };
+
+    let mut section = head.to_string();
+    for (i, hunk) in hunks.iter().enumerate() {
+        // Lines another hunk shows cannot be context of this one
+        let floor = if i > 0 { hunks[i - 1].new_range().1 } else { 0 };
+        let ceiling = hunks.get(i + 1).map_or(lines.len(), |next| next.new_range().0);
+        let (before, after) = if hunk.changed() <= max_changed && hunk.old_len > 0 && hunk.new_len > 0 {
+            widening(hunk, lines, definitions, floor, ceiling)
+        } else {
+            (0, 0)
+        };
+
+        let (start, end) = hunk.new_range();
+        section.push_str(&format!(
+            "@@ -{},{} +{},{} @@{}\n",
+            hunk.old_start - before,
+            hunk.old_len + before + after,
+            hunk.new_start - before,
+            hunk.new_len + before + after,
+            hunk.tail
+        ));
+        for line in &lines[start - before..start] {
+            section.push_str(&format!(" {}\n", line));
+        }
+        for line in &hunk.lines {
+            section.push_str(line);
+            section.push('\n');
+        }
+        for line in &lines[end..end + after] {
+            section.push_str(&format!(" {}\n", line));
+        }
+    }
+    section
+}
+
+/// How many lines to add before and after `hunk` to show the whole
+/// definition its first change is in, staying within `floor..ceiling`.
+fn widening(hunk: &Hunk, lines: &[&str], definitions: &Definitions, floor: usize, ceiling: usize) -> (usize, usize) {
+    let (start, end) = hunk.new_range();
+    // The first changed line, counted in the new file
+    let mut first_change = start;
+    for line in &hunk.lines {
+        if line.starts_with(['+', '-']) {
+            break;
+        }
+        first_change += 1;
+    }
+    let Some(def_start) = definitions.enclosing_start(lines, first_change.min(lines.len().saturating_sub(1))) else {
+        return (0, 0);
+    };
+    let def_end = definition_end(lines, def_start);
+    if def_end - def_start > MAX_FUNCTION_LINES {
+        return (0, 0);
+    }
+
+    let before = start.saturating_sub(def_start.max(floor));
+    let after = def_end.min(ceiling).saturating_sub(end);
+    (before, after)
+}
+
+/// The index just past the definition starting at `lines[start]`: the
+/// next line indented no more than it, included when it closes it.
+fn definition_end(lines: &[&str], start: usize) -> usize {
+    let indentation = symbols::indentation(lines[start]);
+    for (i, line) in lines.iter().enumerate().skip(start + 1) {
+        if line.trim().is_empty() || symbols::indentation(line) > indentation {
+            continue;
+        }
+        let trimmed = line.trim_start();
+        let closes = trimmed.starts_with(['}', ')', ']']) || trimmed == "end" || trimmed.starts_with("end ");
+        return if closes { i + 1 } else { i };
+    }
+    lines.len()
+}
+
+/// Splits the hunks of a section, from its first `@@` line on.
+fn parse_hunks(body: &str) -> Option<Vec<Hunk<'_>>> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for line in body.lines() {
+        if let Some(rest) = line.strip_prefix("@@ -") {
+            let (ranges, tail) = rest.split_once(" @@")?;
+            let (old, new) = ranges.split_once(" +")?;
+            let range = |range: &str| -> Option<(usize, usize)> {
+                match range.split_once(',') {
+                    Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+                    None => Some((range.parse().ok()?, 1)),
+                }
+            };
+            let (old_start, old_len) = range(old)?;
+            let (new_start, new_len) = range(new)?;
+            hunks.push(Hunk {
+                old_start,
+                old_len,
+                new_start,
+                new_len,
+                tail,
+                lines: Vec::new(),
+            });
+        } else {
+            hunks.last_mut()?.lines.push(line);
+        }
+    }
+    Some(hunks)
+}
@@ -0,0 +1,385 @@
+//! `git-qwen daemon`: runs generations for git-qwen over a unix socket,
+//! keeping the next backend process started and waiting for its prompt,
+//! so a commit does not pay for the model CLI's startup. git-qwen sends
+//! its prompts to a running daemon automatically.
+//!
+//! Each request is one JSON line naming the program, its arguments, the
+//! directory to run it in, and the prompt. The daemon answers with a
+//! `{"stdout": ...}` line per line of output, then a final
+//! `{"status": ..., "stderr": ...}` or `{"error": ...}` line.
+
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Name of the socket in the runtime directory.
+#[cfg(unix)]
+const SOCKET_FILE: &str = "git-qwen.sock";
+
+/// How often a client waiting for an answer checks for cancellation.
+#[cfg(unix)]
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A finished generation run by the daemon.
+pub struct Reply {
+    pub success: bool,
+    pub stdout: Vec<u8>,
+    pub stderr: String,
+}
+
+/// Serves requests until stopped, or with `stop`, stops the running
+/// daemon.
+#[cfg(unix)]
+pub fn run(stop: bool) -> Result<(), String> {
+    server::run(stop)
+}
+
+#[cfg(not(unix))]
+pub fn run(_stop: bool) -> Result<(), String> {
+    Err("The daemon needs unix sockets, which this platform lacks".to_string())
+}
+
+/// Has a running daemon run `program` with `args` on `prompt`, passing
+/// each line of its output to `on_output`. None when no daemon is
+/// listening.
+#[cfg(unix)]
+pub fn generate(
+    program: &str,
+    args: &[String],
+    envs: &[(String, String)],
+    prompt: &str,
+    timeout: Option<Duration>,
+    on_output: Option<fn(&[u8])>,
+) -> Option<Result<Reply, String>> {
+    let stream = std::os::unix::net::UnixStream::connect(socket_path().ok()?).ok()?;
+    Some(client::exchange(stream, program, args, envs, prompt, timeout, on_output))
+}
+
+#[cfg(not(unix))]
+pub fn generate(
+    _program: &str,
+    _args: &[String],
+    _envs: &[(String, String)],
+    _prompt: &str,
+    _timeout: Option<Duration>,
+    _on_output: Option<fn(&[u8])>,
+) -> Option<Result<Reply, String>> {
+    None
+}
+
+/// The socket in `$XDG_RUNTIME_DIR`, else in a directory of the system
+/// temporary directory only the user can enter.
+#[cfg(unix)]
+fn socket_path() -> Result<PathBuf, String> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir).join(SOCKET_FILE));
+    }
+
+    // SAFETY: getuid has no memory safety requirements and cannot fail
+    let uid = unsafe { libc::getuid() };
+    let dir = std::env::temp_dir().join(format!("git-qwen-{}", uid));
+    match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            let metadata = std::fs::metadata(&dir).map_err(|e| format!("Failed to inspect {}: {}", dir.display(), e))?;
+            if metadata.permissions().mode() & 0o077 != 0 {
+                return Err(format!("{} is accessible to other users", dir.display()));
+            }
+        }
+        Err(e) => return Err(format!("Failed to create {}: {}", dir.display(), e)),
+    }
+    Ok(dir.join(SOCKET_FILE))
+}
+
+#[cfg(unix)]
+mod client {
+    use super::{POLL_INTERVAL, Reply};
+    use crate::cancel;
+    use serde_json::{Value, json};
+    use std::io::{BufRead, BufReader, ErrorKind, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::{Duration, Instant};
+
+    /// Sends the request and reads the answer, giving up when the user
+    /// cancels or `timeout` passes; closing the connection makes the
+    /// daemon stop the program.
+    pub fn exchange(
+        mut stream: UnixStream,
+        program: &str,
+        args: &[String],
+        envs: &[(String, String)],
+        prompt: &str,
+        timeout: Option<Duration>,
+        on_output: Option<fn(&[u8])>,
+    ) -> Result<Reply, String> {
+        let dir = std::env::current_dir().map_err(|e| format!("Failed to get the current directory: {}", e))?;
+        let request = json!({
+            "program": program,
+            "args": args,
+            "env": envs
+                .iter()
+                .map(|(name, value)| (name.clone(), Value::from(value.as_str())))
+                .collect::<serde_json::Map<String, Value>>(),
+            "dir": dir,
+            "prompt": prompt,
+        });
+        writeln!(stream, "{}", request).map_err(|e| format!("Failed to send the prompt to the daemon: {}", e))?;
+        stream
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|e| format!("Failed to configure the daemon connection: {}", e))?;
+
+        cancel::cancellable(|| {
+            let started = Instant::now();
+            let mut reader = BufReader::new(stream);
+            let mut line = Vec::new();
+            let mut stdout = Vec::new();
+            loop {
+                if cancel::was_cancelled() {
+                    return Err("generation cancelled".to_string());
+                }
+                if let Some(timeout) = timeout
+                    && started.elapsed() >= timeout
+                {
+                    return Err(format!("timed out after {} seconds (see qwen.timeout)", timeout.as_secs()));
+                }
+
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(_) if line.ends_with(b"\n") => {}
+                    Ok(_) => return Err("The daemon closed the connection".to_string()),
+                    Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+                    Err(e) => return Err(format!("Failed to read from the daemon: {}", e)),
+                }
+
+                let message: Value = serde_json::from_slice(&line).map_err(|e| format!("Bad reply from the daemon: {}", e))?;
+                line.clear();
+                if let Some(text) = message["stdout"].as_str() {
+                    stdout.extend_from_slice(text.as_bytes());
+                    if let Some(on_output) = on_output {
+                        on_output(text.as_bytes());
+                    }
+                } else if let Some(error) = message["error"].as_str() {
+                    return Err(error.to_string());
+                } else {
+                    return Ok(Reply {
+                        success: message["status"].as_i64() == Some(0),
+                        stdout,
+                        stderr: message["stderr"].as_str().unwrap_or("").to_string(),
+                    });
+                }
+            }
+        })
+    }
+}
+
+#[cfg(unix)]
+mod server {
+    use super::socket_path;
+    use crate::backend;
+    use crate::cancel;
+    use serde_json::{Value, json};
+    use std::fs;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::PathBuf;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    /// What a process was started as; a spare is only used for a request
+    /// that would start the same.
+    #[derive(Clone, PartialEq, Eq)]
+    struct Key {
+        program: String,
+        args: Vec<String>,
+        /// Server, proxy, and TLS variables, the only ones a request may set
+        envs: Vec<(String, String)>,
+        dir: PathBuf,
+    }
+
+    /// A started process waiting for its prompt.
+    struct Spare {
+        key: Key,
+        child: Child,
+    }
+
+    type SpareSlot = Arc<Mutex<Option<Spare>>>;
+
+    pub fn run(stop: bool) -> Result<(), String> {
+        let path = socket_path()?;
+        if stop {
+            let mut stream = UnixStream::connect(&path).map_err(|_| "No daemon is running".to_string())?;
+            writeln!(stream, "{}", json!({ "stop": true })).map_err(|e| format!("Failed to stop the daemon: {}", e))?;
+            eprintln!("Stopped the daemon.");
+            return Ok(());
+        }
+
+        if UnixStream::connect(&path).is_ok() {
+            return Err(format!("A daemon is already listening on {}", path.display()));
+        }
+        // Left behind by a daemon that was killed
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).map_err(|e| format!("Failed to listen on {}: {}", path.display(), e))?;
+        cancel::remove_on_interrupt(&path);
+        eprintln!("git-qwen daemon listening on {} (Ctrl+C to stop)", path.display());
+
+        let spare: SpareSlot = Arc::default();
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let mut line = String::new();
+            if BufReader::new(&stream).read_line(&mut line).is_err() {
+                continue;
+            }
+            let Ok(request) = serde_json::from_str::<Value>(&line) else { continue };
+            if request["stop"] == true {
+                break;
+            }
+
+            let spare = Arc::clone(&spare);
+            thread::spawn(move || serve(stream, &request, &spare));
+        }
+
+        if let Some(mut spare) = spare.lock().ok().and_then(|mut slot| slot.take()) {
+            let _ = spare.child.kill();
+            let _ = spare.child.wait();
+        }
+        let _ = fs::remove_file(&path);
+        Ok(())
+    }
+
+    fn serve(mut stream: UnixStream, request: &Value, spare: &SpareSlot) {
+        let reply = match generate(&stream, request, spare) {
+            Ok((status, stderr)) => json!({ "status": status, "stderr": stderr }),
+            Err(e) => json!({ "error": e }),
+        };
+        let _ = writeln!(stream, "{}", reply);
+    }
+
+    /// Runs the request, streaming its output, and returns its exit code
+    /// and stderr.
+    fn generate(stream: &UnixStream, request: &Value, spare: &SpareSlot) -> Result<(i32, String), String> {
+        let key = Key {
+            program: request["program"].as_str().unwrap_or("").to_string(),
+            args: request["args"]
+                .as_array()
+                .map(|args| args.iter().filter_map(|arg| arg.as_str().map(String::from)).collect())
+                .unwrap_or_default(),
+            envs: request["env"]
+                .as_object()
+                .map(|envs| {
+                    envs.iter()
+                        .filter(|(name, _)| backend::NETWORK_VARS.contains(&name.as_str()))
+                        .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            dir: PathBuf::from(request["dir"].as_str().unwrap_or(".")),
+        };
+        // Only model backends run here, not whatever a request names
+        if !backend::PROGRAMS.contains(&key.program.as_str()) {
+            return Err(format!("The daemon does not run {}", key.program));
+        }
+
+        let (mut child, warm) = take_or_start(&key, spare)?;
+        eprintln!("Running {} {}", if warm { "a warm" } else { "a newly started" }, key.program);
+        refill(key.clone(), spare);
+
+        let prompt = request["prompt"].as_str().unwrap_or("").to_string();
+        let stdin = child.stdin.take();
+        thread::spawn(move || {
+            if let Some(mut stdin) = stdin {
+                let _ = stdin.write_all(prompt.as_bytes());
+            }
+        });
+        let stderr = child.stderr.take();
+        let stderr = thread::spawn(move || {
+            let mut text = String::new();
+            if let Some(mut stderr) = stderr {
+                let _ = stderr.read_to_string(&mut text);
+            }
+            text
+        });
+
+        // The client closes the connection when it gives up; the program
+        // is stopped then, even if it prints nothing
+        let done = Arc::new(AtomicBool::new(false));
+        let pid = child.id() as libc::pid_t;
+        if let Ok(mut client) = stream.try_clone() {
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                let _ = client.read(&mut [0; 1]);
+                if !done.load(Ordering::SeqCst) {
+                    // SAFETY: kill has no memory safety requirements
+                    unsafe {
+                        libc::kill(pid, libc::SIGKILL);
+                    }
+                }
+            });
+        }
+
+        let mut writer = stream;
+        if let Some(stdout) = child.stdout.take() {
+            let mut reader = BufReader::new(stdout);
+            let mut line = Vec::new();
+            while reader.read_until(b'\n', &mut line).is_ok_and(|read| read > 0) {
+                let text = String::from_utf8_lossy(&line);
+                if writeln!(writer, "{}", json!({ "stdout": text })).is_err() {
+                    let _ = child.kill();
+                    break;
+                }
+                line.clear();
+            }
+        }
+
+        let status = child.wait().map_err(|e| format!("Failed to wait for {}: {}", key.program, e))?;
+        done.store(true, Ordering::SeqCst);
+        Ok((status.code().unwrap_or(-1), stderr.join().unwrap_or_default()))
+    }
+
+    /// The spare process if it was started as `key` and is still waiting,
+    /// else a new one, and whether it was the spare.
+    fn take_or_start(key: &Key, spare: &SpareSlot) -> Result<(Child, bool), String> {
+        let taken = spare.lock().ok().and_then(|mut slot| slot.take());
+        if let Some(mut taken) = taken {
+            if taken.key == *key && matches!(taken.child.try_wait(), Ok(None)) {
+                return Ok((taken.child, true));
+            }
+            let _ = taken.child.kill();
+            let _ = taken.child.wait();
+        }
+        start(key).map(|child| (child, false))
+    }
+
+    /// Starts the next process for `key` in the background, so it is
+    /// ready for the next request.
+    fn refill(key: Key, spare: &SpareSlot) {
+        let spare = Arc::clone(spare);
+        thread::spawn(move || {
+            let Ok(child) = start(&key) else { return };
+            let replaced = spare.lock().ok().and_then(|mut slot| slot.replace(Spare { key, child }));
+            if let Some(mut replaced) = replaced {
+                let _ = replaced.child.kill();
+                let _ = replaced.child.wait();
+            }
+        });
+    }
+
+    fn start(key: &Key) -> Result<Child, String> {
+        let mut command = Command::new(&key.program);
+        // The daemon's own server and proxy settings give way to the client's
+        for name in backend::NETWORK_VARS {
+            command.env_remove(name);
+        }
+        command
+            .args(&key.args)
+            .envs(key.envs.iter().cloned())
+            .current_dir(&key.dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to spawn {}: {}", key.program, e))
+    }
+}
@@ -0,0 +1,246 @@
+use crate::git::GitClient;
+
+/// Options of `git diff` that `qwen.diffArgs` may set. They change only
+/// how the changes are shown, never which files or what output format.
+const ALLOWED_DIFF_ARGS: &[&str] = &[
+    "-U",
+    "--unified=",
+    "--inter-hunk-context=",
+    "-W",
+    "--function-context",
+    "--minimal",
+    "--patience",
+    "--histogram",
+    "--diff-algorithm=",
+    "--indent-heuristic",
+    "--no-indent-heuristic",
+    "-w",
+    "--ignore-all-space",
+    "-b",
+    "--ignore-space-change",
+    "--ignore-space-at-eol",
+    "--ignore-cr-at-eol",
+    "--ignore-blank-lines",
+];
+
+/// Options for the `git diff` runs whose output the model reads: those of
+/// `rename_options`, then those of `qwen.diffArgs`, such as `-U5
+/// --histogram -w`.
+pub fn options(git: &dyn GitClient) -> Vec<String> {
+    let mut options = rename_options(git);
+    for arg in git.config("qwen.diffArgs").unwrap_or_default().split_whitespace() {
+        let allowed = ALLOWED_DIFF_ARGS.iter().any(|allowed| match allowed.strip_suffix('=') {
+            Some(prefix) => arg.starts_with(prefix) && arg.contains('='),
+            None if *allowed == "-U" => arg.strip_prefix("-U").is_some_and(|n| n.parse::<usize>().is_ok()),
+            None => arg == *allowed,
+        });
+        if allowed {
+            options.push(arg.to_string());
+        } else {
+            eprintln!("Warning: ignoring {} in qwen.diffArgs, which only takes options that change how the diff is shown", arg);
+        }
+    }
+    options
+}
+
+/// Renames and copies are found, so a moved file shows as a rename instead
+/// of a deletion and an addition of its whole content: `qwen.renames` is
+/// `copies` (default), `true` for renames only, or `false`, and
+/// `qwen.renameThreshold` sets how similar the files must be, like `50%`.
+pub fn rename_options(git: &dyn GitClient) -> Vec<String> {
+    let threshold = git.config("qwen.renameThreshold").unwrap_or_default();
+    let renames = git.config("qwen.renames").map(|renames| renames.to_lowercase());
+    match renames.as_deref() {
+        Some("false" | "no" | "off" | "0") => vec!["--no-renames".to_string()],
+        Some("true" | "yes" | "on" | "1") => vec![format!("-M{}", threshold)],
+        _ => vec![format!("-M{}", threshold), format!("-C{}", threshold)],
+    }
+}
+
+/// The portion of a unified diff that belongs to a single file.
+#[derive(Debug)]
+pub struct FileDiff<'a> {
+    /// Path of the file after the change (before it, for deletions)
+    pub path: String,
+    /// The full section, from its `diff --git` header to the next one
+    pub text: &'a str,
+}
+
+/// Splits `git diff` output into per-file sections. Anything before the
+/// first `diff --git` header is ignored.
+pub fn split_files(diff: &str) -> Vec<FileDiff<'_>> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = starts.get(i + 1).copied().unwrap_or(diff.len());
+            let text = &diff[start..end];
+            FileDiff {
+                path: section_path(text),
+                text,
+            }
+        })
+        .collect()
+}
+
+impl FileDiff<'_> {
+    /// Describes how the file changed: "added", "deleted", "renamed from
+    /// <old path>", "mode changed", or "modified".
+    pub fn change_kind(&self) -> String {
+        for line in self.text.lines().skip(1) {
+            if line.starts_with("@@") {
+                break;
+            }
+            if line.starts_with("new file mode") {
+                return "added".to_string();
+            }
+            if line.starts_with("deleted file mode") {
+                return "deleted".to_string();
+            }
+            if let Some(old) = line.strip_prefix("rename from ") {
+                return format!("renamed from {}", unquote(old));
+            }
+            if let Some(old) = line.strip_prefix("copy from ") {
+                return format!("copied from {}", unquote(old));
+            }
+        }
+
+        let has_hunks = self.text.lines().any(|line| line.starts_with("@@"));
+        if !has_hunks && self.text.lines().any(|line| line.starts_with("new mode")) {
+            return "mode changed".to_string();
+        }
+        "modified".to_string()
+    }
+
+    /// Path of the file before the change: where it was renamed or copied
+    /// from, else its path.
+    pub fn old_path(&self) -> String {
+        self.text
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.starts_with("@@"))
+            .find_map(|line| line.strip_prefix("rename from ").or_else(|| line.strip_prefix("copy from ")))
+            .map_or_else(|| self.path.clone(), unquote)
+    }
+}
+
+/// Returns the paths touched by a diff, without duplicates.
+pub fn changed_paths(diff: &str) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for file in split_files(diff) {
+        if !paths.contains(&file.path) {
+            paths.push(file.path);
+        }
+    }
+    paths
+}
+
+/// Counts added and removed lines in the hunks of a diff.
+pub fn count_changes(diff: &str) -> (usize, usize) {
+    let mut in_hunk = false;
+    let (mut added, mut removed) = (0, 0);
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            in_hunk = false;
+        } else if line.starts_with("@@") {
+            in_hunk = true;
+        } else if in_hunk && line.starts_with('+') {
+            added += 1;
+        } else if in_hunk && line.starts_with('-') {
+            removed += 1;
+        }
+    }
+    (added, removed)
+}
+
+fn section_path(section: &str) -> String {
+    let mut old_path = None;
+    for line in section.lines().skip(1) {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            if let Some(path) = strip_side(path, "b/") {
+                return path;
+            }
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            old_path = strip_side(path, "a/");
+        } else if let Some(path) = line.strip_prefix("rename to ").or_else(|| line.strip_prefix("copy to ")) {
+            // Pure renames and copies have no ---/+++ lines
+            return unquote(path);
+        } else if line.starts_with("@@") {
+            break;
+        }
+    }
+
+    if let Some(path) = old_path {
+        return path;
+    }
+
+    // Binary files and mode-only changes have no ---/+++ lines, so fall
+    // back to the header, where both sides are identical unless renamed
+    let header = section.lines().next().unwrap_or("");
+    let names = header.strip_prefix("diff --git ").unwrap_or("");
+    if let Some(rest) = names.strip_prefix("a/") {
+        let half = (rest.len().saturating_sub(3)) / 2;
+        if rest.is_char_boundary(half) && rest[half..].starts_with(" b/") {
+            return rest[..half].to_string();
+        }
+        if let Some((old, _)) = rest.split_once(" b/") {
+            return old.to_string();
+        }
+    }
+    unquote(names)
+}
+
+/// Strips the `a/` or `b/` prefix from a ---/+++ path, returning None for
+/// /dev/null.
+fn strip_side(path: &str, side: &str) -> Option<String> {
+    // git appends a tab after names that contain spaces
+    let path = unquote(path.trim_end_matches(['\t', '\r']));
+    path.strip_prefix(side).map(|p| p.to_string())
+}
+
+/// Undoes git's C-style quoting of paths with unusual characters.
+fn unquote(path: &str) -> String {
+    let Some(inner) = path.strip_prefix('"').and_then(|p| p.strip_suffix('"')) else {
+        return path.to_string();
+    };
+
+    let mut bytes = Vec::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some(d @ '0'..='7') => {
+                // Octal escapes encode the raw bytes of non-ASCII names
+                let mut value = d.to_digit(8).unwrap_or(0);
+                for _ in 0..2 {
+                    if let Some(d) = chars.peek().and_then(|c| c.to_digit(8)) {
+                        value = value * 8 + d;
+                        chars.next();
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+            }
+            None => {}
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -0,0 +1,234 @@
+use crate::backend;
+use crate::capabilities::{self, Capabilities};
+use crate::spinner::Spinner;
+use std::path::Path;
+use std::process::Command;
+
+/// Question asked to check that the model answers at all.
+const PING_PROMPT: &str = "Reply with the single word OK and nothing else.";
+
+/// The result of one check.
+struct Check {
+    name: &'static str,
+    ok: bool,
+    /// Whether git-qwen cannot work without it; other failures are warnings
+    required: bool,
+    /// What was found, or what went wrong
+    detail: String,
+    /// How to fix a failure
+    fix: &'static str,
+}
+
+impl Check {
+    fn new(name: &'static str, result: Result<String, String>, fix: &'static str) -> Self {
+        let ok = result.is_ok();
+        Check {
+            name,
+            ok,
+            required: true,
+            detail: result.unwrap_or_else(|e| e),
+            fix,
+        }
+    }
+
+    fn optional(self) -> Self {
+        Check { required: false, ..self }
+    }
+}
+
+/// Runs each check and reports whether git-qwen can work in this environment,
+/// with a fix for each failure, as text or, with `json`, as a JSON object on
+/// stdout.
+pub fn run(json: bool) -> Result<(), String> {
+    let capabilities = Capabilities::detect();
+    let checks = checks(&capabilities);
+    let editor = crate::editor::command();
+
+    let failures = checks.iter().filter(|check| !check.ok && check.required).count();
+
+    if json {
+        let report = serde_json::json!({
+            "ok": failures == 0,
+            "checks": checks
+                .iter()
+                .map(|check| serde_json::json!({
+                    "name": check.name,
+                    "ok": check.ok,
+                    "required": check.required,
+                    "detail": check.detail,
+                    "fix": if check.ok { None } else { Some(check.fix) },
+                }))
+                .collect::<Vec<_>>(),
+            "editor": editor,
+            "capabilities": {
+                "interactive": capabilities.interactive,
+                "ssh": capabilities.ssh,
+                "clipboard": capabilities.clipboard,
+                "keyring": capabilities.keyring,
+                "backends": capabilities
+                    .backends
+                    .iter()
+                    .map(|(backend, path)| serde_json::json!({
+                        "name": backend.name(),
+                        "path": path.as_ref().map(|p| p.display().to_string()),
+                    }))
+                    .collect::<Vec<_>>(),
+                "network": capabilities
+                    .network
+                    .iter()
+                    .map(|server| serde_json::json!({
+                        "backend": server.backend.name(),
+                        "address": server.address,
+                        "reachable": server.reachable,
+                    }))
+                    .collect::<Vec<_>>(),
+            },
+            "notes": capabilities.notes(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+    } else {
+        for check in &checks {
+            let status = match (check.ok, check.required) {
+                (true, _) => "[ok]  ",
+                (false, true) => "[fail]",
+                (false, false) => "[warn]",
+            };
+            if check.detail.is_empty() {
+                println!("{} {}", status, check.name);
+            } else {
+                println!("{} {}: {}", status, check.name, check.detail);
+            }
+            if !check.ok {
+                println!("       fix: {}", check.fix);
+            }
+        }
+        for note in capabilities.notes() {
+            println!("[note] {}", note);
+        }
+    }
+
+    if failures > 0 {
+        return Err(format!("{} check(s) failed", failures));
+    }
+
+    Ok(())
+}
+
+/// Checks git, the repository, the backend, its server, and its model, the
+/// editor, and the hook, skipping those that depend on an earlier failure.
+fn checks(capabilities: &Capabilities) -> Vec<Check> {
+    let mut checks = vec![Check::new(
+        "git",
+        command_output("git", &["--version"]),
+        "Install git and make sure it is in PATH.",
+    )];
+
+    let in_work_tree = command_output("git", &["rev-parse", "--is-inside-work-tree"]);
+    let in_work_tree = match in_work_tree {
+        Ok(answer) if answer == "true" => Ok(String::new()),
+        Ok(_) => Err("not inside a work tree".to_string()),
+        Err(e) => Err(e),
+    };
+    checks.push(Check::new("repository", in_work_tree, "Run git-qwen from inside a git work tree."));
+
+    let backends: Vec<String> = capabilities
+        .backends
+        .iter()
+        .map(|(backend, path)| match (backend.program(), path) {
+            (Some(_), Some(path)) => format!("{} ({})", backend.name(), path.display()),
+            (Some(_), None) => format!("{} (not found in PATH)", backend.name()),
+            (None, _) => backend.name().to_string(),
+        })
+        .collect();
+    let backend_found = capabilities.backends.iter().any(|(backend, path)| backend.program().is_none() || path.is_some());
+    let backend = if backend_found { Ok(backends.join(", ")) } else { Err(backends.join(", ")) };
+    checks.push(Check::new(
+        "backend",
+        backend,
+        "Install the qwen CLI and make sure it is in PATH, or use --backend mock to try git-qwen without it.",
+    ));
+
+    if !capabilities.network.is_empty() {
+        let describe = |server: &capabilities::Server| format!("{} at {}", server.backend.name(), server.address);
+        let unreachable: Vec<String> =
+            capabilities.network.iter().filter(|server| !server.reachable).map(describe).collect();
+        let network = if unreachable.is_empty() {
+            Ok(capabilities.network.iter().map(describe).collect::<Vec<_>>().join(", "))
+        } else {
+            Err(format!("cannot connect to {}", unreachable.join(", ")))
+        };
+        checks.push(
+            Check::new(
+                "network",
+                network,
+                "Start the backend's server, or check OLLAMA_HOST, OPENAI_BASE_URL, and the proxy in qwen.proxy or HTTPS_PROXY.",
+            )
+            .optional(),
+        );
+    }
+
+    if backend_found {
+        // The test question goes to the backend like any prompt, so it
+        // needs the same confirmation for a remote one
+        let answer = crate::remote::confirm().map_err(String::from).and_then(|()| {
+            let _spinner = Spinner::start("Asking the model a test question…", false);
+            backend::generate(PING_PROMPT)
+        });
+        let answer = match answer {
+            Ok(answer) if answer.trim().is_empty() => Err("the model gave an empty answer".to_string()),
+            Ok(_) => Ok(match backend::answered_by().and_then(|backend| backend::backend_model(backend, PING_PROMPT)) {
+                Some(model) => format!("{} answers", model),
+                None => "answers".to_string(),
+            }),
+            Err(e) => Err(e),
+        };
+        checks.push(Check::new(
+            "model",
+            answer,
+            "Run qwen by hand to check that it is signed in and configured, or raise qwen.timeout if it is slow.",
+        ));
+    }
+
+    let editor = crate::editor::command();
+    let program = crate::editor::program(&editor);
+    let editor_found = if program == ":" || Path::new(&program).is_file() || capabilities::find_program(&program).is_some() {
+        Ok(editor.clone())
+    } else {
+        Err(format!("{} not found", editor))
+    };
+    checks.push(Check::new(
+        "editor",
+        editor_found,
+        "Set core.editor (or GIT_EDITOR, VISUAL, or EDITOR) to an installed editor.",
+    ));
+
+    if checks.iter().any(|check| check.name == "repository" && check.ok) {
+        let hook = if crate::hook::is_installed() {
+            Ok("prepare-commit-msg".to_string())
+        } else {
+            Err("not installed".to_string())
+        };
+        checks.push(
+            Check::new(
+                "hook",
+                hook,
+                "Run `git-qwen hook install` to let plain `git commit` use qwen (optional).",
+            )
+            .optional(),
+        );
+    }
+
+    checks
+}
+
+/// The trimmed stdout of a successful command, or why it failed.
+fn command_output(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{} not found: {}", program, e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
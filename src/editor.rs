@@ -0,0 +1,118 @@
+//! Choosing and running the editor for messages the user reviews.
+
+use crate::git;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// The editor git would use: `GIT_EDITOR`, `core.editor`, `VISUAL`, or
+/// `EDITOR`, else the platform default. It is a shell command, which may
+/// have arguments, like `code --wait`.
+pub fn command() -> String {
+    // git var applies git's own precedence, core.editor included
+    if let Ok(editor) = git::output(&["var", "GIT_EDITOR"])
+        && !editor.trim().is_empty()
+    {
+        return editor.trim().to_string();
+    }
+
+    // Check environment variables in order of precedence
+    env::var("GIT_EDITOR")
+        .or_else(|_| env::var("VISUAL"))
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| {
+            // Default editors by platform
+            if cfg!(target_os = "windows") {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        })
+}
+
+/// Opens `file_path` in `editor` and waits for it to exit. Like git, the
+/// editor is run by the shell, so it can have arguments; `:` leaves the
+/// file as it is.
+pub fn open(editor: &str, file_path: &Path) -> Result<(), String> {
+    if editor == ":" {
+        return Ok(());
+    }
+
+    let status = shell_command(editor)
+        .arg(file_path)
+        .status()
+        .map_err(|e| format!("Failed to execute editor: {}", e))?;
+
+    if !status.success() {
+        return Err(format!("Editor exited with non-zero status: {}", status));
+    }
+
+    Ok(())
+}
+
+/// Reads a file back from the editor. Windows editors may have written
+/// CRLF line endings or a byte order mark; both are dropped so the text
+/// parses as if it had been written on Unix.
+pub fn read(path: &Path) -> Result<String, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+    Ok(text.replace("\r\n", "\n"))
+}
+
+/// The program `editor` runs, for checking that it is installed.
+pub fn program(editor: &str) -> String {
+    split(editor).into_iter().next().unwrap_or_default()
+}
+
+/// `editor` ready to be given the file: run by `sh`, which passes it the
+/// file as `"$@"`, as git does.
+#[cfg(not(windows))]
+fn shell_command(editor: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(format!("{} \"$@\"", editor)).arg(editor);
+    command
+}
+
+/// `editor` ready to be given the file: split into the program and its
+/// arguments, since Windows has no shell to do it.
+#[cfg(windows)]
+fn shell_command(editor: &str) -> Command {
+    let mut words = split(editor).into_iter();
+    let mut command = Command::new(words.next().unwrap_or_default());
+    command.args(words);
+    command
+}
+
+/// Splits a command line into words at unquoted whitespace; single or
+/// double quotes group words, like `"C:\Program Files\Vim\gvim.exe" -f`.
+fn split(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
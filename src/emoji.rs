@@ -0,0 +1,105 @@
+//! Optional gitmoji at the start of the subject, from `qwen.emoji`.
+
+use crate::config::Config;
+use crate::subject;
+
+/// Gitmoji for conventional commit types.
+const TYPES: &[(&str, &str)] = &[
+    ("feat", "✨"),
+    ("fix", "🐛"),
+    ("docs", "📝"),
+    ("style", "🎨"),
+    ("refactor", "♻️"),
+    ("perf", "⚡️"),
+    ("test", "✅"),
+    ("build", "📦️"),
+    ("ci", "👷"),
+    ("chore", "🔧"),
+    ("revert", "⏪️"),
+    ("security", "🔒️"),
+];
+
+/// Gitmoji for the verb a subject starts with, when it has no type.
+const VERBS: &[(&[&str], &str)] = &[
+    (&["add", "implement", "introduce", "support", "allow", "enable"], "✨"),
+    (&["fix", "correct", "resolve", "prevent", "handle", "guard"], "🐛"),
+    (&["document"], "📝"),
+    (&["refactor", "simplify", "extract", "rename", "move", "reorder", "tidy", "clean"], "♻️"),
+    (&["optimize", "reduce"], "⚡️"),
+    (&["test"], "✅"),
+    (&["remove", "delete", "drop"], "🔥"),
+    (&["bump", "upgrade"], "⬆️"),
+    (&["revert"], "⏪️"),
+];
+
+/// Gitmoji used for changes that fit none of the above.
+const DEFAULT: &str = "🔧";
+
+/// How the emoji is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// No emoji
+    Off,
+    /// Mapped from the conventional commit type or the first verb
+    Map,
+    /// Picked by the model, falling back to the mapping
+    Model,
+}
+
+impl Mode {
+    pub fn from_config(config: &Config) -> Self {
+        match config.get("emoji").map(str::to_lowercase).as_deref() {
+            None | Some("off") | Some("false") | Some("no") | Some("0") => Mode::Off,
+            Some("map") | Some("true") | Some("yes") | Some("1") => Mode::Map,
+            Some("model") => Mode::Model,
+            Some(other) => {
+                eprintln!("Warning: ignoring invalid qwen.emoji '{}', expected off, map, or model", other);
+                Mode::Off
+            }
+        }
+    }
+}
+
+/// The prompt rule asking the model to pick the emoji, in `model` mode.
+pub fn rule(config: &Config) -> Option<String> {
+    (Mode::from_config(config) == Mode::Model).then(|| {
+        "Start the subject with the one gitmoji that best describes the change, followed by a space: ✨ new feature, 🐛 bug fix, 📝 documentation, ♻️ refactoring, ⚡️ performance, ✅ tests, 🔥 removed code, ⬆️ dependency upgrade, 🔧 configuration or chores".to_string()
+    })
+}
+
+/// Splits a leading emoji (or `:shortcode:`) and the space after it off
+/// `subject`.
+pub fn split(subject: &str) -> (Option<&str>, &str) {
+    if let Some(rest) = subject.strip_prefix(':')
+        && let Some((code, rest)) = rest.split_once(':')
+        && !code.is_empty()
+        && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return (Some(&subject[..code.len() + 2]), rest.trim_start());
+    }
+
+    match subject::clusters(subject).first() {
+        Some(&(_, end)) if subject.chars().next().is_some_and(|c| !c.is_ascii() && !c.is_alphanumeric()) => {
+            (Some(&subject[..end]), subject[end..].trim_start())
+        }
+        _ => (None, subject),
+    }
+}
+
+/// The gitmoji for `subject` (without any emoji) from its conventional
+/// commit type, or failing that its first verb.
+pub fn for_subject(subject: &str) -> &'static str {
+    let kind: String = subject.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+    let rest = &subject[kind.len()..];
+    let is_typed = rest.starts_with(':') || rest.starts_with('(') || rest.starts_with("!:");
+    let kind = kind.to_lowercase();
+
+    if is_typed && let Some((_, emoji)) = TYPES.iter().find(|(name, _)| *name == kind) {
+        return emoji;
+    }
+
+    VERBS
+        .iter()
+        .find(|(verbs, _)| verbs.contains(&kind.as_str()))
+        .map_or(DEFAULT, |(_, emoji)| emoji)
+}
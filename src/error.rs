@@ -0,0 +1,66 @@
+//! Errors that end a git-qwen command, each with its own exit code so
+//! scripts and hooks can tell them apart.
+
+use std::fmt;
+
+/// Why a command failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// There is nothing to describe, such as no staged changes
+    NoChanges(String),
+    /// The model backend is not installed or cannot be found
+    ModelUnavailable(String),
+    /// The model backend ran but gave no usable answer
+    ModelFailed(String),
+    /// The editor could not be run or exited with an error
+    EditorFailed(String),
+    /// A git command git-qwen relies on failed
+    GitFailed(String),
+    /// The user left the message empty
+    EmptyMessage,
+    /// Any other failure
+    Other(String),
+}
+
+impl Error {
+    /// The process exit code for this error, as listed in the README.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Other(_) => 1,
+            Error::NoChanges(_) => 3,
+            Error::ModelUnavailable(_) => 4,
+            Error::ModelFailed(_) => 5,
+            Error::EditorFailed(_) => 6,
+            Error::GitFailed(_) => 7,
+            Error::EmptyMessage => 8,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoChanges(message)
+            | Error::ModelUnavailable(message)
+            | Error::ModelFailed(message)
+            | Error::EditorFailed(message)
+            | Error::GitFailed(message)
+            | Error::Other(message) => f.write_str(message),
+            Error::EmptyMessage => f.write_str("Aborting commit due to empty commit message."),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Other(message)
+    }
+}
+
+impl From<Error> for String {
+    fn from(error: Error) -> Self {
+        error.to_string()
+    }
+}
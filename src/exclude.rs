@@ -0,0 +1,160 @@
+use crate::config::Config;
+use crate::diff;
+use crate::glob;
+use std::fs;
+use std::path::PathBuf;
+
+/// File at the repository root listing globs whose diffs are left out of
+/// the prompt, one per line, in gitignore syntax.
+const IGNORE_FILE: &str = ".gitqwenignore";
+
+/// Lockfiles whose diffs carry no information a commit message needs.
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "go.sum",
+    "Gemfile.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "composer.lock",
+    "flake.lock",
+    "mix.lock",
+    "pubspec.lock",
+    "Podfile.lock",
+    "packages.lock.json",
+];
+
+/// Generated or minified files, matched by name.
+const GENERATED: &[&str] = &["*.min.js", "*.min.css", "*.js.map", "*.css.map"];
+
+/// Markers that tools put at the top of generated sources.
+const GENERATED_MARKERS: &[&str] = &["@generated", "DO NOT EDIT"];
+
+/// Only the first lines of a file are checked for generated markers.
+const MARKER_SCAN_LINES: usize = 20;
+
+/// Removes the sections of files matched by `.gitqwenignore` or the
+/// multi-valued `qwen.exclude` from the diff. Like gitignore, later
+/// patterns win and a leading `!` re-includes a path.
+pub fn strip_excluded(diff: &str, config: &Config) -> String {
+    let patterns = patterns(config);
+    if patterns.is_empty() {
+        return diff.to_string();
+    }
+
+    let files = diff::split_files(diff);
+    let (kept, excluded): (Vec<_>, Vec<_>) = files
+        .iter()
+        .partition(|file| !is_excluded(&patterns, &file.path));
+
+    if excluded.is_empty() {
+        return diff.to_string();
+    }
+
+    if kept.is_empty() {
+        // Still tell the model which files changed rather than nothing at all
+        eprintln!("Warning: every changed file is excluded from the prompt; sending only their names.");
+        let names: Vec<&str> = excluded.iter().map(|file| file.path.as_str()).collect();
+        return format!("Changed files (contents excluded):\n{}\n", names.join("\n"));
+    }
+
+    kept.iter().map(|file| file.text).collect()
+}
+
+/// The patterns of `.gitqwenignore` followed by those of `qwen.exclude`,
+/// in the order they apply.
+pub fn patterns(config: &Config) -> Vec<String> {
+    let mut patterns = read_ignore_file();
+    patterns.extend(config.get_all("exclude").into_iter().map(String::from));
+    patterns
+}
+
+/// Whether `path`, relative to the top of the work tree, is left out of the
+/// prompt by `patterns`.
+pub fn is_excluded(patterns: &[String], path: &str) -> bool {
+    let mut excluded = false;
+    for pattern in patterns {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if glob::matches(negated, path) {
+                excluded = false;
+            }
+        } else if glob::matches(pattern, path) {
+            excluded = true;
+        }
+    }
+    excluded
+}
+
+fn read_ignore_file() -> Vec<String> {
+    let Ok(toplevel) = crate::git::output(&["rev-parse", "--show-toplevel"]) else {
+        return Vec::new();
+    };
+
+    let path = PathBuf::from(toplevel.trim()).join(IGNORE_FILE);
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Replaces the diffs of lockfiles and generated files with a one-line
+/// note, unless `qwen.collapseGenerated` is false. The model still learns
+/// that they changed without their bulk drowning out the real change.
+pub fn collapse_generated(diff: &str, config: &Config) -> String {
+    if !config.get_bool("collapseGenerated").unwrap_or(true) {
+        return diff.to_string();
+    }
+
+    let files = diff::split_files(diff);
+    if files.is_empty() {
+        return diff.to_string();
+    }
+
+    files
+        .iter()
+        .map(|file| match generated_kind(file) {
+            Some(kind) => {
+                let (added, removed) = diff::count_changes(file.text);
+                // Keep the header so the note still reads as a file section
+                let header = file.text.lines().next().unwrap_or_default();
+                format!(
+                    "{}\n{} updated (+{} -{} lines, diff omitted)\n",
+                    header, kind, added, removed
+                )
+            }
+            None => file.text.to_string(),
+        })
+        .collect()
+}
+
+fn generated_kind(file: &diff::FileDiff) -> Option<&'static str> {
+    let name = file.path.rsplit('/').next().unwrap_or(&file.path);
+    if LOCKFILES.contains(&name) {
+        return Some("lockfile");
+    }
+
+    if GENERATED.iter().any(|pattern| glob::matches(pattern, &file.path)) {
+        return Some("generated file");
+    }
+
+    let has_marker = hunk_lines(file.text)
+        .take(MARKER_SCAN_LINES)
+        .any(|line| GENERATED_MARKERS.iter().any(|marker| line.contains(marker)));
+    has_marker.then_some("generated file")
+}
+
+/// Lines of the hunks in a file section, skipping the headers.
+fn hunk_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().skip_while(|line| !line.starts_with("@@")).filter(|line| !line.starts_with("@@"))
+}
@@ -0,0 +1,64 @@
+//! `git-qwen explain [TARGET]`: explains a commit, a range, or the staged
+//! changes in plain language, at more length than a commit message.
+
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use crate::history;
+use crate::output;
+use crate::prompt;
+
+const EXPLAIN_PROMPT: &str = "Explain the change below to a developer who does not know this code. Say what it does and why it was most likely made, then walk through how it works, file by file or step by step, and point out anything surprising, risky, or easy to miss. Refer to files, functions, and settings by name. Write plain prose in a few paragraphs; use a bullet list only for a series of separate steps. Answer with only the explanation.
+
+";
+
+/// Prints an explanation of `target`: a commit, a range like `A..B`, or
+/// the staged changes when there is none.
+pub fn run(target: Option<&str>) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let explanation = explain(target, &Config::load())?;
+    println!("{}", explanation);
+    Ok(())
+}
+
+/// The explanation of `target`, as for `run`.
+pub fn explain(target: Option<&str>, config: &Config) -> Result<String, String> {
+    let mut prompt = format!("{}{}", prompt::DATA_NOTICE.trim_start(), EXPLAIN_PROMPT);
+    let diff = match target {
+        None => {
+            let diff = git::output(&["diff", "--cached"])?;
+            if diff.trim().is_empty() {
+                return Err("No staged changes to explain; name a commit or a range instead".to_string());
+            }
+            prompt.push_str("The change is staged and not committed yet.\n\n");
+            diff
+        }
+        Some(range) if range.contains("..") => {
+            let (base, end) = history::split_range(range);
+            let commits = history::commits(range)?;
+            let budget = prompt::max_chars(config).map(|max| max.saturating_sub(prompt.len() + prompt::FENCE_CHARS) / 2);
+            prompt.push_str("The change is made by these commits, oldest first:\n");
+            prompt.push_str(&prompt::fence(&history::prompt_listing(&commits, budget)));
+            git::output(&["diff", base, end])?
+        }
+        Some(rev) => {
+            let message = git::output(&["log", "-1", "--format=%B", rev, "--"])
+                .map_err(|_| format!("Not a commit: {}", rev))?;
+            prompt.push_str(&format!("The change is commit {}, whose message is:\n", rev));
+            prompt.push_str(&prompt::fence(&message));
+            git::output(&["show", "--format=", rev, "--"])?
+        }
+    };
+
+    let diff = prompt::prepare_diff(&diff, config);
+    prompt.push_str("The diff:\n");
+    prompt.push_str(&prompt::fenced_fit(&diff, prompt.len(), config));
+
+    eprintln!("Asking qwen to explain the change...");
+    let answer = backend::generate(&prompt).map_err(|e| format!("Failed to generate explanation: {}", e))?;
+    let explanation = output::clean_document(&answer);
+    if explanation.is_empty() {
+        return Err("qwen returned an empty explanation".to_string());
+    }
+    Ok(explanation)
+}
@@ -0,0 +1,164 @@
+//! Running git. Commands go through [`GitClient`] so a [`MockGit`] can
+//! stand in for a repository in tests.
+
+use std::cell::RefCell;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// The git commands git-qwen runs.
+pub trait GitClient {
+    /// Runs git with `args` and returns its stdout, failing on a non-zero
+    /// exit.
+    fn output(&self, args: &[&str]) -> Result<String, String>;
+
+    /// Runs git with `args` on the terminal, such as `git commit` opening
+    /// the editor, and returns its exit code.
+    fn status(&self, args: &[&OsStr]) -> Result<i32, String>;
+
+    /// The value of a git config key, if it is set.
+    fn config(&self, key: &str) -> Option<String> {
+        self.output(&["config", key]).ok().map(|value| value.trim().to_string())
+    }
+
+    /// The absolute git directory of the repository, or of the linked
+    /// worktree or submodule git runs in.
+    fn git_dir(&self) -> Result<PathBuf, String> {
+        Ok(PathBuf::from(self.output(&["rev-parse", "--absolute-git-dir"])?.trim()))
+    }
+}
+
+/// Runs the `git` found on `PATH`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemGit;
+
+impl GitClient for SystemGit {
+    fn output(&self, args: &[&str]) -> Result<String, String> {
+        let output = Command::new("git")
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to execute git {}: {}", args.join(" "), e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("git {} failed: {}", args.join(" "), stderr.trim()));
+        }
+
+        String::from_utf8(output.stdout)
+            .map_err(|e| format!("Invalid UTF-8 in git {} output: {}", args.join(" "), e))
+    }
+
+    fn status(&self, args: &[&OsStr]) -> Result<i32, String> {
+        let status = Command::new("git")
+            .args(args)
+            .status()
+            .map_err(|e| format!("Failed to execute git: {}", e))?;
+        Ok(status.code().unwrap_or(1))
+    }
+}
+
+/// A stand-in for git that answers commands from a script and records
+/// every command it is given.
+#[derive(Debug, Default)]
+pub struct MockGit {
+    responses: Vec<(Vec<String>, Result<String, String>)>,
+    exit_code: i32,
+    calls: RefCell<Vec<Vec<String>>>,
+}
+
+impl MockGit {
+    pub fn new() -> Self {
+        MockGit::default()
+    }
+
+    /// Answers `git <args>` with `stdout`.
+    pub fn respond(mut self, args: &[&str], stdout: &str) -> Self {
+        self.responses.push((args.iter().map(|arg| arg.to_string()).collect(), Ok(stdout.to_string())));
+        self
+    }
+
+    /// Makes `git <args>` fail with `error`.
+    pub fn fail(mut self, args: &[&str], error: &str) -> Self {
+        self.responses.push((args.iter().map(|arg| arg.to_string()).collect(), Err(error.to_string())));
+        self
+    }
+
+    /// The exit code of commands run on the terminal, 0 unless set.
+    pub fn exit_code(mut self, code: i32) -> Self {
+        self.exit_code = code;
+        self
+    }
+
+    /// The commands given so far, in order.
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.borrow().clone()
+    }
+}
+
+impl GitClient for MockGit {
+    fn output(&self, args: &[&str]) -> Result<String, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        self.calls.borrow_mut().push(args.clone());
+        match self.responses.iter().find(|(expected, _)| *expected == args) {
+            Some((_, response)) => response.clone(),
+            None => Err(format!("unexpected command: git {}", args.join(" "))),
+        }
+    }
+
+    fn status(&self, args: &[&OsStr]) -> Result<i32, String> {
+        let args = args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+        self.calls.borrow_mut().push(args);
+        Ok(self.exit_code)
+    }
+}
+
+/// Runs git with `args` and returns its stdout, failing on a non-zero exit.
+pub fn output(args: &[&str]) -> Result<String, String> {
+    SystemGit.output(args)
+}
+
+/// Like `output`, but feeds `input` to git's stdin. Meant for filters such
+/// as `git stripspace` that read all input before writing.
+pub fn output_with_input(args: &[&str], input: &str) -> Result<String, String> {
+    let mut child = Command::new("git")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute git {}: {}", args.join(" "), e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| format!("Failed to write to git {}: {}", args.join(" "), e))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to execute git {}: {}", args.join(" "), e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {} failed: {}", args.join(" "), stderr.trim()));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|e| format!("Invalid UTF-8 in git {} output: {}", args.join(" "), e))
+}
+
+/// Absolute path of `name` in the git directory, as git resolves it: in a
+/// linked worktree, files such as hooks are shared with the main worktree
+/// and others are the worktree's own. Being absolute, it stays right after
+/// a change of directory, such as git running a hook from the top level.
+pub fn path(name: &str) -> Result<PathBuf, String> {
+    Ok(PathBuf::from(output(&["rev-parse", "--path-format=absolute", "--git-path", name])?.trim()))
+}
+
+/// Directory for git-qwen's cached data. It lives in the common git
+/// directory so linked worktrees share it.
+pub fn cache_dir() -> Result<PathBuf, String> {
+    let git_dir = output(&["rev-parse", "--path-format=absolute", "--git-common-dir"])?;
+    Ok(PathBuf::from(git_dir.trim()).join("qwen-cache"))
+}
@@ -0,0 +1,63 @@
+/// Matches a repository path against a gitignore-style glob.
+///
+/// Patterns without a slash match a file or directory name at any depth
+/// (`*.lock`, `vendor`). Patterns with a slash are anchored at the
+/// repository root (`docs/*.md`, `/migrations`). A pattern that matches a
+/// directory also matches everything below it, and a trailing slash is
+/// accepted for readability. `*` and `?` stay within one path component
+/// while `**` matches across them.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim().trim_end_matches('/');
+    if pattern.is_empty() {
+        return false;
+    }
+
+    let components: Vec<&str> = path.split('/').collect();
+
+    if !pattern.contains('/') {
+        return components.iter().any(|c| wildcard(pattern, c));
+    }
+
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+
+    // Try the full path and every leading directory of it
+    (1..=components.len()).any(|n| wildcard(pattern, &components[..n].join("/")))
+}
+
+fn wildcard(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    wildcard_at(&pattern, &text)
+}
+
+fn wildcard_at(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            // "**/" may also match zero directories
+            let rest = &pattern[2..];
+            if let Some(after_slash) = rest.strip_prefix(&['/'])
+                && wildcard_at(after_slash, text)
+            {
+                return true;
+            }
+            (0..=text.len()).any(|i| wildcard_at(rest, &text[i..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if wildcard_at(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => {
+            matches!(text.first(), Some(c) if *c != '/') && wildcard_at(&pattern[1..], &text[1..])
+        }
+        Some(p) => text.first() == Some(p) && wildcard_at(&pattern[1..], &text[1..]),
+    }
+}
@@ -0,0 +1,88 @@
+use crate::config::Config;
+use crate::git;
+
+/// Number of recent commit messages shown to the model by default.
+const DEFAULT_RECENT_COMMITS: usize = 5;
+
+/// Long bodies say little more about style than their first lines do.
+const MAX_EXAMPLE_LINES: usize = 15;
+
+/// Returns the messages of the last `qwen.recentCommits` non-merge commits,
+/// newest first, so the model can pick up the repository's conventions.
+pub fn recent_messages(config: &Config) -> Vec<String> {
+    let count = config.get_usize("recentCommits").unwrap_or(DEFAULT_RECENT_COMMITS);
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let max_count = format!("--max-count={}", count);
+    // Fails in a repository without commits, which has no examples to give
+    let Ok(log) = git::output(&["log", "--no-merges", &max_count, "--format=%B%x00"]) else {
+        return Vec::new();
+    };
+
+    log.split('\0')
+        .map(|message| {
+            let lines: Vec<&str> = message.trim().lines().collect();
+            if lines.len() > MAX_EXAMPLE_LINES {
+                format!("{}\n[...]", lines[..MAX_EXAMPLE_LINES].join("\n"))
+            } else {
+                lines.join("\n")
+            }
+        })
+        .filter(|message| !message.is_empty())
+        .collect()
+}
+
+/// A commit in a range, for prompts that describe several commits.
+pub struct Commit {
+    /// Abbreviated object name
+    pub id: String,
+    pub message: String,
+}
+
+/// Splits `A..B` into its ends, reading `A` as `A..HEAD` and `A..` as
+/// `A..HEAD`.
+pub fn split_range(range: &str) -> (&str, &str) {
+    match range.split_once("..") {
+        Some((base, end)) => (base, if end.is_empty() { "HEAD" } else { end }),
+        None => (range, "HEAD"),
+    }
+}
+
+/// The most recent tag reachable from `rev`, if there is one.
+pub fn latest_tag(rev: &str) -> Option<String> {
+    git::output(&["describe", "--tags", "--abbrev=0", rev])
+        .ok()
+        .map(|tag| tag.trim().to_string())
+}
+
+/// The non-merge commits in `range` (anything `git log` accepts), oldest
+/// first.
+pub fn commits(range: &str) -> Result<Vec<Commit>, String> {
+    let log = git::output(&["log", "--no-merges", "--reverse", "--format=%h%x00%B%x00", range, "--"])?;
+    let fields: Vec<&str> = log.split('\0').collect();
+    Ok(fields
+        .chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .map(|pair| Commit {
+            id: pair[0].trim().to_string(),
+            message: pair[1].trim().to_string(),
+        })
+        .collect())
+}
+
+/// Lists `commits` for a prompt, each message under a line with its name.
+/// With a `budget`, commits that no longer fit are only counted.
+pub fn prompt_listing(commits: &[Commit], budget: Option<usize>) -> String {
+    let mut listing = String::new();
+    for (index, commit) in commits.iter().enumerate() {
+        let entry = format!("commit {}\n{}\n\n", commit.id, commit.message);
+        if budget.is_some_and(|budget| listing.len() + entry.len() > budget) {
+            listing.push_str(&format!("[{} more commits not shown]\n", commits.len() - index));
+            break;
+        }
+        listing.push_str(&entry);
+    }
+    listing
+}
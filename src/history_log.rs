@@ -0,0 +1,159 @@
+//! An append-only log of the messages git-qwen generated, in
+//! `.git/qwen-history.jsonl`, recording each prompt, the message qwen
+//! wrote, and whether it was committed as is, edited, or rejected.
+//! `git-qwen history` shows it.
+
+use crate::config::Config;
+use crate::git;
+use serde_json::{Value, json};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the log file in the git directory.
+const LOG_FILE: &str = "qwen-history.jsonl";
+
+/// Entries shown by `git-qwen history` by default.
+const DEFAULT_COUNT: usize = 20;
+
+/// What became of a generated message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Committed unchanged.
+    Accepted,
+    /// Committed after the user changed it.
+    Edited,
+    /// Not committed: discarded, aborted, or refused by git or a hook.
+    Rejected,
+}
+
+impl Outcome {
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Accepted => "accepted",
+            Outcome::Edited => "edited",
+            Outcome::Rejected => "rejected",
+        }
+    }
+
+    /// Accepted or edited, depending on whether `committed` differs from
+    /// `generated` by more than trailing whitespace.
+    pub fn of_commit(generated: &str, committed: &str) -> Self {
+        let normalize = |text: &str| text.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim().to_string();
+        if normalize(generated) == normalize(committed) {
+            Outcome::Accepted
+        } else {
+            Outcome::Edited
+        }
+    }
+}
+
+/// Appends an entry for a generated message, unless qwen.history is off.
+/// `committed` is the message as committed or as last seen, and `commit`
+/// the new commit. Failures only warn, since the log is never needed to
+/// commit.
+pub fn record(config: &Config, prompt: &str, generated: &str, committed: Option<&str>, outcome: Outcome) {
+    if !config.get_bool("history").unwrap_or(true) {
+        return;
+    }
+
+    let commit = match outcome {
+        Outcome::Rejected => None,
+        _ => git::output(&["rev-parse", "HEAD"]).ok().map(|sha| sha.trim().to_string()),
+    };
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+    let entry = json!({
+        "time": time,
+        "outcome": outcome.name(),
+        "commit": commit,
+        "prompt": prompt,
+        "generated": generated,
+        "final": committed,
+    });
+
+    let appended = log_path().and_then(|path| {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        writeln!(file, "{}", entry).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    });
+    if let Err(e) = appended {
+        eprintln!("Warning: {}", e);
+    }
+}
+
+/// Prints the last `max_count` entries, newest first: one line each, the
+/// messages too with `verbose`, or the raw entries with `json`.
+pub fn run(max_count: Option<usize>, verbose: bool, json: bool) -> Result<(), String> {
+    let path = log_path()?;
+    let log = match fs::read_to_string(&path) {
+        Ok(log) => log,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    let entries: Vec<Value> = log.lines().filter_map(|line| serde_json::from_str(line).ok()).collect();
+    if entries.is_empty() && !json {
+        eprintln!("No generated messages recorded yet.");
+        return Ok(());
+    }
+
+    for entry in entries.iter().rev().take(max_count.unwrap_or(DEFAULT_COUNT)) {
+        if json {
+            println!("{}", entry);
+            continue;
+        }
+
+        let text = |key: &str| entry[key].as_str().unwrap_or("").to_string();
+        let commit = text("commit");
+        let message = entry["final"].as_str().unwrap_or_else(|| entry["generated"].as_str().unwrap_or(""));
+        println!(
+            "{}  {:<8}  {:<7}  {}",
+            format_time(entry["time"].as_u64().unwrap_or(0)),
+            text("outcome"),
+            commit.get(..7).unwrap_or("-"),
+            message.lines().next().unwrap_or("")
+        );
+
+        if verbose {
+            println!("  Generated:");
+            for line in text("generated").lines() {
+                println!("    {}", line);
+            }
+            if entry["final"].is_string() && text("final") != text("generated") {
+                println!("  Final:");
+                for line in text("final").lines() {
+                    println!("    {}", line);
+                }
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
+
+fn log_path() -> Result<PathBuf, String> {
+    git::path(LOG_FILE)
+}
+
+/// `secs` since the epoch as `YYYY-MM-DD HH:MM` in UTC.
+fn format_time(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let minutes = secs % 86400 / 60;
+
+    // Civil date from a day count, after Howard Hinnant's algorithm
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, minutes / 60, minutes % 60)
+}
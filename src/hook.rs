@@ -0,0 +1,154 @@
+use crate::cli::HookAction;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Marker identifying hooks written by `git-qwen hook install`.
+const HOOK_MARKER: &str = "# Installed by git-qwen";
+
+pub fn run(action: HookAction) -> Result<(), String> {
+    match action {
+        HookAction::Install { force } => install(force),
+        HookAction::Uninstall => uninstall(),
+        HookAction::Run { file, source, .. } => {
+            // A failing prepare-commit-msg hook aborts the commit, so report
+            // problems but always let git continue with its own template
+            if let Err(e) = prepare_message(&file, source.as_deref()) {
+                eprintln!("git-qwen: {}", e);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn hook_path() -> Result<PathBuf, String> {
+    crate::git::path("hooks/prepare-commit-msg")
+}
+
+/// Whether the hook installed by `git-qwen hook install` is in place.
+pub fn is_installed() -> bool {
+    hook_path().is_ok_and(|path| is_git_qwen_hook(&path))
+}
+
+fn is_git_qwen_hook(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.contains(HOOK_MARKER))
+        .unwrap_or(false)
+}
+
+fn install(force: bool) -> Result<(), String> {
+    let path = hook_path()?;
+
+    if path.exists() && !force && !is_git_qwen_hook(&path) {
+        return Err(format!(
+            "{} already exists. Use --force to replace it.",
+            path.display()
+        ));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create hooks directory: {}", e))?;
+    }
+
+    let script = format!("#!/bin/sh\n{}\nexec git-qwen hook run \"$@\"\n", HOOK_MARKER);
+    fs::write(&path, script).map_err(|e| format!("Failed to write hook: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make hook executable: {}", e))?;
+    }
+
+    println!("Installed {}", path.display());
+    Ok(())
+}
+
+fn uninstall() -> Result<(), String> {
+    let path = hook_path()?;
+
+    if !path.exists() {
+        println!("No prepare-commit-msg hook installed.");
+        return Ok(());
+    }
+
+    if !is_git_qwen_hook(&path) {
+        return Err(format!("{} was not installed by git-qwen; leaving it alone.", path.display()));
+    }
+
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove hook: {}", e))?;
+    println!("Removed {}", path.display());
+    Ok(())
+}
+
+/// Prepends a generated message to the file git is about to open in the editor.
+fn prepare_message(file: &Path, source: Option<&str>) -> Result<(), String> {
+    let existing = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read commit message file: {}", e))?;
+
+    // When `git rebase -i` squashes commits, the file holds their messages
+    if let Some((comment, messages)) = crate::squash::rebase_messages(&existing) {
+        return prepare_squash_message(file, &existing, &comment, messages);
+    }
+
+    // Any source (message, template, merge, squash, commit) means git
+    // already has a message to show, so leave it untouched
+    if source.is_some_and(|s| !s.is_empty()) {
+        return Ok(());
+    }
+
+    crate::capabilities::require_backend()?;
+
+    let diff = crate::DiffSource::Staged.read(&[])?;
+    if diff.trim().is_empty() {
+        return Ok(());
+    }
+
+    let config = crate::config::Config::load();
+    let options = crate::prompt::PromptOptions {
+        untracked: crate::untracked::files(),
+        ..Default::default()
+    };
+    let generator = crate::MessageGenerator::new(&config, &options);
+    let prompt = generator.prompt(&diff)?;
+    let mut message = generator.answer(&prompt)?;
+    let footer = crate::trailers::assisted_by(&prompt, &config)
+        .map(|assisted_by| crate::trailers::block(&message, &[assisted_by], None, &[]))
+        .unwrap_or_default();
+    if !footer.is_empty() {
+        message = format!("{}\n\n{}", message, footer.trim_end());
+    }
+
+    fs::write(file, format!("{}\n{}", message, existing))
+        .map_err(|e| format!("Failed to write commit message file: {}", e))
+}
+
+/// Replaces the messages git collected for a squash with one synthesized
+/// from them. The originals stay below it, commented out, for reference.
+fn prepare_squash_message(file: &Path, existing: &str, comment: &str, messages: Vec<String>) -> Result<(), String> {
+    if messages.len() < 2 {
+        return Ok(());
+    }
+
+    crate::capabilities::require_backend()?;
+
+    let diff = crate::squash::rebase_diff()?;
+    let config = crate::config::Config::load();
+    let options = crate::prompt::PromptOptions {
+        squashed: messages,
+        ..Default::default()
+    };
+    let message = crate::MessageGenerator::new(&config, &options).generate(&diff)?;
+
+    let originals: Vec<String> = existing
+        .lines()
+        .map(|line| match line {
+            _ if line.starts_with(comment) => line.to_string(),
+            "" => comment.to_string(),
+            _ => format!("{} {}", comment, line),
+        })
+        .collect();
+
+    fs::write(file, format!("{}\n\n{}\n", message, originals.join("\n")))
+        .map_err(|e| format!("Failed to write commit message file: {}", e))
+}
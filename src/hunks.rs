@@ -0,0 +1,182 @@
+//! Staged changes as individual hunks, and committing them in groups.
+//! Shared by the subcommands that turn one set of staged changes into
+//! several commits.
+
+use crate::config::Config;
+use crate::exclude;
+use crate::git;
+use crate::redact;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// A file in a zero-context diff, with its hunks.
+pub struct FilePatch {
+    /// The `diff --git` line and the headers up to the first hunk
+    pub header: String,
+    /// Path of the file before the change, or None for a new file
+    pub old_path: Option<String>,
+    /// Path of the file after the change, or before it for a deletion
+    pub path: String,
+    pub hunks: Vec<Hunk>,
+}
+
+/// One zero-context hunk, starting with its `@@` line.
+pub struct Hunk {
+    pub text: String,
+    pub old_start: usize,
+    pub old_count: usize,
+}
+
+/// The staged changes, one hunk per changed block of lines. Renames show
+/// as a deletion and an addition, so every hunk applies on its own.
+pub fn staged() -> Result<Vec<FilePatch>, String> {
+    let diff = git::output(&["diff", "--cached", "-U0", "--no-renames", "--no-color", "--no-ext-diff"])?;
+    Ok(parse(&diff))
+}
+
+/// Splits a zero-context diff into files and hunks.
+pub fn parse(diff: &str) -> Vec<FilePatch> {
+    let mut files: Vec<FilePatch> = Vec::new();
+
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            files.push(FilePatch {
+                header: line.to_string(),
+                old_path: None,
+                path: String::new(),
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else { continue };
+
+        if let Some(range) = line.strip_prefix("@@ -") {
+            let old = range.split(' ').next().unwrap_or("");
+            let (start, count) = match old.split_once(',') {
+                Some((start, count)) => (start.parse().unwrap_or(0), count.parse().unwrap_or(0)),
+                None => (old.parse().unwrap_or(0), 1),
+            };
+            file.hunks.push(Hunk {
+                text: line.to_string(),
+                old_start: start,
+                old_count: count,
+            });
+        } else if let Some(hunk) = file.hunks.last_mut() {
+            hunk.text.push_str(line);
+        } else {
+            let line_path = |prefix: &str| line.strip_prefix(prefix).map(|path| path.trim_end_matches('\n').to_string());
+            if let Some(path) = line_path("--- a/") {
+                file.path = path.clone();
+                file.old_path = Some(path);
+            } else if let Some(path) = line_path("+++ b/") {
+                file.path = path;
+            }
+            file.header.push_str(line);
+        }
+    }
+
+    files
+}
+
+/// The (file, hunk) indexes of every hunk, in the order they are numbered
+/// for the model.
+pub fn numbered(files: &[FilePatch]) -> Vec<(usize, usize)> {
+    files
+        .iter()
+        .enumerate()
+        .flat_map(|(file_index, file)| (0..file.hunks.len()).map(move |hunk_index| (file_index, hunk_index)))
+        .collect()
+}
+
+/// Lists the `numbered` hunks as "Hunk N (path):" sections for a prompt.
+/// With a `budget`, it is shared between the hunks so a huge one cannot
+/// crowd out the others, and longer hunks are cut at a line boundary. As
+/// in the commit prompt, hunks of files excluded by `.gitqwenignore` or
+/// `qwen.exclude` are listed without their lines, and secrets are masked.
+pub fn prompt_listing(files: &[FilePatch], numbered: &[(usize, usize)], budget: Option<usize>, config: &Config) -> String {
+    let per_hunk = budget.map(|budget| budget / numbered.len().max(1));
+    let patterns = exclude::patterns(config);
+
+    let mut listing = String::new();
+    for (number, (file_index, hunk_index)) in numbered.iter().enumerate() {
+        let file = &files[*file_index];
+        let text = &file.hunks[*hunk_index].text;
+        listing.push_str(&format!("Hunk {} ({}):\n", number + 1, file.path));
+        if exclude::is_excluded(&patterns, &file.path) {
+            listing.push_str("(contents excluded)\n\n");
+            continue;
+        }
+        match per_hunk {
+            Some(limit) if text.len() > limit => {
+                let mut cut = limit;
+                while !text.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                let shown = &text[..text[..cut].rfind('\n').map_or(0, |end| end + 1)];
+                listing.push_str(shown);
+                listing.push_str(&format!("[{} more lines]\n", text[shown.len()..].lines().count()));
+            }
+            _ => listing.push_str(text),
+        }
+        listing.push('\n');
+    }
+    redact::redact_diff(&listing, config)
+}
+
+/// Commits the hunks in `groups` commits. `group_of(file, hunk)` says which
+/// group a hunk belongs to, if any, and `commit(group)` runs once that
+/// group is staged. Each group is staged as the original HEAD plus the
+/// hunks of all groups so far, so the hunk positions stay valid. Whatever
+/// happens, the index ends up with everything that was staged, which is the
+/// new HEAD plus the hunks in no group.
+pub fn commit_groups(
+    files: &[FilePatch],
+    group_of: impl Fn(usize, usize) -> Option<usize>,
+    groups: usize,
+    mut commit: impl FnMut(usize) -> Result<(), String>,
+) -> Result<(), String> {
+    let staged_tree = git::output(&["write-tree"])?;
+    let head_tree = git::output(&["rev-parse", "HEAD^{tree}"])?;
+
+    let mut result = Ok(());
+    for group in 0..groups {
+        let mut patch = String::new();
+        for (file_index, file) in files.iter().enumerate() {
+            let hunks: Vec<&Hunk> = file
+                .hunks
+                .iter()
+                .enumerate()
+                .filter(|(hunk_index, _)| group_of(file_index, *hunk_index).is_some_and(|g| g <= group))
+                .map(|(_, hunk)| hunk)
+                .collect();
+            if !hunks.is_empty() {
+                patch.push_str(&file.header);
+                hunks.iter().for_each(|hunk| patch.push_str(&hunk.text));
+            }
+        }
+
+        result = git::output(&["read-tree", head_tree.trim()])
+            .and_then(|_| git::output_with_input(&["apply", "--cached", "--unidiff-zero", "-"], &patch))
+            .and_then(|_| commit(group));
+        if result.is_err() {
+            break;
+        }
+    }
+
+    git::output(&["read-tree", staged_tree.trim()])?;
+    result
+}
+
+/// Asks a yes/no `question`, defaulting to no. Without a terminal nothing
+/// is committed; the caller's --yes skips the question.
+pub fn confirm(question: &str) -> bool {
+    if !io::stdin().is_terminal() {
+        eprintln!("Not a terminal; run with --yes to create the commits.");
+        return false;
+    }
+
+    eprint!("{} [y/N] ", question);
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).unwrap_or(0) > 0
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
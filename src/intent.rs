@@ -0,0 +1,139 @@
+//! The intent behind the code a change rewrites. `git blame` finds the
+//! commits that wrote the modified and removed lines, and their messages
+//! go in the prompt, so the model can say why the code changes rather
+//! than only what changes.
+
+use crate::config::Config;
+use crate::diff::{self, FileDiff};
+use crate::git;
+use crate::prompt;
+use std::collections::HashMap;
+
+/// Files blamed; the rest of a large change goes without.
+const MAX_FILES: usize = 10;
+
+/// Earlier commits shown, those that wrote the most lines first.
+const MAX_COMMITS: usize = 5;
+
+/// Characters kept of each earlier message.
+const MAX_MESSAGE_CHARS: usize = 600;
+
+/// Lists the messages of the commits that wrote the lines `diff` modifies
+/// or removes, unless `qwen.blame` is false.
+pub fn prompt_section(diff: &str, config: &Config) -> Option<String> {
+    if !config.get_bool("blame").unwrap_or(true) {
+        return None;
+    }
+
+    let mut lines_by_commit: HashMap<String, usize> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+    for file in diff::split_files(diff).iter().take(MAX_FILES) {
+        for commit in blame(file).unwrap_or_default() {
+            let count = lines_by_commit.entry(commit.clone()).or_insert(0);
+            if *count == 0 {
+                order.push(commit);
+            }
+            *count += 1;
+        }
+    }
+    if order.is_empty() {
+        return None;
+    }
+    // Stable, so commits writing as many lines keep the order they were met
+    order.sort_by_key(|commit| std::cmp::Reverse(lines_by_commit[commit]));
+
+    let mut section = String::from(
+        "\nThe lines this change modifies or removes were written by the commits below. Where it helps, use their intent to explain why the code is changing:\n",
+    );
+    let mut messages: Vec<String> = Vec::new();
+    for commit in order.iter().take(MAX_COMMITS) {
+        let Ok(message) = git::output(&["log", "-1", "--format=%B", commit]) else {
+            continue;
+        };
+        let message = message.trim();
+        let message = match message.char_indices().nth(MAX_MESSAGE_CHARS) {
+            Some((at, _)) => format!("{}…", &message[..at]),
+            None => message.to_string(),
+        };
+        messages.push(message);
+    }
+    section.push_str(&prompt::fence(&prompt::separated(&messages)));
+    Some(section)
+}
+
+/// The commit that wrote each modified or removed line of `file`, one
+/// entry per line.
+fn blame(file: &FileDiff) -> Option<Vec<String>> {
+    let ranges = removed_ranges(file.text);
+    if ranges.is_empty() {
+        return None;
+    }
+    let old_path = file.old_path();
+    let revision = old_revision(file, &old_path)?;
+
+    let mut args = vec!["blame".to_string(), "--porcelain".to_string()];
+    for (start, end) in ranges {
+        args.push(format!("-L{},{}", start, end));
+    }
+    args.extend([revision, "--".to_string(), old_path]);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let porcelain = git::output(&args).ok()?;
+
+    // Each blamed line starts with a header naming its commit
+    Some(
+        porcelain
+            .lines()
+            .filter_map(|line| {
+                let commit = line.split(' ').next()?;
+                let is_header = commit.len() == 40 && commit.bytes().all(|b| b.is_ascii_hexdigit());
+                is_header.then(|| commit.to_string())
+            })
+            .collect(),
+    )
+}
+
+/// The 1-based, inclusive line ranges of the old file that `text` removes.
+fn removed_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut old_line = 0;
+    for line in text.lines().skip_while(|line| !line.starts_with("@@")) {
+        if line.starts_with("@@") {
+            old_line = hunk_old_start(line).unwrap_or(1);
+            continue;
+        }
+        match line.chars().next() {
+            Some('-') => {
+                match ranges.last_mut() {
+                    Some((_, end)) if *end + 1 == old_line => *end = old_line,
+                    _ => ranges.push((old_line, old_line)),
+                }
+                old_line += 1;
+            }
+            Some(' ') => old_line += 1,
+            _ => {}
+        }
+    }
+    ranges
+}
+
+/// The first line of the old side of a hunk, from its `@@ -a,b +c,d @@`
+/// header.
+fn hunk_old_start(header: &str) -> Option<usize> {
+    let old = header.split_whitespace().find(|part| part.starts_with('-'))?;
+    old[1..].split(',').next()?.parse().ok()
+}
+
+/// A commit whose copy of `old_path` is the old side of `file`: `HEAD` for
+/// staged and unstaged changes, its parent when amending.
+fn old_revision(file: &FileDiff, old_path: &str) -> Option<String> {
+    let ids = file.text.lines().find_map(|line| line.strip_prefix("index "))?;
+    let old = ids.split(' ').next()?.split_once("..")?.0;
+    if old.bytes().all(|b| b == b'0') {
+        return None;
+    }
+
+    ["HEAD", "HEAD^"].into_iter().find_map(|revision| {
+        let blob = git::output(&["rev-parse", "--verify", "-q", &format!("{}:{}", revision, old_path)]).ok()?;
+        blob.trim().starts_with(old).then(|| revision.to_string())
+    })
+}
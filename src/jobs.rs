@@ -0,0 +1,241 @@
+//! Running model calls in parallel. Calls run on a bounded pool of threads
+//! with progress on stderr; batches that may be interrupted record each
+//! result in a journal under `.git/qwen-cache/jobs` so a rerun picks up
+//! where the last one stopped, and `qwen.<backend>.jobs` caps how many
+//! calls a backend serves at once.
+
+use crate::git;
+use crate::spinner;
+use serde_json::{Map, Value};
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+/// Default number of model calls to run at once.
+pub const DEFAULT_JOBS: usize = 4;
+
+/// Calls running on each backend, for `qwen.<backend>.jobs`.
+static RUNNING: Mutex<Vec<(&'static str, usize)>> = Mutex::new(Vec::new());
+
+/// Signalled when a call ends and its backend has room again.
+static SLOT_FREED: Condvar = Condvar::new();
+
+/// Runs `job` for every item on up to `limit` threads and returns the
+/// results in the order of `items`.
+///
+/// After the first failure no new jobs are started and that error is
+/// returned once the running ones finish. Progress is shown on stderr as
+/// `<label>: done/total`.
+pub fn run_parallel<T, R, F>(items: &[T], limit: usize, label: &str, job: F) -> Result<Vec<R>, String>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> Result<R, String> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..items.len()).map(|_| None).collect());
+    let first_error: Mutex<Option<String>> = Mutex::new(None);
+    let progress = Progress::new(label, items.len());
+
+    thread::scope(|scope| {
+        for _ in 0..limit.clamp(1, items.len().max(1)) {
+            scope.spawn(|| {
+                while !stop.load(Ordering::SeqCst) {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+
+                    match job(item) {
+                        Ok(result) => {
+                            results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+                            progress.update(done.fetch_add(1, Ordering::SeqCst) + 1);
+                        }
+                        Err(e) => {
+                            stop.store(true, Ordering::SeqCst);
+                            first_error.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert(e);
+                        }
+                    }
+                }
+            });
+        }
+    });
+    progress.finish();
+
+    if let Some(e) = first_error.into_inner().unwrap_or_else(|e| e.into_inner()) {
+        return Err(e);
+    }
+
+    Ok(results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+/// Like `run_parallel` for jobs whose results are text, keeping each
+/// result in `journal` as soon as it is ready under `key(item)`. A run that
+/// failed or was interrupted resumes where it stopped: items that already
+/// have a result are not run again.
+pub fn run_resumable<T, K, F>(
+    journal: &Journal,
+    items: &[T],
+    limit: usize,
+    label: &str,
+    key: K,
+    job: F,
+) -> Result<Vec<String>, String>
+where
+    T: Sync,
+    K: Fn(&T) -> String + Sync,
+    F: Fn(&T) -> Result<String, String> + Sync,
+{
+    let kept = items.iter().filter(|item| journal.get(&key(item)).is_some()).count();
+    if kept > 0 {
+        eprintln!("{}: resuming, {} of {} already done", label, kept, items.len());
+    }
+
+    run_parallel(items, limit, label, |item| {
+        let key = key(item);
+        if let Some(result) = journal.get(&key) {
+            return Ok(result);
+        }
+        let result = job(item)?;
+        journal.record(&key, &result);
+        Ok(result)
+    })
+    .map_err(|e| {
+        let done = items.iter().filter(|item| journal.get(&key(item)).is_some()).count();
+        format!(
+            "{}\n{} of {} done and kept; run the command again to continue from there",
+            e,
+            done,
+            items.len()
+        )
+    })
+}
+
+/// The results of a batch run, kept in the cache directory as they come
+/// in, so that a run cut short does not lose them.
+pub struct Journal {
+    path: PathBuf,
+    results: Mutex<Map<String, Value>>,
+}
+
+impl Journal {
+    /// Opens the journal called `name`, with the results kept by earlier
+    /// runs.
+    pub fn open(name: &str) -> Result<Self, String> {
+        let path = git::cache_dir()?.join("jobs").join(format!("{}.json", name));
+        let results = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<Map<String, Value>>(&text).ok())
+            .unwrap_or_default();
+        Ok(Journal {
+            path,
+            results: Mutex::new(results),
+        })
+    }
+
+    /// The result kept for `key`.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let results = self.results.lock().unwrap_or_else(|e| e.into_inner());
+        results.get(key).and_then(Value::as_str).map(String::from)
+    }
+
+    /// Keeps `result` for `key`. A journal that cannot be written only
+    /// costs the ability to resume, so that is a warning.
+    pub fn record(&self, key: &str, result: &str) {
+        let mut results = self.results.lock().unwrap_or_else(|e| e.into_inner());
+        results.insert(key.to_string(), Value::from(result));
+        let written = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&self.path, Value::Object(results.clone()).to_string()));
+        if let Err(e) = written {
+            spinner::message(&format!("Warning: failed to write {}: {}", self.path.display(), e));
+        }
+    }
+
+    /// Deletes the journal once its run is complete.
+    pub fn remove(self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A call running on a backend, counted against its limit until dropped.
+pub struct Slot {
+    backend: &'static str,
+}
+
+/// Waits until `backend` runs fewer than `limit` calls and takes a slot for
+/// one more. Without a limit there is nothing to wait for.
+pub fn backend_slot(backend: &'static str, limit: Option<usize>) -> Option<Slot> {
+    let limit = limit?.max(1);
+    let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+        let count = match running.iter_mut().find(|(name, _)| *name == backend) {
+            Some((_, count)) => count,
+            None => {
+                running.push((backend, 0));
+                &mut running.last_mut().expect("just pushed").1
+            }
+        };
+        if *count < limit {
+            *count += 1;
+            return Some(Slot { backend });
+        }
+        running = SLOT_FREED.wait(running).unwrap_or_else(|e| e.into_inner());
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, count)) = running.iter_mut().find(|(name, _)| *name == self.backend) {
+            *count = count.saturating_sub(1);
+        }
+        SLOT_FREED.notify_all();
+    }
+}
+
+/// A single status line on stderr, redrawn in place on a terminal.
+struct Progress<'a> {
+    label: &'a str,
+    total: usize,
+    interactive: bool,
+}
+
+impl<'a> Progress<'a> {
+    fn new(label: &'a str, total: usize) -> Self {
+        Progress {
+            label,
+            total,
+            interactive: io::stderr().is_terminal(),
+        }
+    }
+
+    fn update(&self, done: usize) {
+        // Inside a generation the spinner shows the progress instead
+        if spinner::set_status(&format!("{}: {}/{}", self.label, done, self.total)) {
+            return;
+        }
+        if self.interactive {
+            eprint!("\r{}: {}/{}", self.label, done, self.total);
+            let _ = io::stderr().flush();
+        }
+    }
+
+    fn finish(&self) {
+        if self.interactive && !spinner::set_status("") {
+            eprintln!();
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! Secrets stored in the system keyring, so an API key need not sit in an
+//! environment variable. They are stored under the service `git-qwen`
+//! with the backend's name as the account, e.g.
+//! `secret-tool store --label=git-qwen service git-qwen account openai`.
+
+use crate::capabilities;
+use std::process::{Command, Stdio};
+
+const SERVICE: &str = "git-qwen";
+
+/// The secret stored for `account`, if there is a keyring tool and it has
+/// one.
+pub fn secret(account: &str) -> Option<String> {
+    let tool = capabilities::keyring()?;
+    let args = match tool {
+        "security" => vec!["find-generic-password", "-s", SERVICE, "-a", account, "-w"],
+        _ => vec!["lookup", "service", SERVICE, "account", account],
+    };
+    let output = Command::new(tool).args(args).stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !secret.is_empty()).then_some(secret)
+}
@@ -0,0 +1,160 @@
+//! git-qwen writes commit messages with a local Qwen model.
+//!
+//! Besides the `git-qwen` command, the crate exposes the stages of writing
+//! a message for other tools to embed: a [`DiffSource`] reads the changes,
+//! a [`MessageGenerator`] asks the model for a message, a
+//! [`MessageFormatter`] lays its answer out, and a [`CommitExecutor`]
+//! commits it.
+
+mod absorb;
+pub mod amend;
+mod backend;
+mod binary;
+mod branch;
+mod cache;
+mod cancel;
+mod capabilities;
+mod changelog;
+mod cleanup;
+mod clipboard;
+pub mod cli;
+mod commit;
+mod commit_args;
+pub mod config;
+mod conflicts;
+mod context;
+mod daemon;
+mod diff;
+mod doctor;
+mod editor;
+mod emoji;
+mod error;
+mod exclude;
+mod explain;
+pub mod git;
+mod glob;
+mod history;
+mod history_log;
+mod hook;
+mod hunks;
+mod intent;
+mod jobs;
+mod keyring;
+mod lint;
+mod lock;
+mod mapreduce;
+mod mock;
+mod note;
+mod output;
+pub mod pipeline;
+mod pr;
+mod prefix;
+mod probe;
+pub mod prompt;
+mod redact;
+mod remote;
+mod release_notes;
+mod review;
+mod reword;
+mod search;
+mod signing;
+mod similar;
+mod spinner;
+mod split;
+mod squash;
+mod stage;
+mod stash;
+mod state;
+mod style;
+mod subject;
+mod submodule;
+mod summary;
+mod symbols;
+mod tag;
+mod template;
+mod trailers;
+mod truncate;
+mod untracked;
+mod verify;
+mod watch;
+mod wrap;
+
+pub use error::Error;
+pub use pipeline::{CommitExecutor, DiffSource, MessageFormatter, MessageGenerator};
+
+use cli::{Cli, Command as CliCommand};
+use config::Config;
+
+/// Runs the command line `cli` describes.
+pub fn run(cli: Cli) -> Result<(), Error> {
+    cancel::install_handler();
+    if let Some(name) = &cli.backend {
+        backend::select(name)?;
+    }
+    if let Some(name) = &cli.model {
+        backend::select_model(name);
+    }
+    backend::select_sampling(cli.temperature, cli.seed);
+
+    match cli.command {
+        None => commit::run(&cli.commit),
+        Some(CliCommand::Commit(opts)) => commit::run(&opts),
+        Some(CliCommand::Amend(mut opts)) => {
+            opts.git_args.insert(0, "--amend".to_string());
+            commit::run(&opts)
+        }
+        Some(CliCommand::Squash { base, opts }) => {
+            let squash = squash::Squash::load(&base)?;
+            commit::generate_and_commit(&opts, &Config::load(), Some(&squash))
+        }
+        Some(command) => run_subcommand(command),
+    }
+}
+
+/// Runs the subcommands other than those that commit.
+fn run_subcommand(command: CliCommand) -> Result<(), Error> {
+    let result = match command {
+        CliCommand::Commit(_) | CliCommand::Amend(_) | CliCommand::Squash { .. } => {
+            unreachable!("committing subcommands are run by `run`")
+        }
+        CliCommand::Reword { range, todo, message } => match (range, todo, message) {
+            (_, Some(todo), _) => reword::edit_todo(&todo),
+            (_, _, Some(message)) => reword::edit_message(&message),
+            (Some(range), None, None) => reword::run(&range),
+            (None, None, None) => unreachable!("clap requires a range"),
+        },
+        CliCommand::Absorb { yes, dry_run } => absorb::run(yes, dry_run),
+        CliCommand::Split { yes, dry_run, edit } => split::run(yes, dry_run, edit),
+        CliCommand::Tag { name, range, sign } => tag::run(&name, range.as_deref(), sign),
+        CliCommand::Changelog { range, version, style, write } => {
+            changelog::run(range.as_deref(), version.as_deref(), style.as_deref(), write)
+        }
+        CliCommand::ReleaseNotes { tag } => release_notes::run(&tag),
+        CliCommand::Pr { base, create, draft, copy } => pr::run(base.as_deref(), create, draft, copy),
+        CliCommand::Branch { intent, create } => branch::run(&intent.join(" "), create),
+        CliCommand::Explain { target } => explain::run(target.as_deref()),
+        CliCommand::Review { all } => review::run(all),
+        CliCommand::Stash { git_args } => stash::run(&git_args),
+        CliCommand::Note { rev, force } => note::run(&rev, force),
+        CliCommand::Search { query, max_count } => search::run(&query.join(" "), max_count),
+        CliCommand::Learn => style::learn(),
+        CliCommand::History { max_count, verbose, json } => history_log::run(max_count, verbose, json),
+        CliCommand::Hook { action } => hook::run(action),
+        CliCommand::Config { action } => config::run(action),
+        CliCommand::Doctor { json } => doctor::run(json),
+        CliCommand::Daemon { stop } => daemon::run(stop),
+        CliCommand::Watch => watch::run(),
+        CliCommand::DiffSummary { refs, staged: _, stdin } => {
+            let input = if stdin {
+                summary::DiffInput::Stdin
+            } else if refs.is_empty() {
+                summary::DiffInput::Staged
+            } else {
+                summary::DiffInput::Refs(refs)
+            };
+            summary::run(input)
+        }
+    };
+    result.map_err(Error::from)
+}
+
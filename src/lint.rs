@@ -0,0 +1,338 @@
+//! Checks generated messages against common commit message conventions,
+//! with rule names borrowed from commitlint.
+
+use crate::config::Config;
+
+/// Default longest subject, in characters.
+const SUBJECT_MAX_LENGTH: usize = 50;
+
+/// Default body wrap width, in characters.
+const BODY_MAX_LINE_LENGTH: usize = 72;
+
+/// Default number of times the model is asked to fix its own message in
+/// `retry` mode.
+const DEFAULT_RETRIES: usize = 1;
+
+/// Verbs commonly used to start a subject, in their imperative form. The
+/// mood check only looks at subjects starting with one of these, so
+/// unusual words are never "corrected".
+const VERBS: &[&str] = &[
+    "add", "adjust", "allow", "apply", "avoid", "bump", "change", "check", "clean", "clarify",
+    "convert", "correct", "create", "define", "delete", "deprecate", "detect", "disable",
+    "document", "drop", "enable", "ensure", "expose", "extend", "extract", "fix", "guard",
+    "handle", "hide", "ignore", "implement", "improve", "include", "increase", "initialize",
+    "introduce", "limit", "load", "log", "make", "merge", "migrate", "move", "optimize", "parse",
+    "pass", "prefer", "prevent", "print", "read", "reduce", "refactor", "remove", "rename",
+    "reorder", "replace", "report", "require", "reset", "resolve", "restore", "restrict",
+    "return", "reuse", "revert", "rewrite", "run", "set", "show", "simplify", "skip", "sort",
+    "split", "start", "stop", "store", "strip", "support", "switch", "test", "tidy", "track",
+    "update", "upgrade", "use", "validate", "warn", "wrap", "write",
+];
+
+/// Past tense forms that do not follow the usual rules.
+const IRREGULAR: &[(&str, &str)] = &[
+    ("made", "make"),
+    ("ran", "run"),
+    ("wrote", "write"),
+    ("rewrote", "rewrite"),
+    ("hid", "hide"),
+];
+
+/// Line lengths the message should keep to, from `qwen.subjectLimit` and
+/// `qwen.wrapWidth`. None means there is no limit: the subject may be any
+/// length, or body paragraphs are left unwrapped.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub subject: Option<usize>,
+    pub wrap: Option<usize>,
+}
+
+impl Limits {
+    pub fn from_config(config: &Config) -> Self {
+        Limits {
+            subject: limit(config, "subjectLimit", SUBJECT_MAX_LENGTH),
+            wrap: limit(config, "wrapWidth", BODY_MAX_LINE_LENGTH),
+        }
+    }
+}
+
+/// Reads a length setting, where `0`, `none`, or `off` disables the limit.
+fn limit(config: &Config, key: &str, default: usize) -> Option<usize> {
+    let Some(value) = config.get(key) else {
+        return Some(default);
+    };
+    match value.trim().to_lowercase().as_str() {
+        "0" | "none" | "off" | "false" => None,
+        number => match number.parse() {
+            Ok(n) => Some(n),
+            Err(_) => {
+                eprintln!("Warning: ignoring qwen.{}: expected a number or 'none', got '{}'", key, value);
+                Some(default)
+            }
+        },
+    }
+}
+
+/// A broken rule, with a description the model can act on.
+#[derive(Debug)]
+pub struct Violation {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+/// What to do when a generated message breaks a rule, from `qwen.lint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Don't check messages
+    Off,
+    /// Only print warnings
+    Warn,
+    /// Fix what can be fixed and warn about the rest
+    Fix,
+    /// Ask the model for a corrected message, then fix and warn
+    Retry,
+}
+
+/// The rules in effect, from `qwen.lint` and the multi-valued
+/// `qwen.lintDisable`.
+pub struct Linter {
+    pub action: Action,
+    pub retries: usize,
+    limits: Limits,
+    disabled: Vec<String>,
+}
+
+impl Linter {
+    pub fn from_config(config: &Config) -> Self {
+        let action = match config.get("lint").map(str::to_lowercase).as_deref() {
+            None | Some("fix") | Some("true") => Action::Fix,
+            Some("off") | Some("false") => Action::Off,
+            Some("warn") => Action::Warn,
+            Some("retry") => Action::Retry,
+            Some(other) => {
+                eprintln!("Warning: ignoring invalid qwen.lint '{}', expected off, warn, fix, or retry", other);
+                Action::Fix
+            }
+        };
+
+        Linter {
+            action,
+            retries: config.get_usize("lintRetries").unwrap_or(DEFAULT_RETRIES),
+            limits: Limits::from_config(config),
+            disabled: config.get_all("lintDisable").into_iter().map(|rule| rule.trim().to_string()).collect(),
+        }
+    }
+
+    fn enabled(&self, rule: &str) -> bool {
+        self.action != Action::Off && !self.disabled.iter().any(|disabled| disabled == rule)
+    }
+
+    /// Returns the rules `message` breaks.
+    pub fn check(&self, message: &str) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut report = |rule: &'static str, message: String| {
+            if self.enabled(rule) {
+                violations.push(Violation { rule, message });
+            }
+        };
+
+        let lines: Vec<&str> = message.lines().collect();
+        let subject = lines.first().copied().unwrap_or("").trim();
+
+        if subject.is_empty() {
+            report("subject-empty", "the subject is empty".to_string());
+            return violations;
+        }
+
+        let length = subject.chars().count();
+        if let Some(max) = self.limits.subject
+            && length > max
+        {
+            report(
+                "subject-max-length",
+                format!("the subject is {} characters long; keep it within {}", length, max),
+            );
+        }
+
+        if subject.ends_with('.') && !subject.ends_with("...") {
+            report("subject-full-stop", "the subject ends with a period".to_string());
+        }
+
+        if let Some((word, base)) = non_imperative_verb(subject) {
+            report(
+                "subject-imperative",
+                format!("the subject starts with \"{}\"; use the imperative \"{}\"", word, base),
+            );
+        }
+
+        if lines.get(1).is_some_and(|line| !line.trim().is_empty()) {
+            report("body-leading-blank", "the line after the subject is not blank".to_string());
+        }
+
+        if let Some(width) = self.limits.wrap {
+            for line in lines.iter().skip(2) {
+                if line.chars().count() > width && is_wrappable(line) {
+                    report(
+                        "body-max-line-length",
+                        format!("body lines must be wrapped at {} characters", width),
+                    );
+                    break;
+                }
+            }
+        }
+
+        violations
+    }
+
+    /// Fixes the violations that have a safe mechanical fix: a trailing
+    /// period, a verb in the wrong mood, and a missing blank line.
+    pub fn fix(&self, message: &str) -> String {
+        let mut lines: Vec<String> = message.lines().map(String::from).collect();
+        let Some(subject) = lines.first_mut() else {
+            return message.to_string();
+        };
+
+        if self.enabled("subject-full-stop") {
+            strip_full_stop(subject);
+        }
+
+        if self.enabled("subject-imperative") {
+            make_imperative(subject);
+        }
+
+        if self.enabled("body-leading-blank") && lines.get(1).is_some_and(|line| !line.trim().is_empty()) {
+            lines.insert(1, String::new());
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Puts a subject in the usual shape: no trailing period, a first verb in
+/// the imperative mood, and that verb capitalized unless the subject starts
+/// with a prefix such as `net/http:`, whose convention is left alone.
+pub fn normalize_subject(subject: &str) -> String {
+    let mut subject = subject.trim().to_string();
+    strip_full_stop(&mut subject);
+    make_imperative(&mut subject);
+
+    let first = subject.chars().take_while(|c| c.is_alphabetic()).collect::<String>();
+    if strip_prefix(&subject) == subject && VERBS.contains(&first.as_str()) {
+        subject = capitalize(&subject);
+    }
+    subject
+}
+
+fn strip_full_stop(subject: &mut String) {
+    while subject.ends_with('.') && !subject.ends_with("...") {
+        subject.pop();
+    }
+}
+
+/// Replaces a first verb such as "Added" with its imperative form, keeping
+/// its case.
+fn make_imperative(subject: &mut String) {
+    if let Some((word, base)) = non_imperative_verb(subject) {
+        let replacement = match word.chars().next() {
+            Some(first) if first.is_uppercase() => capitalize(base),
+            _ => base.to_string(),
+        };
+        *subject = subject.replacen(word.as_str(), &replacement, 1);
+    }
+}
+
+/// Tells the model what was wrong with its last message, to be appended to
+/// the original prompt; `reminder` says how to answer.
+pub fn correction(message: &str, violations: &[Violation], reminder: &str) -> String {
+    let mut text = format!(
+        "\n\nA previous attempt produced this commit message:\n---\n{}\n---\nIt has these problems:\n",
+        message
+    );
+    for violation in violations {
+        text.push_str(&format!("- {}\n", violation.message));
+    }
+    text.push_str("Write the commit message again with these problems fixed. ");
+    text.push_str(reminder);
+    text
+}
+
+/// Finds a first verb that is not in the imperative mood, such as "Added",
+/// "Adds", or "Adding", and returns it with its imperative form.
+fn non_imperative_verb(subject: &str) -> Option<(String, &'static str)> {
+    let word: String = strip_prefix(subject)
+        .split_whitespace()
+        .next()?
+        .chars()
+        .take_while(|c| c.is_alphabetic())
+        .collect();
+    let lower = word.to_lowercase();
+
+    if VERBS.contains(&lower.as_str()) {
+        return None;
+    }
+
+    if let Some(&(_, base)) = IRREGULAR.iter().find(|(form, _)| *form == lower) {
+        return Some((word, base));
+    }
+
+    let mut candidates: Vec<String> = Vec::new();
+    for (suffix, replacements) in [
+        ("ies", &["y"][..]),
+        ("ied", &["y"][..]),
+        ("ing", &["", "e"][..]),
+        ("ed", &["", "e"][..]),
+        ("es", &[""][..]),
+        ("s", &[""][..]),
+    ] {
+        if let Some(stem) = lower.strip_suffix(suffix) {
+            for replacement in replacements {
+                candidates.push(format!("{}{}", stem, replacement));
+            }
+            // Doubled final consonant: "stopped", "running"
+            let mut chars = stem.chars().rev();
+            if let (Some(last), Some(before)) = (chars.next(), chars.next())
+                && last == before
+            {
+                candidates.push(stem[..stem.len() - last.len_utf8()].to_string());
+            }
+        }
+    }
+
+    candidates
+        .iter()
+        .find_map(|candidate| VERBS.iter().find(|verb| **verb == candidate))
+        .map(|base| (word, *base))
+}
+
+/// Skips a subject prefix such as `net/http: `, `feat(parser): `, or
+/// `[backend] `.
+fn strip_prefix(subject: &str) -> &str {
+    if let Some(rest) = subject.strip_prefix('[').and_then(|s| s.split_once(']')) {
+        return rest.1.trim_start();
+    }
+    match subject.split_once(": ") {
+        Some((prefix, rest)) if !prefix.contains(' ') => rest,
+        _ => subject,
+    }
+}
+
+/// Whether wrapping could shorten the line: prose, as opposed to trailers,
+/// URLs, indented code, and single long words.
+fn is_wrappable(line: &str) -> bool {
+    let trimmed = line.trim();
+    !(line.starts_with("    ")
+        || line.starts_with('\t')
+        || trimmed.contains("://")
+        || !trimmed.contains(' ')
+        || trimmed.split_once(": ").is_some_and(|(key, _)| {
+            key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        }))
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
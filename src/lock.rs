@@ -0,0 +1,79 @@
+//! The repository lock, which keeps two git-qwen commits in one repository
+//! from fighting over the message file and the index.
+
+use crate::cancel;
+use crate::git;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+
+/// File in the git directory holding the process ID of the git-qwen that
+/// holds the lock.
+const LOCK_FILE: &str = "qwen.lock";
+
+/// The held lock, released when dropped.
+pub struct RepoLock {
+    path: PathBuf,
+}
+
+impl RepoLock {
+    /// Takes the lock, failing if another running git-qwen holds it. A lock
+    /// left behind by a git-qwen that was killed is taken over.
+    pub fn acquire() -> Result<Self, String> {
+        let path = git::path(LOCK_FILE)?;
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let _ = write!(file, "{}", std::process::id());
+                    cancel::remove_on_interrupt(&path);
+                    return Ok(RepoLock { path });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    // A lock without a process ID may be being written
+                    let holder = fs::read_to_string(&path).ok().and_then(|pid| pid.trim().parse().ok());
+                    match holder {
+                        Some(pid) if !is_running(pid) => {
+                            let _ = fs::remove_file(&path);
+                        }
+                        _ => {
+                            return Err(format!(
+                                "Another git-qwen is committing in this repository; if none is, remove {}",
+                                path.display()
+                            ));
+                        }
+                    }
+                }
+                Err(e) => return Err(format!("Failed to create {}: {}", path.display(), e)),
+            }
+        }
+    }
+}
+
+impl Drop for RepoLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+        cancel::keep_on_interrupt(&self.path);
+    }
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    // A zero or negative pid would signal a process group instead
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    // Signal 0 only checks that the process exists; EPERM means it does
+    // but belongs to someone else
+    // SAFETY: kill has no memory safety requirements, and signal 0 is
+    // never delivered
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn is_running(_pid: u32) -> bool {
+    true
+}
@@ -0,0 +1,73 @@
+//! Summarizing a diff too large for one prompt. Each file is summarized
+//! on its own, in parallel, and the prompt carries the summaries in place
+//! of the diff, unless `qwen.mapReduce` is false.
+
+use crate::backend;
+use crate::config::Config;
+use crate::diff::{self, FileDiff};
+use crate::jobs;
+use crate::prompt;
+use crate::spinner;
+use crate::truncate;
+
+const FILE_PROMPT: &str = "Summarize the following change to one file in a single line of at most 20 words. Describe what changed and, if it is evident, why. Output only that line:
+
+";
+
+/// Asks the model for a one-line summary of each file in `diff`, returning
+/// `(path, summary)` pairs in diff order. Each file is sent on its own and
+/// trimmed to `max_chars` (if set) when even that is too large. Up to
+/// `qwen.jobs` files are summarized at once.
+pub fn summarize_files(
+    diff: &str,
+    max_chars: Option<usize>,
+    config: &Config,
+) -> Result<Vec<(String, String)>, String> {
+    let files = diff::split_files(diff);
+    spinner::message(&format!(
+        "The diff is too large for one prompt; summarizing {} file(s) first...",
+        files.len()
+    ));
+
+    let limit = config.get_usize("jobs").unwrap_or(jobs::DEFAULT_JOBS);
+    jobs::run_parallel(&files, limit, "Summarizing files", |file| {
+        Ok((file.path.clone(), summarize_file(file, max_chars)?))
+    })
+}
+
+fn summarize_file(file: &FileDiff, max_chars: Option<usize>) -> Result<String, String> {
+    // Sections without hunks (mode changes, binary files, collapsed
+    // lockfiles) already say everything in a line or two
+    if !file.text.lines().any(|line| line.starts_with("@@")) {
+        let details: Vec<&str> = file
+            .text
+            .lines()
+            .skip(1)
+            .filter(|line| !line.starts_with("index "))
+            .collect();
+        return Ok(if details.is_empty() {
+            "changed".to_string()
+        } else {
+            details.join("; ")
+        });
+    }
+
+    let mut prompt = format!("{}{}", prompt::DATA_NOTICE.trim_start(), FILE_PROMPT);
+    match max_chars {
+        Some(max) => {
+            let budget = max.saturating_sub(prompt.len() + prompt::FENCE_CHARS);
+            prompt.push_str(&prompt::fence(&truncate::fit(file.text, budget)));
+        }
+        None => prompt.push_str(&prompt::fence(file.text)),
+    }
+
+    let summary = backend::generate(&prompt)
+        .map_err(|e| format!("Failed to summarize {}: {}", file.path, e))?;
+
+    Ok(summary
+        .lines()
+        .map(|line| line.trim().trim_start_matches("- ").trim())
+        .find(|line| !line.is_empty() && !line.starts_with("```"))
+        .unwrap_or("changed")
+        .to_string())
+}
@@ -0,0 +1,48 @@
+//! The mock backend: answers every prompt with a message derived from the
+//! diff in it, so tests, CI, and demos work offline and always get the same
+//! answer for the same changes.
+
+use crate::diff;
+use serde_json::json;
+
+/// The canned answer to `prompt`, as JSON when the prompt asks for a
+/// subject and body and as plain text otherwise.
+pub fn generate(prompt: &str) -> String {
+    let files = diff::split_files(prompt);
+
+    let subject = match files.as_slice() {
+        [] => "Update files".to_string(),
+        [file] => format!("{} {}", verb(&file.change_kind()), file.path),
+        files => {
+            let kinds: Vec<String> = files.iter().map(|file| file.change_kind()).collect();
+            let verb = if kinds.iter().all(|kind| *kind == kinds[0]) { verb(&kinds[0]) } else { "Update" };
+            format!("{} {} files", verb, files.len())
+        }
+    };
+
+    let body = files
+        .iter()
+        .map(|file| {
+            let (added, removed) = diff::count_changes(file.text);
+            format!("- {}: {} insertion(s), {} deletion(s)", file.path, added, removed)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if prompt.contains("{\"subject\": ") {
+        json!({ "subject": subject, "body": body }).to_string()
+    } else if body.is_empty() {
+        subject
+    } else {
+        format!("{}\n\n{}", subject, body)
+    }
+}
+
+fn verb(change_kind: &str) -> &'static str {
+    match change_kind {
+        "added" => "Add",
+        "deleted" => "Remove",
+        kind if kind.starts_with("renamed") => "Rename",
+        _ => "Update",
+    }
+}
@@ -0,0 +1,39 @@
+//! `git-qwen note <COMMIT>`: attaches a detailed explanation of a commit
+//! as a git note, leaving its message as it is.
+
+use crate::config::Config;
+use crate::explain;
+use crate::git;
+use std::fs;
+
+/// Explains `rev` and adds the explanation with `git notes add`. A commit
+/// that already has a note keeps it unless `force` is given.
+pub fn run(rev: &str, force: bool) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+
+    let commit = git::output(&["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", rev)])
+        .map_err(|_| format!("Not a commit: {}", rev))?;
+    let commit = commit.trim();
+    if !force && git::output(&["notes", "show", commit]).is_ok() {
+        return Err(format!("{} already has a note; use --force to replace it", rev));
+    }
+
+    let explanation = explain::explain(Some(commit), &config)?;
+
+    let note_file = git::path("QWEN_NOTE_EDITMSG")?;
+    let note_file = note_file.to_string_lossy();
+    fs::write(&*note_file, format!("{}\n", explanation)).map_err(|e| format!("Failed to write note file: {}", e))?;
+
+    let mut args = vec!["notes", "add", "--file", &note_file];
+    if force {
+        args.push("--force");
+    }
+    args.push(commit);
+    let result = git::output(&args);
+    let _ = fs::remove_file(&*note_file);
+    result?;
+
+    eprintln!("Added a note to {}; see it with: git log -1 --notes {}", rev, rev);
+    Ok(())
+}
@@ -0,0 +1,347 @@
+//! Checks on the raw text the model returns and the steps that turn it
+//! into a bare commit message.
+
+use crate::config::Config;
+use regex::Regex;
+use serde_json::Value;
+
+/// Default number of times the model is asked again for output in the
+/// required format.
+pub const DEFAULT_FORMAT_RETRIES: usize = 2;
+
+/// Openers of chat-style answers that talk about the message instead of
+/// being the message.
+const PREAMBLE_PATTERN: &str = r"(?i)^(?:here(?:'s| is| are)\b|sure\b|certainly\b|of course\b|okay\b|ok[,.!]|below is\b|the following\b|based on the (?:diff|changes)\b|i (?:have|'ve) |this commit message\b|commit message:)";
+
+/// Labels models put in front of the message, e.g. `Commit message: Fix x`
+/// or `**Subject:** Fix x`; the rest of the line is kept.
+const LABEL_PATTERN: &str = r"(?i)^\**(?:suggested |proposed |git )?(?:commit message|subject(?: line)?|title)\**:\**\s*";
+
+/// Openers of paragraphs that explain the message after it.
+const EXPLANATION_PATTERN: &str = r"(?i)^(?:this (?:commit )?message\b|this follows\b|note:|explanation:|i (?:have|'ve) |i hope\b|let me know\b|feel free\b|hope this\b|the (?:subject|message) (?:line )?(?:is|uses|follows)\b)";
+
+/// Openers of answers where the model refuses or talks about itself,
+/// which happens when text in the changes derails it.
+const REFUSAL_PATTERN: &str = r"(?i)^(?:i(?:'m| am) sorry\b|i (?:cannot|can't|won't)\b|as an ai\b|i(?:'m| am) (?:an ai|a language model)\b)";
+
+/// Subjects longer than this are not a commit subject, whatever the
+/// configured limit.
+const MAX_SUBJECT_CHARS: usize = 200;
+
+/// Tags reasoning models wrap their thinking in.
+const REASONING_TAGS: &[&str] = &["think", "thinking", "reasoning"];
+
+/// Turns a plain-text answer into the bare commit message, one step at a time:
+/// reasoning blocks are removed, a fenced block is unwrapped, and chat
+/// preambles, labels, trailing explanations, and markdown emphasis on the
+/// subject are stripped.
+fn clean(raw: &str) -> String {
+    let text = strip_reasoning(raw);
+    let text = unwrap_fence(&text);
+    let text = strip_preamble(&text);
+    let text = strip_explanation(&text);
+    strip_subject_markup(&text)
+}
+
+/// Turns a plain-text answer that is a document rather than a commit
+/// message, such as a changelog section, into its bare text: reasoning, a
+/// fence around it, and chatter before or after it are removed, while its
+/// markdown is kept.
+pub fn clean_document(raw: &str) -> String {
+    let text = strip_reasoning(raw);
+    let text = unwrap_fence(&text);
+    let text = strip_preamble(&text);
+    strip_explanation(&text)
+}
+
+/// Removes `<think>...</think>` style blocks. An unclosed block swallows
+/// everything after it, so only the text before it is kept then.
+fn strip_reasoning(text: &str) -> String {
+    let mut text = text.to_string();
+    for tag in REASONING_TAGS {
+        let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+        while let Some(start) = text.find(&open) {
+            match text[start..].find(&close) {
+                Some(end) => text.replace_range(start..start + end + close.len(), ""),
+                None => text.truncate(start),
+            }
+        }
+        // Some models omit the opening tag and only close the block
+        if let Some(end) = text.find(&close) {
+            text.replace_range(..end + close.len(), "");
+        }
+    }
+    text.trim().to_string()
+}
+
+/// Returns the contents of the first ``` fenced block, dropping any text
+/// around it and the language tag after the opening fence. Text without a
+/// fence is returned as is.
+fn unwrap_fence(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let Some(open) = lines.iter().position(|line| line.trim_start().starts_with("```")) else {
+        return text.trim().to_string();
+    };
+
+    let close = lines[open + 1..]
+        .iter()
+        .position(|line| line.trim() == "```")
+        .map_or(lines.len(), |i| open + 1 + i);
+    let inside = lines[open + 1..close].join("\n");
+
+    // A fence around nothing, or around a code sample inside the body,
+    // does not hold the message
+    let before = lines[..open].join("\n");
+    if inside.trim().is_empty() || (!before.trim().is_empty() && !is_preamble(before.trim())) {
+        return text.trim().to_string();
+    }
+    inside.trim().to_string()
+}
+
+/// Drops leading lines that introduce the message and strips a label such
+/// as `Commit message:` from the subject line.
+fn strip_preamble(text: &str) -> String {
+    let label = Regex::new(LABEL_PATTERN).expect("built-in label pattern is valid");
+    let mut lines: Vec<&str> = text.lines().collect();
+
+    while let Some(first) = lines.first().map(|line| line.trim()) {
+        if first.is_empty() {
+            lines.remove(0);
+            continue;
+        }
+
+        if let Some(found) = label.find(first) {
+            let rest = &first[found.end()..];
+            if rest.trim().is_empty() {
+                lines.remove(0);
+                continue;
+            }
+            lines[0] = rest;
+            break;
+        }
+
+        // "Here is the commit message:" introduces the message; a subject
+        // never ends with a colon
+        if is_preamble(first) && (first.ends_with(':') || lines.len() > 1) {
+            lines.remove(0);
+            continue;
+        }
+        break;
+    }
+
+    lines.join("\n").trim().to_string()
+}
+
+/// Cuts off paragraphs after the message that explain or comment on it.
+fn strip_explanation(text: &str) -> String {
+    let explanation = Regex::new(EXPLANATION_PATTERN).expect("built-in explanation pattern is valid");
+    let paragraphs: Vec<&str> = text.split("\n\n").collect();
+
+    // The subject paragraph always stays
+    let end = paragraphs
+        .iter()
+        .skip(1)
+        .position(|paragraph| {
+            let first = paragraph.trim_start().trim_start_matches(['*', '_', '-', ' ']);
+            explanation.is_match(first) || paragraph.trim() == "---"
+        })
+        .map_or(paragraphs.len(), |i| i + 1);
+
+    paragraphs[..end].join("\n\n").trim().to_string()
+}
+
+/// Removes markdown that models like to put around the subject: bold or
+/// italic markers, a heading marker, and surrounding quotes or backticks.
+fn strip_subject_markup(text: &str) -> String {
+    let (subject, rest) = text.split_once('\n').unwrap_or((text, ""));
+
+    let mut subject = subject.trim().trim_start_matches('#').trim();
+    for marker in ["**", "__", "`", "\"", "*", "_"] {
+        if subject.len() > 2 * marker.len()
+            && let Some(inner) = subject.strip_prefix(marker).and_then(|s| s.strip_suffix(marker))
+        {
+            subject = inner.trim();
+        }
+    }
+
+    if rest.is_empty() {
+        subject.to_string()
+    } else {
+        format!("{}\n{}", subject, rest)
+    }
+}
+
+fn is_preamble(line: &str) -> bool {
+    Regex::new(PREAMBLE_PATTERN)
+        .expect("built-in preamble pattern is valid")
+        .is_match(line.trim_start_matches(['*', '_']))
+}
+
+/// How the model is asked to shape its answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A `{"subject": ..., "body": ...}` object, which separates the two
+    /// reliably
+    Json,
+    /// The plain commit message
+    Text,
+}
+
+impl Format {
+    /// JSON unless `qwen.jsonOutput` is false, for models that handle
+    /// JSON poorly.
+    pub fn from_config(config: &Config) -> Self {
+        if config.get_bool("jsonOutput").unwrap_or(true) {
+            Format::Json
+        } else {
+            Format::Text
+        }
+    }
+
+    /// Ends the request in the prompt, just before the changes.
+    pub fn instruction(self) -> &'static str {
+        match self {
+            Format::Json => {
+                "\nAnswer with only a JSON object of the form {\"subject\": \"...\", \"body\": \"...\"}, nothing else. The body holds the paragraphs, separated by blank lines, and may be empty. The changes:\n\n"
+            }
+            Format::Text => "\nOutput only the commit message, nothing else:\n\n",
+        }
+    }
+
+    /// Ends a follow-up request, such as a correction.
+    pub fn reminder(self) -> &'static str {
+        match self {
+            Format::Json => {
+                "Answer with only a JSON object of the form {\"subject\": \"...\", \"body\": \"...\"}, nothing else:\n"
+            }
+            Format::Text => "Output only the commit message, nothing else:\n",
+        }
+    }
+
+    /// Describes how `raw` breaks this format, if it does, or how the
+    /// message in it does not look like a commit message.
+    pub fn problems(self, raw: &str) -> Vec<String> {
+        let problems = self.format_problems(raw);
+        if !problems.is_empty() {
+            return problems;
+        }
+        message_problems(&self.parse(raw))
+    }
+
+    fn format_problems(self, raw: &str) -> Vec<String> {
+        match self {
+            Format::Json => match parse_json(raw) {
+                Some((subject, _)) if subject.trim().is_empty() => {
+                    vec!["the \"subject\" was empty".to_string()]
+                }
+                Some(_) => Vec::new(),
+                None => vec!["it was not a JSON object with a \"subject\" and a \"body\" string".to_string()],
+            },
+            Format::Text => format_problems(raw),
+        }
+    }
+
+    /// Asks the model to answer again in this format, to be appended to the
+    /// original prompt.
+    pub fn correction(self, problems: &[String]) -> String {
+        let mut text = "\n\nA previous answer to this request was not in the required format:\n".to_string();
+        for problem in problems {
+            text.push_str(&format!("- {}\n", problem));
+        }
+        if self == Format::Text {
+            text.push_str(
+                "Answer with the commit message alone: the subject on the first line, a blank line, then the body. No introduction, explanation, or formatting around it.\n",
+            );
+        }
+        text.push_str(self.reminder());
+        text
+    }
+
+    /// Turns the answer into the bare message. JSON that cannot be parsed
+    /// falls back to the plain-text cleanup.
+    pub fn parse(self, raw: &str) -> String {
+        if self == Format::Json
+            && let Some((subject, body)) = parse_json(raw)
+        {
+            return format!("{}\n\n{}", subject.trim(), body.trim()).trim().to_string();
+        }
+        clean(raw)
+    }
+}
+
+/// Finds the JSON object in an answer, which may be wrapped in a fence,
+/// reasoning, or chatter.
+pub fn json_object(raw: &str) -> Option<Value> {
+    let text = strip_reasoning(raw);
+    let start = text.find('{')?;
+    let end = text.rfind('}')?;
+    serde_json::from_str(text.get(start..=end)?).ok()
+}
+
+/// Extracts the subject and body from a JSON answer, which may be wrapped
+/// in a fence or reasoning. A body given as a list of paragraphs is joined.
+fn parse_json(raw: &str) -> Option<(String, String)> {
+    let value = json_object(raw)?;
+    let subject = value.get("subject")?.as_str()?.to_string();
+    let body = match value.get("body") {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(body)) => body.clone(),
+        Some(Value::Array(paragraphs)) => paragraphs
+            .iter()
+            .map(|p| p.as_str().map(str::trim))
+            .collect::<Option<Vec<_>>>()?
+            .join("\n\n"),
+        Some(_) => return None,
+    };
+    Some((subject, body))
+}
+
+/// Describes how `message` does not look like a commit message, as when
+/// instructions hidden in the changes derailed the model: it refuses,
+/// repeats the diff or the markers around it, or has no subject line.
+pub fn message_problems(message: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    let subject = message.lines().next().unwrap_or("").trim();
+    if Regex::new(REFUSAL_PATTERN).expect("built-in refusal pattern is valid").is_match(subject) {
+        problems.push(format!("it was a refusal or a remark (\"{}\") instead of a commit message", subject));
+    } else if subject.chars().count() > MAX_SUBJECT_CHARS {
+        problems.push(format!("its first line was {} characters long, too long for a subject", subject.chars().count()));
+    }
+    if message.contains("<data>") || message.contains("</data>") {
+        problems.push("it repeated the <data> markers".to_string());
+    }
+    if message.lines().any(|line| line.starts_with("diff --git ") || line.starts_with("@@ -")) {
+        problems.push("it contained diff text instead of describing it".to_string());
+    }
+    problems
+}
+
+/// Describes how a plain-text answer breaks the requested format, if it
+/// does: a chat preamble, a missing blank line after the subject, or
+/// nothing at all.
+fn format_problems(raw: &str) -> Vec<String> {
+    // Reasoning is expected from some models and never part of the answer
+    let answer = strip_reasoning(raw);
+    let lines: Vec<&str> = answer
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect();
+
+    let mut problems = Vec::new();
+    let Some(first) = lines.first().map(|line| line.trim()) else {
+        problems.push("the answer was empty".to_string());
+        return problems;
+    };
+
+    if is_preamble(first) {
+        problems.push(format!(
+            "it started with \"{}\" instead of the subject line",
+            first
+        ));
+    } else if lines.get(1).is_some_and(|line| !line.trim().is_empty()) {
+        problems.push("the subject line was not followed by a blank line".to_string());
+    }
+
+    problems
+}
+
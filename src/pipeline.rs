@@ -0,0 +1,323 @@
+//! The stages of writing a commit message, usable on their own: where the
+//! diff comes from, how the message is generated and formatted, and how the
+//! commit is made. The `git-qwen` command strings them together; other
+//! tools can embed them the same way.
+
+use crate::backend;
+use crate::cache;
+use crate::config::Config;
+use crate::git::{GitClient, SystemGit};
+use crate::lint;
+use crate::output;
+use crate::prompt::{self, PromptOptions};
+use crate::spinner;
+use crate::subject;
+use crate::trailers;
+use crate::wrap;
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// Where the changes to describe come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffSource {
+    /// The staged changes, as `git commit` commits them
+    Staged,
+    /// The staged and unstaged changes to tracked files, as with `-a`
+    AllTracked,
+    /// The HEAD commit's changes plus the staged ones, and with
+    /// `include_all` the unstaged ones too, as with `--amend`
+    Amend { include_all: bool },
+    /// A diff supplied by the caller
+    Text(String),
+}
+
+impl DiffSource {
+    /// The source `git commit` would commit from with `-a` and `--amend`.
+    pub fn for_commit(include_all: bool, amend: bool) -> Self {
+        match (include_all, amend) {
+            (include_all, true) => DiffSource::Amend { include_all },
+            (true, false) => DiffSource::AllTracked,
+            (false, false) => DiffSource::Staged,
+        }
+    }
+
+    /// Reads the diff, limited to `pathspecs` if any are given. The HEAD
+    /// commit of an amend is always shown whole.
+    pub fn read(&self, pathspecs: &[String]) -> Result<String, String> {
+        self.read_with(&SystemGit, pathspecs)
+    }
+
+    /// Like `read`, running `git` through the given client.
+    pub fn read_with(&self, git: &dyn GitClient, pathspecs: &[String]) -> Result<String, String> {
+        let options = crate::diff::options(git);
+        let diff = self.read_using(git, pathspecs, &options)?;
+        // Options like -w can hide every change, which is not the same as
+        // there being none
+        let rename_options = crate::diff::rename_options(git);
+        if diff.trim().is_empty() && options != rename_options {
+            return self.read_using(git, pathspecs, &rename_options);
+        }
+        Ok(diff)
+    }
+
+    fn read_using(&self, git: &dyn GitClient, pathspecs: &[String], options: &[String]) -> Result<String, String> {
+        // Runs `command` (`diff` or `show`) with the diff options, then `args`
+        let run = |command: &str, args: &[&str], limited: bool| {
+            let mut full_args = vec![command];
+            full_args.extend(options.iter().map(String::as_str));
+            full_args.extend(args);
+            if limited && !pathspecs.is_empty() {
+                full_args.push("--");
+                full_args.extend(pathspecs.iter().map(String::as_str));
+            }
+            git.output(&full_args)
+        };
+        let diff = |args: &[&str]| run("diff", args, true);
+
+        match self {
+            DiffSource::Staged => diff(&["--cached"]),
+            DiffSource::AllTracked => Ok(format!("{}{}", diff(&["--cached"])?, diff(&[])?)),
+            DiffSource::Amend { include_all } => {
+                // A root commit has no parent to diff against, so show
+                // everything it adds instead
+                let head = if git.output(&["rev-parse", "--verify", "--quiet", "HEAD^"]).is_ok() {
+                    run("diff", &["HEAD~1", "HEAD"], false)?
+                } else {
+                    run("show", &["--format=", "HEAD"], false)?
+                };
+                let unstaged = if *include_all { diff(&[])? } else { String::new() };
+                Ok(format!("{}{}{}", head, diff(&["--cached"])?, unstaged))
+            }
+            DiffSource::Text(text) => Ok(text.clone()),
+        }
+    }
+}
+
+/// Writes commit messages with the model, following the settings in
+/// `config` and the context in `options`.
+pub struct MessageGenerator<'a> {
+    config: &'a Config,
+    options: &'a PromptOptions,
+    reuse_cached: bool,
+}
+
+impl<'a> MessageGenerator<'a> {
+    pub fn new(config: &'a Config, options: &'a PromptOptions) -> Self {
+        MessageGenerator {
+            config,
+            options,
+            reuse_cached: false,
+        }
+    }
+
+    /// Answers with the message generated earlier for the same prompt, if
+    /// there is one and `qwen.cache` allows it, instead of asking again.
+    pub fn reuse_cached(mut self, reuse: bool) -> Self {
+        self.reuse_cached = reuse;
+        self
+    }
+
+    /// Builds the prompt for `diff` and generates a message from it.
+    pub fn generate(&self, diff: &str) -> Result<String, String> {
+        self.answer(&self.prompt(diff)?)
+    }
+
+    /// The prompt asking for a message describing `diff`.
+    pub fn prompt(&self, diff: &str) -> Result<String, String> {
+        prompt::build(diff, self.config, self.options)
+    }
+
+    /// Generates a message from `prompt`, or reuses the cached one.
+    pub fn answer(&self, prompt: &str) -> Result<String, String> {
+        let caching = self.config.get_bool("cache").unwrap_or(true);
+        if caching
+            && self.reuse_cached
+            && let Some(message) = cache::get(prompt)
+        {
+            spinner::message("Reusing the message generated earlier for these changes.");
+            return Ok(message);
+        }
+
+        let message = self.generate_from_prompt(prompt)?;
+        if caching {
+            cache::put(prompt, &message);
+        }
+        Ok(message)
+    }
+
+    /// Generates a message from `prompt` and checks it with the linter.
+    ///
+    /// Output that is not in the requested format is asked for again, up
+    /// to `qwen.formatRetries` times. In `retry` mode the model is also
+    /// asked to correct a message that breaks a lint rule; whatever remains
+    /// is fixed where possible and reported.
+    pub fn generate_from_prompt(&self, prompt: &str) -> Result<String, String> {
+        let linter = lint::Linter::from_config(self.config);
+        let format = output::Format::from_config(self.config);
+        let formatter = MessageFormatter::new(self.config);
+        let mut message = formatter.format(&self.ask(prompt)?);
+        let mut retries = if linter.action == lint::Action::Retry { linter.retries } else { 0 };
+
+        loop {
+            let violations = linter.check(&message);
+            if violations.is_empty() {
+                return Ok(message);
+            }
+
+            if retries > 0 {
+                retries -= 1;
+                spinner::message(&format!(
+                    "The generated message breaks {} rule(s); asking qwen to fix it...",
+                    violations.len()
+                ));
+                let correction = lint::correction(&message, &violations, format.reminder());
+                message = formatter.format(&self.ask(&format!("{}{}", prompt, correction))?);
+                continue;
+            }
+
+            if linter.action != lint::Action::Warn {
+                message = linter.fix(&message);
+            }
+            for violation in linter.check(&message) {
+                spinner::message(&format!("Warning: {}: {}", violation.rule, violation.message));
+            }
+            return Ok(message);
+        }
+    }
+
+    /// Runs the model, asking again with a correction while its answer is
+    /// not shaped like a commit message. The last answer is returned unless
+    /// it is no commit message at all; cleaning it up is left to the
+    /// formatter.
+    fn ask(&self, prompt: &str) -> Result<String, String> {
+        let mut raw = backend::generate(prompt)?;
+        let format = output::Format::from_config(self.config);
+        let retries = self.config.get_usize("formatRetries").unwrap_or(output::DEFAULT_FORMAT_RETRIES);
+
+        for _ in 0..retries {
+            let problems = format.problems(&raw);
+            if problems.is_empty() {
+                break;
+            }
+            spinner::message(&format!(
+                "qwen's answer is not in the expected format ({}); asking again...",
+                problems.join("; ")
+            ));
+            raw = backend::generate(&format!("{}{}", prompt, format.correction(&problems)))?;
+        }
+
+        // Formatting slips are cleaned up later; an answer that is not a
+        // commit message at all is not worth offering
+        let problems = output::message_problems(&format.parse(&raw));
+        if !problems.is_empty() {
+            return Err(format!(
+                "qwen's answer does not look like a commit message ({}); text in the changes may have misled it",
+                problems.join("; ")
+            ));
+        }
+
+        Ok(raw)
+    }
+}
+
+/// Turns raw model output into a commit message laid out the way git
+/// expects, following the subject and wrapping settings in `config`.
+pub struct MessageFormatter<'a> {
+    config: &'a Config,
+}
+
+impl<'a> MessageFormatter<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        MessageFormatter { config }
+    }
+
+    /// The bare, formatted message in the model's answer.
+    pub fn format(&self, raw: &str) -> String {
+        let message = trailers::strip_reserved(&output::Format::from_config(self.config).parse(raw));
+        let wrap = lint::Limits::from_config(self.config).wrap;
+        Self::layout(&subject::apply(&message, self.config), wrap)
+    }
+
+    /// Separates the subject from the body with a blank line and wraps the
+    /// body at `wrap` characters, if set.
+    pub fn layout(message: &str, wrap: Option<usize>) -> String {
+        let lines: Vec<&str> = message.lines().collect();
+
+        if lines.is_empty() {
+            return String::new();
+        }
+
+        let subject = lines[0];
+
+        let mut result = subject.trim_end().to_string();
+
+        // If there's more content, add blank line and wrap the body
+        if lines.len() > 1 {
+            // Skip any existing blank lines after subject
+            let body_start = lines.iter().skip(1).position(|l| !l.trim().is_empty());
+
+            if let Some(start_idx) = body_start {
+                result.push_str("\n\n");
+
+                let body_lines = &lines[start_idx + 1..];
+                let body_text = body_lines.join("\n");
+                match wrap {
+                    Some(width) => result.push_str(&wrap::wrap(&body_text, width)),
+                    None => result.push_str(body_text.trim_end()),
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Runs `git commit` with the user's arguments.
+#[derive(Debug, Clone, Default)]
+pub struct CommitExecutor {
+    args: Vec<String>,
+    comment_config: Option<String>,
+}
+
+impl CommitExecutor {
+    pub fn new(args: &[String]) -> Self {
+        CommitExecutor {
+            args: args.to_vec(),
+            comment_config: None,
+        }
+    }
+
+    /// Passes a `-c <key>=<value>` setting to git, such as the comment
+    /// character the message was written with.
+    pub fn with_config(mut self, config: Option<String>) -> Self {
+        self.comment_config = config;
+        self
+    }
+
+    /// Commits, leaving the message to the arguments or to git, and returns
+    /// git's exit code.
+    pub fn commit(&self) -> Result<i32, String> {
+        self.execute(&SystemGit, None)
+    }
+
+    /// Commits with the message in `message_file` and returns git's exit
+    /// code.
+    pub fn commit_with_file(&self, message_file: &Path) -> Result<i32, String> {
+        self.execute(&SystemGit, Some(message_file))
+    }
+
+    /// Runs `git commit` through the given client, with the message in
+    /// `message_file` if there is one, and returns its exit code.
+    pub fn execute(&self, git: &dyn GitClient, message_file: Option<&Path>) -> Result<i32, String> {
+        let mut args: Vec<&OsStr> = Vec::new();
+        if let Some(config) = &self.comment_config {
+            args.extend([OsStr::new("-c"), OsStr::new(config)]);
+        }
+        args.push(OsStr::new("commit"));
+        if let Some(file) = message_file {
+            args.extend([OsStr::new("-F"), file.as_os_str()]);
+        }
+        args.extend(self.args.iter().map(OsStr::new));
+        git.status(&args)
+    }
+}
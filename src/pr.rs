@@ -0,0 +1,164 @@
+//! `git-qwen pr`: writes a pull request title and description for the
+//! current branch, and can open the pull request with `gh` or `glab`.
+
+use crate::backend;
+use crate::branch;
+use crate::clipboard;
+use crate::config::Config;
+use crate::git;
+use crate::history;
+use crate::output;
+use crate::prompt;
+use std::fs;
+use std::process::Command;
+
+const PR_PROMPT: &str = "Write the title and description of a pull request for the branch below, from its commits and its diff against the branch it will be merged into. The title is one line of under 72 characters in the imperative mood, without a period at the end. The description is markdown for reviewers: a short summary of what the change does and why, then a \"## Changes\" section with a bullet per notable change, then a \"## Testing\" section saying how the change can be verified, based only on what the diff shows. Don't mention commit names.
+Answer with only a JSON object of the form {\"title\": \"...\", \"body\": \"...\"}, nothing else.
+
+";
+
+/// Prints a title and description for the changes between the current
+/// branch and `base` (by default the remote's default branch). With
+/// `create`, the user reviews them in the editor and the pull request is
+/// opened with `gh`, or `glab` for GitLab remotes; with `copy`, they are
+/// also copied to the clipboard.
+pub fn run(base: Option<&str>, create: bool, draft: bool, copy: bool) -> Result<(), String> {
+    let clipboard = if copy { Some(clipboard::require()?) } else { None };
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+
+    let base = match base {
+        Some(base) => base.to_string(),
+        None => default_base().ok_or("Could not find the branch to compare with; pass it with --base")?,
+    };
+    let merge_base = git::output(&["merge-base", &base, "HEAD"])
+        .map_err(|_| format!("{} has no history in common with HEAD", base))?;
+    let merge_base = merge_base.trim();
+
+    let commits = history::commits(&format!("{}..HEAD", merge_base))?;
+    if commits.is_empty() {
+        return Err(format!("The current branch has no commits that are not on {}", base));
+    }
+    eprintln!("Describing {} commit(s) since {}...", commits.len(), base);
+
+    let mut prompt = format!("{}{}", prompt::DATA_NOTICE.trim_start(), PR_PROMPT);
+    let mut branches = String::new();
+    if let Some(name) = branch::current() {
+        branches.push_str(&format!("Branch: {}\n", name));
+    }
+    branches.push_str(&format!("Merging into: {}\n", base));
+    prompt.push_str(&prompt::fence(&branches));
+    prompt.push_str("\nCommits, oldest first:\n");
+    let budget = prompt::max_chars(&config).map(|max| max.saturating_sub(prompt.len() + prompt::FENCE_CHARS));
+    prompt.push_str(&prompt::fence(&history::prompt_listing(&commits, budget.map(|budget| budget / 2))));
+
+    let diff = prompt::prepare_diff(&git::output(&["diff", merge_base, "HEAD"])?, &config);
+    prompt.push_str("The diff:\n");
+    prompt.push_str(&prompt::fenced_fit(&diff, prompt.len(), &config));
+
+    let answer = backend::generate(&prompt).map_err(|e| format!("Failed to generate pull request: {}", e))?;
+    let (title, body) = parse_answer(&answer);
+    if title.is_empty() {
+        return Err("qwen returned an empty pull request title".to_string());
+    }
+
+    if !create {
+        let text = format!("{}\n\n{}", title, body);
+        println!("{}", text);
+        if let Some(tool) = clipboard {
+            clipboard::copy(tool, &text)?;
+            eprintln!("Copied to the clipboard.");
+        }
+        return Ok(());
+    }
+
+    let (title, body) = review(&title, &body)?;
+    open_pull_request(&base, &title, &body, draft)
+}
+
+/// The remote's default branch, as recorded by `git clone` in
+/// `refs/remotes/<remote>/HEAD`, or else a local main or master.
+fn default_base() -> Option<String> {
+    let remote = git::output(&["remote"]).ok()?;
+    let remotes: Vec<&str> = remote.lines().collect();
+    let preferred = remotes.iter().find(|name| **name == "origin").or(remotes.first());
+
+    preferred
+        .and_then(|remote| git::output(&["symbolic-ref", "--short", "-q", &format!("refs/remotes/{}/HEAD", remote)]).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| {
+            ["main", "master"]
+                .iter()
+                .find(|name| git::output(&["rev-parse", "--verify", "--quiet", &format!("refs/heads/{}", name)]).is_ok())
+                .map(|name| name.to_string())
+        })
+}
+
+/// The title and body from a JSON answer, or else from the first line and
+/// the rest of a plain one.
+fn parse_answer(answer: &str) -> (String, String) {
+    if let Some(value) = output::json_object(answer)
+        && let Some(title) = value.get("title").and_then(|title| title.as_str())
+    {
+        let body = value.get("body").and_then(|body| body.as_str()).unwrap_or("");
+        return (title.trim().to_string(), body.trim().to_string());
+    }
+
+    let text = output::clean_document(answer);
+    let (title, body) = text.split_once('\n').unwrap_or((&text, ""));
+    (title.trim_start_matches('#').trim().to_string(), body.trim().to_string())
+}
+
+/// Opens the editor on the title and body, the title being the first
+/// line. Markdown headings in the body would look like comments, so
+/// nothing is stripped.
+fn review(title: &str, body: &str) -> Result<(String, String), String> {
+    let path = git::path("QWEN_PR_EDITMSG")?;
+    fs::write(&path, format!("{}\n\n{}\n", title, body))
+        .map_err(|e| format!("Failed to write pull request file: {}", e))?;
+
+    let edited = crate::editor::open(&crate::editor::command(), &path).and_then(|_| crate::editor::read(&path));
+    let _ = fs::remove_file(&path);
+
+    let edited = edited?;
+    let (title, body) = edited.trim().split_once('\n').unwrap_or((edited.trim(), ""));
+    if title.trim().is_empty() {
+        return Err("Empty pull request title; not creating it".to_string());
+    }
+    Ok((title.trim().to_string(), body.trim().to_string()))
+}
+
+/// Runs `glab mr create` for GitLab remotes and `gh pr create` otherwise.
+fn open_pull_request(base: &str, title: &str, body: &str, draft: bool) -> Result<(), String> {
+    // The forge knows the base by its name on the remote
+    let base_branch = git::output(&["remote"])
+        .unwrap_or_default()
+        .lines()
+        .find_map(|remote| base.strip_prefix(&format!("{}/", remote)))
+        .unwrap_or(base)
+        .to_string();
+
+    let remote_url = git::output(&["remote", "get-url", "origin"]).unwrap_or_default();
+    let mut command = if remote_url.contains("gitlab") {
+        let mut command = Command::new("glab");
+        command.args(["mr", "create", "--title", title, "--description", body, "--target-branch", &base_branch]);
+        command
+    } else {
+        let mut command = Command::new("gh");
+        command.args(["pr", "create", "--title", title, "--body", body, "--base", &base_branch]);
+        command
+    };
+    if draft {
+        command.arg("--draft");
+    }
+
+    let program = command.get_program().to_string_lossy().into_owned();
+    let status = command
+        .status()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+    if !status.success() {
+        return Err(format!("{} could not create the pull request", program));
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::git;
+use regex::Regex;
+
+/// Number of recent commits on the same paths to learn the prefix from.
+const HISTORY_DEPTH: &str = "20";
+
+/// Minimum number of commits that must share a prefix for it to count as
+/// a convention.
+const MIN_OCCURRENCES: usize = 2;
+
+/// Subject prefixes like `net/http: `, `fix(parser): `, or `[backend] `.
+const PREFIX_PATTERN: &str = r"^(?:\[[^\]]+\]|[\w./-]+(?:\([^)]*\))?!?:)\s";
+
+/// Finds the subject prefix used by most recent commits touching `paths`,
+/// if any prefix is used by at least half of them.
+pub fn detect(paths: &[String]) -> Option<String> {
+    if paths.is_empty() {
+        return None;
+    }
+
+    let mut args = vec!["log", "-n", HISTORY_DEPTH, "--no-merges", "--format=%s", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    let log = git::output(&args).ok()?;
+
+    let pattern = Regex::new(PREFIX_PATTERN).expect("prefix pattern is valid");
+    let subjects: Vec<&str> = log.lines().filter(|line| !line.trim().is_empty()).collect();
+
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for subject in &subjects {
+        if let Some(found) = pattern.find(subject) {
+            let prefix = found.as_str().trim_end().to_string();
+            match counts.iter_mut().find(|(p, _)| *p == prefix) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((prefix, 1)),
+            }
+        }
+    }
+
+    let (prefix, count) = counts.into_iter().max_by_key(|(_, count)| *count)?;
+    (count >= MIN_OCCURRENCES && count * 2 >= subjects.len()).then_some(prefix)
+}
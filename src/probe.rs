@@ -0,0 +1,54 @@
+use crate::config::Config;
+use std::process::Command;
+
+/// Runs the commands configured with the multi-valued `qwen.probe` and
+/// turns their output into trailers. Each entry has the form
+/// `<Trailer-Key>=<command>`, e.g. `Built-with=rustc --version`; the first
+/// line of the command's output becomes the trailer value. Probes that
+/// fail or print nothing are skipped with a warning.
+pub fn collect_trailers(config: &Config) -> Vec<String> {
+    config
+        .get_all("probe")
+        .into_iter()
+        .filter_map(|entry| {
+            let Some((key, command)) = entry.split_once('=') else {
+                eprintln!("Warning: ignoring qwen.probe without '=': {}", entry);
+                return None;
+            };
+
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                eprintln!("Warning: ignoring qwen.probe with invalid trailer key: {}", entry);
+                return None;
+            }
+
+            match run_probe(command.trim()) {
+                Ok(value) => Some(format!("{}: {}", key, value)),
+                Err(e) => {
+                    eprintln!("Warning: probe for {} failed: {}", key, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn run_probe(command: &str) -> Result<String, String> {
+    let output = if cfg!(target_os = "windows") {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| format!("Failed to run '{}': {}", command, e))?;
+
+    if !output.status.success() {
+        return Err(format!("'{}' exited with {}", command, output.status));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(String::from)
+        .ok_or_else(|| format!("'{}' printed nothing", command))
+}
@@ -0,0 +1,73 @@
+//! `git-qwen release-notes <TAG>`: writes release notes for the commits
+//! since the previous tag, ready to paste into a GitHub Release.
+
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use crate::history;
+use crate::output;
+use crate::prompt;
+
+const RELEASE_NOTES_PROMPT: &str = "Write the release notes for the release below, for people using the project, from its commit messages and diff. Use these markdown sections, leaving out any with nothing to say:
+## Highlights: the few changes that matter most, each in a short paragraph or bullet
+## Breaking Changes: anything that stops working as before, and what to do about it
+## Upgrade Notes: steps needed when upgrading, such as configuration or migration changes
+## Other Changes: a bullet per remaining user-visible change
+Write in plain language, leave out changes users will not notice, such as refactoring, tests, and CI, and don't mention commit names. Answer with only the markdown.
+
+";
+
+/// Prints release notes for `tag`: the commits since the tag before it, or
+/// if `tag` does not exist yet, the commits since the latest tag.
+pub fn run(tag: &str) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+
+    let end = match git::output(&["rev-parse", "--verify", "--quiet", &format!("refs/tags/{}", tag)]) {
+        Ok(_) => tag.to_string(),
+        Err(_) => {
+            eprintln!("Tag {} does not exist yet; describing the commits up to HEAD", tag);
+            "HEAD".to_string()
+        }
+    };
+    let base = history::latest_tag(&format!("{}^", end));
+    let log_range = match &base {
+        Some(base) => format!("{}..{}", base, end),
+        None => end.clone(),
+    };
+
+    let commits = history::commits(&log_range)?;
+    if commits.is_empty() {
+        return Err(format!("No commits in {} to describe", log_range));
+    }
+    eprintln!("Summarizing {} commit(s) in {}...", commits.len(), log_range);
+
+    let mut prompt = format!("{}{}Release: {}\n", prompt::DATA_NOTICE.trim_start(), RELEASE_NOTES_PROMPT, tag);
+    if let Some(base) = &base {
+        prompt.push_str(&format!("Previous release: {}\n", base));
+    }
+
+    // The messages say why things changed, so they get the first half of
+    // the budget and the diff what is left
+    prompt.push_str("\nCommits, oldest first:\n");
+    let budget = prompt::max_chars(&config).map(|max| max.saturating_sub(prompt.len() + prompt::FENCE_CHARS));
+    prompt.push_str(&prompt::fence(&history::prompt_listing(&commits, budget.map(|budget| budget / 2))));
+
+    // Without an earlier tag, the release is everything up to it
+    let from = match &base {
+        Some(base) => base.clone(),
+        None => git::output(&["hash-object", "-t", "tree", "/dev/null"])?.trim().to_string(),
+    };
+    let diff = git::output(&["diff", &from, &end])?;
+    let diff = prompt::prepare_diff(&diff, &config);
+    prompt.push_str("The combined diff:\n");
+    prompt.push_str(&prompt::fenced_fit(&diff, prompt.len(), &config));
+
+    let answer = backend::generate(&prompt).map_err(|e| format!("Failed to generate release notes: {}", e))?;
+    let notes = output::clean_document(&answer);
+    if notes.is_empty() {
+        return Err("qwen returned empty release notes".to_string());
+    }
+    println!("{}", notes);
+    Ok(())
+}
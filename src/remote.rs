@@ -0,0 +1,61 @@
+//! The confirmation asked before the changes first go to a backend that
+//! does not run on this machine. The answer is kept in the repository's
+//! config, so it is asked once per repository.
+
+use crate::backend::{self, Backend};
+use crate::config::Config;
+use crate::error::Error;
+use crate::git;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Config key recording that sending this repository's changes was
+/// allowed.
+const CONFIRMED_KEY: &str = "qwen.remoteConfirmed";
+
+/// Makes sure the changes may go where the backends would send them:
+/// refuses when `qwen.localOnly` leaves no backend, and otherwise asks
+/// before the first use of a remote one, unless `qwen.confirmRemote` is
+/// false. Without a terminal nothing can be asked, so sending is refused
+/// until it is allowed in the config.
+pub fn confirm() -> Result<(), Error> {
+    let chain = backend::allowed_chain().map_err(Error::ModelUnavailable)?;
+    let remote: Vec<Backend> = chain
+        .into_iter()
+        .filter(|backend| backend.is_installed() && !backend.is_local())
+        .collect();
+    if remote.is_empty() {
+        return Ok(());
+    }
+
+    let config = Config::load();
+    if !config.get_bool("confirmRemote").unwrap_or(true) || config.get_bool("remoteConfirmed").unwrap_or(false) {
+        return Ok(());
+    }
+
+    let destinations: Vec<String> = remote.iter().map(|backend| backend.destination()).collect();
+    if !io::stdin().is_terminal() {
+        return Err(Error::ModelUnavailable(format!(
+            "Not sending the changes to {} without confirmation, and there is no terminal to ask on; run git-qwen once in a terminal, or allow it with `git config qwen.remoteConfirmed true`, or set qwen.localOnly to keep them on this machine",
+            destinations.join(", then ")
+        )));
+    }
+
+    eprintln!("git-qwen sends what it describes to {}.", destinations.join(", then "));
+    eprintln!(
+        "That is the diff of the changes and context from the repository, such as file names, the branch name, and past commit messages. Likely secrets in diffs and file contents are masked (qwen.redact), and the contents of files matching qwen.exclude or .gitqwenignore are left out."
+    );
+    eprint!("Send this repository's changes? [y/N] ");
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    let yes = io::stdin().lock().read_line(&mut answer).unwrap_or(0) > 0
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+    if !yes {
+        return Err(Error::Other(
+            "Not sending the changes; set qwen.localOnly and use a local backend such as ollama, or answer yes to send them"
+                .to_string(),
+        ));
+    }
+
+    git::output(&["config", "--local", CONFIRMED_KEY, "true"])?;
+    Ok(())
+}
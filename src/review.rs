@@ -0,0 +1,45 @@
+//! `git-qwen review`: a quick review of the staged changes before they are
+//! committed. It only prints; nothing is committed or changed.
+
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use crate::output;
+use crate::prompt;
+
+const REVIEW_PROMPT: &str = "Review the staged changes below as a careful senior engineer would before they are committed. Report only real problems, most serious first, under these markdown headings, leaving out headings with nothing under them:
+## Bugs: mistakes in logic, error handling, edge cases, concurrency, or security
+## Missing tests: behavior the change adds or alters that no test covers
+## Style: naming, duplication, dead code, or unclear code worth fixing
+Give each problem as a bullet starting with the file and line, like \"- src/main.rs:42: ...\", taking the line from the hunk headers, and say briefly how to fix it. Don't praise the change or restate what it does. If there is nothing worth reporting, answer only \"No issues found.\" Answer with only the review.
+
+";
+
+/// Prints a review of the staged changes, or with `include_all` of all
+/// changes to tracked files.
+pub fn run(include_all: bool) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+
+    let diff = if include_all {
+        git::output(&["diff", "HEAD"])?
+    } else {
+        git::output(&["diff", "--cached"])?
+    };
+    if diff.trim().is_empty() {
+        return Err("No changes to review".to_string());
+    }
+
+    let mut prompt = format!("{}{}", prompt::DATA_NOTICE.trim_start(), REVIEW_PROMPT);
+    let diff = prompt::prepare_diff(&diff, &config);
+    prompt.push_str(&prompt::fenced_fit(&diff, prompt.len(), &config));
+
+    eprintln!("Asking qwen to review the changes...");
+    let answer = backend::generate(&prompt).map_err(|e| format!("Failed to generate review: {}", e))?;
+    let review = output::clean_document(&answer);
+    if review.is_empty() {
+        return Err("qwen returned an empty review".to_string());
+    }
+    println!("{}", review);
+    Ok(())
+}
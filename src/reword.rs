@@ -0,0 +1,208 @@
+//! `git-qwen reword <RANGE>`: regenerates the messages of existing commits
+//! through `git rebase -i`, letting the user review each one.
+//!
+//! git-qwen runs the rebase with itself as both the sequence editor, which
+//! marks the commits in the range for rewording, and the message editor,
+//! which puts a regenerated message in front of the user's real editor.
+//! The messages are generated before the rebase starts, several at once,
+//! and kept until it is done, so a run that fails part way resumes without
+//! asking for them again.
+
+use crate::amend::{Amend, Mode};
+use crate::backend;
+use crate::cleanup::Cleanup;
+use crate::config::Config;
+use crate::git::{self, SystemGit};
+use crate::history;
+use crate::jobs::{self, Journal};
+use crate::pipeline::MessageGenerator;
+use crate::prompt::PromptOptions;
+use crate::trailers;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Full names of the commits to reword, separated by spaces, passed from
+/// the command to its editor steps.
+const COMMITS_VAR: &str = "QWEN_REWORD_COMMITS";
+
+/// The user's own editor, which reviews each regenerated message.
+const EDITOR_VAR: &str = "QWEN_REWORD_EDITOR";
+
+/// Journal of the messages generated for the commits to reword, by their
+/// full names.
+const JOURNAL: &str = "reword";
+
+/// Starts an interactive rebase that rewords every commit in `range`
+/// (`A..B`, or `A` for `A..HEAD`).
+pub fn run(range: &str) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+
+    let (base, end) = history::split_range(range);
+    let base = git::output(&["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", base)])
+        .map_err(|_| format!("Not a commit: {}", base))?;
+    let base = base.trim();
+
+    git::output(&["merge-base", "--is-ancestor", end, "HEAD"])
+        .map_err(|_| format!("{} is not on the current branch", end))?;
+    if !git::output(&["rev-list", "--merges", &format!("{}..HEAD", base)])?.trim().is_empty() {
+        return Err("The commits to rebase include merges, which reword cannot replay".to_string());
+    }
+
+    let commits = git::output(&["rev-list", &format!("{}..{}", base, end)])?;
+    let commits: Vec<&str> = commits.split_whitespace().collect();
+    if commits.is_empty() {
+        return Err(format!("No commits in {}", range));
+    }
+
+    eprintln!("Rewording {} commit(s)...", commits.len());
+    let config = Config::load();
+    let journal = Journal::open(JOURNAL)?;
+    let limit = config.get_usize("jobs").unwrap_or(jobs::DEFAULT_JOBS);
+    jobs::run_resumable(
+        &journal,
+        &commits,
+        limit,
+        "Generating messages",
+        |commit| commit.to_string(),
+        |commit| regenerate(commit, &config),
+    )?;
+
+    let exe = env::current_exe().map_err(|e| format!("Failed to locate git-qwen: {}", e))?;
+    let exe = format!("'{}'", exe.display());
+
+    let mut command = Command::new("git");
+    command
+        .args(["rebase", "-i", base])
+        .env("GIT_SEQUENCE_EDITOR", format!("{} reword --todo", exe))
+        .env("GIT_EDITOR", format!("{} reword --message", exe))
+        .env(COMMITS_VAR, commits.join(" "))
+        .env(EDITOR_VAR, crate::editor::command());
+    // The editor steps run as new processes, which only see the environment
+    let backends: Vec<&str> = backend::chain().iter().map(|backend| backend.name()).collect();
+    command.env(backend::BACKENDS_VAR, backends.join(","));
+    if let Some(model) = backend::selected_model() {
+        command.env(backend::MODEL_VAR, model);
+    }
+    let sampling = backend::sampling();
+    if let Some(temperature) = sampling.temperature {
+        command.env(backend::TEMPERATURE_VAR, temperature.to_string());
+    }
+    if let Some(seed) = sampling.seed {
+        command.env(backend::SEED_VAR, seed.to_string());
+    }
+    let status = command
+        .status()
+        .map_err(|e| format!("Failed to execute git rebase: {}", e))?;
+
+    if !status.success() {
+        return Err("git rebase did not complete; see its output above".to_string());
+    }
+    journal.remove();
+    Ok(())
+}
+
+/// Sequence editor step: turns the `pick` of each commit to reword into
+/// `reword`.
+pub fn edit_todo(file: &Path) -> Result<(), String> {
+    let commits = env::var(COMMITS_VAR).unwrap_or_default();
+    let commits: Vec<&str> = commits.split_whitespace().collect();
+    let todo = fs::read_to_string(file).map_err(|e| format!("Failed to read rebase todo: {}", e))?;
+
+    let mut edited = String::new();
+    for line in todo.lines() {
+        let mut words = line.split_whitespace();
+        let reword = matches!(words.next(), Some("pick" | "p"))
+            && words.next().is_some_and(|name| commits.iter().any(|commit| commit.starts_with(name)));
+
+        match line.split_once(char::is_whitespace) {
+            Some((_, rest)) if reword => edited.push_str(&format!("reword {}\n", rest)),
+            _ => edited.push_str(&format!("{}\n", line)),
+        }
+    }
+
+    fs::write(file, edited).map_err(|e| format!("Failed to write rebase todo: {}", e))
+}
+
+/// Message editor step: puts the message generated for the commit being
+/// reworded in the file, or regenerates it from HEAD if there is none,
+/// keeps the original below it as a comment, and opens the user's editor
+/// to review it. If generation fails, the original is shown unchanged.
+pub fn edit_message(file: &Path) -> Result<(), String> {
+    let path = PathBuf::from(file);
+    let existing = fs::read_to_string(&path).map_err(|e| format!("Failed to read commit message: {}", e))?;
+
+    let generated = match rebased_commit().and_then(|commit| Journal::open(JOURNAL).ok()?.get(&commit)) {
+        Some(message) => Ok(message),
+        None => regenerate("HEAD", &Config::load()),
+    };
+    match generated {
+        Ok(message) if !message.is_empty() => {
+            let cleanup = Cleanup::resolve(None, false, &existing)?;
+            let mut text = message;
+            text.push_str(&format!("\n\n{}\n", cleanup.comment_line("Original message:")));
+            for line in existing.lines() {
+                if line.starts_with(&cleanup.comment) {
+                    text.push_str(&format!("{}\n", line));
+                } else {
+                    text.push_str(&format!("{}\n", cleanup.comment_line(line)));
+                }
+            }
+            fs::write(&path, text).map_err(|e| format!("Failed to write commit message: {}", e))?;
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Warning: {}; keeping the original message", e),
+    }
+
+    let editor = env::var(EDITOR_VAR).unwrap_or_else(|_| crate::editor::command());
+    crate::editor::open(&editor, &path)
+}
+
+/// The original name of the commit the rebase is rewording: the last one
+/// in its list of done steps.
+fn rebased_commit() -> Option<String> {
+    let done = fs::read_to_string(git::path("rebase-merge/done").ok()?).ok()?;
+    let name = done
+        .lines()
+        .rev()
+        .find(|line| !line.starts_with('#') && !line.trim().is_empty())?
+        .split_whitespace()
+        .nth(1)?;
+    let commit = git::output(&["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", name)]).ok()?;
+    Some(commit.trim().to_string())
+}
+
+/// A new message for `commit`, revising its current one, followed by the
+/// trailers it keeps.
+fn regenerate(commit: &str, config: &Config) -> Result<String, String> {
+    let (message, kept_trailers) = trailers::split(&git::output(&["log", "-1", "--format=%B", commit])?);
+    let trailer_block = trailers::block("", &kept_trailers, None, &[]);
+
+    let options = PromptOptions {
+        amend: Some(Amend {
+            message,
+            trailers: kept_trailers,
+            added: String::new(),
+            mode: Mode::Revise,
+        }),
+        ..Default::default()
+    };
+    let message = MessageGenerator::new(config, &options)
+        .reuse_cached(true)
+        .generate(&commit_diff(commit)?)
+        .map_err(|e| format!("Failed to generate a message for {}: {}", commit, e))?;
+    if message.is_empty() || trailer_block.is_empty() {
+        return Ok(message);
+    }
+    Ok(format!("{}\n\n{}", message, trailer_block.trim_end()))
+}
+
+/// The changes `commit` made, or everything it adds if it is a root commit.
+fn commit_diff(commit: &str) -> Result<String, String> {
+    let mut args = vec!["show".to_string(), "--format=".to_string()];
+    args.extend(crate::diff::options(&SystemGit));
+    args.push(commit.to_string());
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    git::output(&args)
+}
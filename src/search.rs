@@ -0,0 +1,135 @@
+//! `git-qwen search <QUERY>`: finds commits related to a query by meaning
+//! rather than by keyword, by letting the model read the history.
+
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use crate::jobs;
+use crate::output;
+use crate::prompt;
+
+/// Default number of recent commits searched.
+const DEFAULT_MAX_COUNT: usize = 500;
+
+/// Most results shown.
+const MAX_RESULTS: usize = 10;
+
+/// Lines of each message body shown to the model besides the subject.
+const BODY_LINES: usize = 3;
+
+const SEARCH_PROMPT: &str = "Find the commits below that are related to the search query, by what they are about and not only by matching words. Rate each related commit from 1 (loosely related) to 10 (exactly what is asked for), and leave out unrelated ones. Answer with only a JSON object of the form {\"matches\": [{\"commit\": \"<name as listed>\", \"score\": 8, \"reason\": \"<one short line on why it matches>\"}]}, with an empty list if nothing matches, nothing else.
+
+";
+
+/// A commit the model found related to the query.
+struct Match {
+    id: String,
+    subject: String,
+    score: u64,
+    reason: String,
+}
+
+/// Prints the commits among the last `max_count` (by default
+/// `qwen.searchDepth`) that best match `query`, most relevant first, each
+/// with why it matches.
+pub fn run(query: &str, max_count: Option<usize>) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+    if query.trim().is_empty() {
+        return Err("Give a query to search for".to_string());
+    }
+
+    let max_count = max_count.or(config.get_usize("searchDepth")).unwrap_or(DEFAULT_MAX_COUNT);
+    let log = git::output(&["log", "--no-merges", &format!("--max-count={}", max_count), "--format=%h%x00%B%x00"])?;
+    let fields: Vec<&str> = log.split('\0').collect();
+    let commits: Vec<(String, String)> = fields
+        .chunks(2)
+        .filter(|pair| pair.len() == 2 && !pair[0].trim().is_empty())
+        .map(|pair| (pair[0].trim().to_string(), summary(pair[1])))
+        .collect();
+    if commits.is_empty() {
+        return Err("No commits to search".to_string());
+    }
+
+    // Each call gets as many commits as fit in the prompt budget
+    let header = format!(
+        "{}{}Search query: {}\n\nCommits, newest first:\n",
+        prompt::DATA_NOTICE.trim_start(),
+        SEARCH_PROMPT,
+        query.trim()
+    );
+    let budget = prompt::max_chars(&config).map(|max| max.saturating_sub(header.len() + prompt::FENCE_CHARS));
+    let mut batches: Vec<Vec<&(String, String)>> = vec![Vec::new()];
+    let mut used = 0;
+    for commit in &commits {
+        let size = commit.0.len() + commit.1.len() + 2;
+        if budget.is_some_and(|budget| used + size > budget) && used > 0 {
+            batches.push(Vec::new());
+            used = 0;
+        }
+        batches.last_mut().expect("there is always a batch").push(commit);
+        used += size;
+    }
+
+    let limit = config.get_usize("jobs").unwrap_or(jobs::DEFAULT_JOBS);
+    let results = jobs::run_parallel(&batches, limit, "Searching commits", |batch| {
+        let mut listing = String::new();
+        for (id, summary) in batch {
+            listing.push_str(&format!("{} {}\n\n", id, summary));
+        }
+        let prompt = format!("{}{}", header, prompt::fence(&listing));
+        backend::generate(&prompt).map(|answer| parse_matches(&answer, batch))
+    })?;
+
+    let mut matches: Vec<Match> = results.into_iter().flatten().collect();
+    // Stable, so equal scores stay newest first
+    matches.sort_by_key(|found| std::cmp::Reverse(found.score));
+    let mut seen = Vec::new();
+    matches.retain(|found| {
+        let new = !seen.contains(&found.id);
+        seen.push(found.id.clone());
+        new
+    });
+    if matches.is_empty() {
+        println!("No commits match \"{}\".", query.trim());
+    }
+    for found in matches.iter().take(MAX_RESULTS) {
+        println!("{} {}", found.id, found.subject);
+        if !found.reason.is_empty() {
+            println!("    {}", found.reason);
+        }
+    }
+    Ok(())
+}
+
+/// The subject and the first few non-empty lines of the body.
+fn summary(message: &str) -> String {
+    let lines: Vec<&str> = message.trim().lines().filter(|line| !line.trim().is_empty()).collect();
+    lines.iter().take(1 + BODY_LINES).copied().collect::<Vec<_>>().join("\n")
+}
+
+/// The matches in a JSON answer that name one of the `batch` commits.
+fn parse_matches(answer: &str, batch: &[&(String, String)]) -> Vec<Match> {
+    let Some(value) = output::json_object(answer) else {
+        return Vec::new();
+    };
+    let Some(matches) = value.get("matches").and_then(|matches| matches.as_array()) else {
+        return Vec::new();
+    };
+
+    matches
+        .iter()
+        .filter_map(|found| {
+            let name = found.get("commit")?.as_str()?.trim();
+            let (id, summary) = batch
+                .iter()
+                .find(|(id, _)| name.len() >= 4 && (id.starts_with(name) || name.starts_with(id.as_str())))?;
+            Some(Match {
+                id: id.clone(),
+                subject: summary.lines().next().unwrap_or("").to_string(),
+                score: found.get("score").and_then(|score| score.as_u64()).unwrap_or(0),
+                reason: found.get("reason").and_then(|reason| reason.as_str()).unwrap_or("").trim().to_string(),
+            })
+        })
+        .collect()
+}
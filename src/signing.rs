@@ -0,0 +1,136 @@
+//! Commit signing. git signs when `-S` is given or `commit.gpgSign` is set;
+//! git-qwen checks beforehand that it can, so a missing program or key
+//! fails before a message is generated, and explains a commit that failed
+//! while signing.
+
+use crate::capabilities;
+use crate::error::Error;
+use crate::git;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The kinds of signature git makes, from `gpg.format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    OpenPgp,
+    X509,
+    Ssh,
+}
+
+/// How the commit is going to be signed.
+#[derive(Debug)]
+pub struct Signing {
+    format: Format,
+    /// The program git signs with
+    program: String,
+    /// The key given with `-S`, else `user.signingKey`, if set
+    key: Option<String>,
+}
+
+impl Signing {
+    /// How git will sign the commit, or None when it will not: `sign` is
+    /// what the command line asks for (`-S` or `--no-gpg-sign`), else
+    /// `commit.gpgSign` decides, and `key` is the key it names, if any.
+    pub fn resolve(sign: Option<bool>, key: Option<&str>) -> Option<Self> {
+        let sign = sign.unwrap_or_else(|| {
+            git::output(&["config", "--type=bool", "commit.gpgSign"]).is_ok_and(|value| value.trim() == "true")
+        });
+        if !sign {
+            return None;
+        }
+
+        let setting = |key: &str| git::output(&["config", key]).ok().map(|value| value.trim().to_string());
+        let (format, name, default_program) = match setting("gpg.format").as_deref() {
+            Some("ssh") => (Format::Ssh, "ssh", "ssh-keygen"),
+            Some("x509") => (Format::X509, "x509", "gpgsm"),
+            _ => (Format::OpenPgp, "openpgp", "gpg"),
+        };
+        // gpg.program is the older name of gpg.openpgp.program
+        let program = setting(&format!("gpg.{}.program", name))
+            .or_else(|| if format == Format::OpenPgp { setting("gpg.program") } else { None })
+            .unwrap_or_else(|| default_program.to_string());
+        Some(Signing {
+            format,
+            program,
+            key: key
+                .map(String::from)
+                .or_else(|| setting("user.signingKey"))
+                .filter(|key| !key.is_empty()),
+        })
+    }
+
+    /// Checks that the signing program is installed and has a key to sign
+    /// with.
+    pub fn check(&self) -> Result<(), Error> {
+        let installed = Path::new(&self.program).is_file() || capabilities::find_program(&self.program).is_some();
+        if !installed {
+            return Err(Error::Other(format!(
+                "Commits are signed here, but {} is not installed; install it, or pass --no-sign to commit without a signature",
+                self.program
+            )));
+        }
+
+        match self.format {
+            Format::Ssh => {
+                let default_key = git::output(&["config", "gpg.ssh.defaultKeyCommand"]).is_ok();
+                if self.key.is_none() && !default_key {
+                    return Err(Error::Other(
+                        "Commits are signed with SSH here, but user.signingKey is not set; set it to your public key, or pass --no-sign to commit without a signature".to_string(),
+                    ));
+                }
+            }
+            // gpgsm takes the same options as gpg for listing keys
+            Format::OpenPgp | Format::X509 => {
+                // Without a key git signs as the committer
+                let key = match &self.key {
+                    Some(key) => key.clone(),
+                    None => committer_email().unwrap_or_default(),
+                };
+                let found = Command::new(&self.program)
+                    .args(["--batch", "--list-secret-keys", &key])
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .is_ok_and(|status| status.success());
+                if !found {
+                    return Err(Error::Other(format!(
+                        "Commits are signed here, but {} has no secret key for {}; set user.signingKey or pass -S<key>, or pass --no-sign to commit without a signature",
+                        self.program, key
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// What to tell the user when `git commit` failed with signing on;
+    /// git's own error says little more than that signing failed.
+    pub fn failure_hint(&self) -> String {
+        let cause = match self.format {
+            Format::OpenPgp => format!(
+                "If signing failed, {} most likely could not ask for the passphrase: run `export GPG_TTY=$(tty)` in this shell, or check that `echo test | {} --clearsign` works.",
+                self.program, self.program
+            ),
+            Format::X509 => format!(
+                "If signing failed, check that {} can reach your certificate and ask for its passphrase.",
+                self.program
+            ),
+            Format::Ssh => {
+                "If signing failed, check that ssh-agent holds the key in user.signingKey (`ssh-add -l`).".to_string()
+            }
+        };
+        format!(
+            "{}\nThe message is saved: `git-qwen --resume` commits it without generating it again, and --no-sign commits without a signature.",
+            cause
+        )
+    }
+}
+
+/// The committer's email address, which gpg finds the key by when
+/// `user.signingKey` is not set.
+fn committer_email() -> Option<String> {
+    let ident = git::output(&["var", "GIT_COMMITTER_IDENT"]).ok()?;
+    let start = ident.find('<')? + 1;
+    let end = ident[start..].find('>')? + start;
+    Some(ident[start..end].to_string())
+}
@@ -0,0 +1,280 @@
+//! Experimental retrieval of past commits that resemble the current change.
+//!
+//! Each commit is embedded as a hashed bag-of-words vector built from its
+//! message and the paths it touched. The vectors are kept in an index in
+//! the cache directory and extended incrementally as new commits appear.
+
+use crate::config::Config;
+use crate::diff;
+use crate::git;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const DIMENSIONS: usize = 1024;
+const INDEX_FILE: &str = "similar-index.tsv";
+const INDEX_VERSION: &str = "git-qwen-similar-v1";
+
+/// Upper bound on indexed commits so huge histories stay cheap to search.
+const MAX_INDEXED_COMMITS: usize = 5000;
+
+/// Commits scoring below this cosine similarity are not worth showing.
+const MIN_SCORE: f32 = 0.15;
+
+/// Only the first lines of a diff are used for the query, which is plenty
+/// to capture the identifiers involved.
+const MAX_QUERY_LINES: usize = 2000;
+
+struct IndexedCommit {
+    hash: String,
+    message: String,
+    vector: Vec<(usize, f32)>,
+}
+
+#[derive(Default)]
+struct Index {
+    tip: String,
+    commits: Vec<IndexedCommit>,
+}
+
+/// Returns the messages of up to `qwen.similarCommits` past commits that are
+/// most similar to `diff`, or nothing when the feature is disabled.
+pub fn examples(diff: &str, config: &Config) -> Vec<String> {
+    let count = config.get_usize("similarCommits").unwrap_or(0);
+    if count == 0 {
+        return Vec::new();
+    }
+
+    match find_similar(diff, count) {
+        Ok(messages) => messages,
+        Err(e) => {
+            eprintln!("Warning: similar commit lookup failed: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn find_similar(diff: &str, count: usize) -> Result<Vec<String>, String> {
+    let index = update_index()?;
+    let query = embed(&query_text(diff));
+
+    let mut scored: Vec<(f32, &IndexedCommit)> = index
+        .commits
+        .iter()
+        .map(|commit| {
+            let score = commit.vector.iter().map(|&(i, w)| query[i] * w).sum();
+            (score, commit)
+        })
+        .filter(|(score, _)| *score >= MIN_SCORE)
+        .collect();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    Ok(scored
+        .into_iter()
+        .take(count)
+        .map(|(_, commit)| commit.message.clone())
+        .collect())
+}
+
+/// Brings the on-disk index up to date with HEAD and returns it.
+fn update_index() -> Result<Index, String> {
+    let Ok(head) = git::output(&["rev-parse", "--verify", "-q", "HEAD"]) else {
+        // No commits yet, so there is nothing to learn from
+        return Ok(Index::default());
+    };
+    let head = head.trim().to_string();
+
+    let path = git::cache_dir()?.join(INDEX_FILE);
+    let mut index = load_index(&path).unwrap_or_default();
+    if index.tip == head {
+        return Ok(index);
+    }
+
+    // Extend the index when the previous tip is still part of history;
+    // after a rebase or branch switch, start over
+    let range = if !index.tip.is_empty() && is_ancestor(&index.tip, &head) {
+        format!("{}..{}", index.tip, head)
+    } else {
+        index.commits.clear();
+        head.clone()
+    };
+
+    let mut commits = read_commits(&range)?;
+    commits.append(&mut index.commits);
+    commits.truncate(MAX_INDEXED_COMMITS);
+
+    index = Index { tip: head, commits };
+    save_index(&path, &index)?;
+    Ok(index)
+}
+
+fn is_ancestor(ancestor: &str, descendant: &str) -> bool {
+    git::output(&["merge-base", "--is-ancestor", ancestor, descendant]).is_ok()
+}
+
+/// Reads and embeds the commits in `range`, newest first.
+fn read_commits(range: &str) -> Result<Vec<IndexedCommit>, String> {
+    let max_count = format!("--max-count={}", MAX_INDEXED_COMMITS);
+    let log = git::output(&[
+        "log",
+        "--no-merges",
+        &max_count,
+        "--format=%x1e%H%x1f%B%x1f",
+        "--name-only",
+        range,
+    ])?;
+
+    Ok(log
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.split('\x1f');
+            let hash = fields.next()?.trim().to_string();
+            let message = fields.next()?.trim().to_string();
+            let paths = fields.next().unwrap_or("");
+            if hash.is_empty() || message.is_empty() {
+                return None;
+            }
+
+            let vector = sparse(&embed(&format!("{}\n{}", message, paths)));
+            Some(IndexedCommit { hash, message, vector })
+        })
+        .collect())
+}
+
+/// Text representing the change being committed: the touched paths plus
+/// the added and removed lines.
+fn query_text(diff: &str) -> String {
+    let mut text = diff::changed_paths(diff).join("\n");
+    for line in diff.lines().take(MAX_QUERY_LINES) {
+        if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+        if let Some(content) = line.strip_prefix('+').or_else(|| line.strip_prefix('-')) {
+            text.push('\n');
+            text.push_str(content);
+        }
+    }
+    text
+}
+
+/// Embeds text as a normalized vector of hashed token frequencies.
+fn embed(text: &str) -> Vec<f32> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for token in text.split(|c: char| !c.is_alphanumeric()) {
+        if token.chars().count() < 2 || token.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        *counts.entry(token.to_lowercase()).or_default() += 1;
+    }
+
+    let mut vector = vec![0.0f32; DIMENSIONS];
+    for (token, count) in counts {
+        let hash = fnv1a(token.as_bytes());
+        // A hash-derived sign keeps colliding tokens from always adding up
+        let sign = if hash & (1 << 63) == 0 { 1.0 } else { -1.0 };
+        vector[(hash % DIMENSIONS as u64) as usize] += sign * (1.0 + (count as f32).ln());
+    }
+
+    let norm = vector.iter().map(|w| w * w).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|w| *w /= norm);
+    }
+    vector
+}
+
+fn sparse(vector: &[f32]) -> Vec<(usize, f32)> {
+    vector
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| **w != 0.0)
+        .map(|(i, w)| (i, *w))
+        .collect()
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Index format: a header line with the version and the indexed tip, then
+/// one `hash<TAB>vector<TAB>message` line per commit, where the vector is a
+/// comma-separated list of `dimension:weight` pairs.
+fn load_index(path: &Path) -> Option<Index> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut lines = content.lines();
+
+    let (version, tip) = lines.next()?.split_once('\t')?;
+    if version != INDEX_VERSION {
+        return None;
+    }
+
+    let mut commits = Vec::new();
+    for line in lines {
+        let mut fields = line.splitn(3, '\t');
+        let hash = fields.next()?.to_string();
+        let vector = fields
+            .next()?
+            .split(',')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (i, w) = pair.split_once(':')?;
+                let i: usize = i.parse().ok()?;
+                (i < DIMENSIONS).then_some((i, w.parse().ok()?))
+            })
+            .collect::<Option<Vec<(usize, f32)>>>()?;
+        let message = unescape(fields.next()?);
+        commits.push(IndexedCommit { hash, message, vector });
+    }
+
+    Some(Index {
+        tip: tip.to_string(),
+        commits,
+    })
+}
+
+fn save_index(path: &Path, index: &Index) -> Result<(), String> {
+    let mut content = format!("{}\t{}\n", INDEX_VERSION, index.tip);
+    for commit in &index.commits {
+        let vector: Vec<String> = commit
+            .vector
+            .iter()
+            .map(|(i, w)| format!("{}:{:.4}", i, w))
+            .collect();
+        content.push_str(&format!(
+            "{}\t{}\t{}\n",
+            commit.hash,
+            vector.join(","),
+            escape(&commit.message)
+        ));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write similar commit index: {}", e))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n").replace('\t', "\\t")
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
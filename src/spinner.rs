@@ -0,0 +1,147 @@
+//! A spinner with the elapsed time on stderr while the model works, so a
+//! slow local model does not look like a hang. The model's answer can be
+//! streamed above it as it is written.
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+const TICK: Duration = Duration::from_millis(100);
+
+/// What the running spinner shows, if one is running. Drawing happens with
+/// the lock held so other output never lands in the middle of the line.
+static LINE: Mutex<Option<Line>> = Mutex::new(None);
+
+struct Line {
+    label: String,
+    status: Option<String>,
+    /// Whether model output read on the `owner` thread is shown
+    stream: bool,
+    owner: ThreadId,
+    /// Streamed output after the last complete line
+    pending: Vec<u8>,
+}
+
+/// A running spinner, cleared when dropped.
+pub struct Spinner {
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Spinner {
+    /// Starts a spinner labelled `label`, if stderr is a terminal. With
+    /// `stream`, the answers to model calls made on this thread are shown
+    /// as they arrive.
+    pub fn start(label: &str, stream: bool) -> Option<Spinner> {
+        if !io::stderr().is_terminal() {
+            return None;
+        }
+        *lock() = Some(Line {
+            label: label.to_string(),
+            status: None,
+            stream,
+            owner: thread::current().id(),
+            pending: Vec::new(),
+        });
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let thread = thread::spawn(move || {
+            let started = Instant::now();
+            let mut frame = 0;
+            while !flag.load(Ordering::SeqCst) {
+                if let Some(line) = lock().as_ref() {
+                    let status = line.status.as_ref().map(|status| format!(" ({})", status)).unwrap_or_default();
+                    eprint!(
+                        "\r\x1b[2K{} {}{} {}s",
+                        FRAMES[frame % FRAMES.len()],
+                        line.label,
+                        status,
+                        started.elapsed().as_secs()
+                    );
+                    let _ = io::stderr().flush();
+                }
+                frame += 1;
+                thread::sleep(TICK);
+            }
+        });
+
+        Some(Spinner {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        let mut line = lock();
+        eprint!("\r\x1b[2K");
+        if let Some(pending) = line.take().map(|line| line.pending).filter(|pending| !pending.is_empty()) {
+            print_streamed(&pending);
+        }
+        let _ = io::stderr().flush();
+    }
+}
+
+/// Shows `status` next to the spinner's label, or nothing if it is empty.
+/// Returns false when no spinner is running, so the caller can report it
+/// another way.
+pub fn set_status(status: &str) -> bool {
+    match lock().as_mut() {
+        Some(line) => {
+            line.status = (!status.is_empty()).then(|| status.to_string());
+            true
+        }
+        None => false,
+    }
+}
+
+/// Prints `message` on its own line of stderr, above the spinner if one is
+/// running.
+pub fn message(message: &str) {
+    let line = lock();
+    if line.is_some() {
+        eprint!("\r\x1b[2K");
+    }
+    eprintln!("{}", message);
+}
+
+/// Whether a model call made on this thread should stream its answer.
+pub fn streams_here() -> bool {
+    lock()
+        .as_ref()
+        .is_some_and(|line| line.stream && line.owner == thread::current().id())
+}
+
+/// Shows the complete lines of streamed model output above the spinner.
+pub fn stream(chunk: &[u8]) {
+    let mut line = lock();
+    let Some(line) = line.as_mut() else {
+        return;
+    };
+    line.pending.extend_from_slice(chunk);
+    while let Some(end) = line.pending.iter().position(|&byte| byte == b'\n') {
+        let text: Vec<u8> = line.pending.drain(..=end).collect();
+        eprint!("\r\x1b[2K");
+        print_streamed(&text[..end]);
+    }
+}
+
+/// Prints a line of model output, dimmed so it stands apart from
+/// git-qwen's own messages.
+fn print_streamed(text: &[u8]) {
+    eprintln!("\x1b[2m{}\x1b[0m", String::from_utf8_lossy(text).trim_end());
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<Line>> {
+    LINE.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,138 @@
+//! `git-qwen split`: asks the model to group the staged hunks into several
+//! logical commits, each with its own message, and creates them.
+
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use crate::hunks::{self, FilePatch};
+use crate::lint;
+use crate::output;
+use crate::prompt;
+use serde_json::Value;
+use std::fs;
+use std::process::Command;
+
+const SPLIT_PROMPT: &str = "The staged changes below mix several unrelated changes. Group their numbered hunks into logical commits, each a coherent change that makes sense on its own, ordered so that each commit builds only on the ones before it. Put every hunk in exactly one commit, and keep hunks that depend on each other together. Write a git commit message for each commit, following these rules:
+";
+
+const SPLIT_INSTRUCTION: &str = "
+Answer with only a JSON object of the form {\"commits\": [{\"hunks\": [1, 2], \"message\": \"subject\\n\\nbody\"}]}, nothing else. The hunks:
+
+";
+
+/// A proposed commit: the global numbers of its hunks (from 0) and its
+/// message.
+struct Proposal {
+    hunks: Vec<usize>,
+    message: String,
+}
+
+/// Proposes a split of the staged changes and, after confirmation (or with
+/// `yes`), commits it. With `edit`, the editor opens on each message before
+/// its commit is made; with `dry_run` the plan is only printed.
+pub fn run(yes: bool, dry_run: bool, edit: bool) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+
+    let files = hunks::staged()?;
+    let numbered = hunks::numbered(&files);
+    if numbered.len() < 2 {
+        return Err("Stage at least two hunks to split".to_string());
+    }
+
+    eprintln!("Asking qwen how to split {} hunk(s)...", numbered.len());
+    let answer = backend::generate(&build_prompt(&files, &numbered, &config))?;
+    let proposals = parse_answer(&answer, numbered.len(), &config)
+        .ok_or_else(|| "qwen did not propose a usable split".to_string())?;
+
+    for (index, proposal) in proposals.iter().enumerate() {
+        println!("{}. {}", index + 1, proposal.message.lines().next().unwrap_or(""));
+        for (file_index, file) in files.iter().enumerate() {
+            let count = proposal.hunks.iter().filter(|hunk| numbered[**hunk].0 == file_index).count();
+            if count > 0 {
+                println!("    {} ({} hunk(s))", file.path, count);
+            }
+        }
+    }
+    let assigned: usize = proposals.iter().map(|proposal| proposal.hunks.len()).sum();
+    if assigned < numbered.len() {
+        println!("{} hunk(s) were not placed in any commit and stay staged.", numbered.len() - assigned);
+    }
+
+    if dry_run || !(yes || hunks::confirm(&format!("Create these {} commits?", proposals.len()))) {
+        return Ok(());
+    }
+
+    let message_file = git::path("QWEN_SPLIT_MSG")?;
+    let message_file = message_file.to_string_lossy();
+    let result = hunks::commit_groups(
+        &files,
+        |file, hunk| {
+            let number = numbered.iter().position(|entry| *entry == (file, hunk))?;
+            proposals.iter().position(|proposal| proposal.hunks.contains(&number))
+        },
+        proposals.len(),
+        |group| commit(&message_file, &proposals[group].message, edit),
+    );
+    let _ = fs::remove_file(&*message_file);
+    result
+}
+
+fn build_prompt(files: &[FilePatch], numbered: &[(usize, usize)], config: &Config) -> String {
+    let mut prompt = SPLIT_PROMPT.to_string();
+    prompt.push_str(&prompt::format_rules(lint::Limits::from_config(config)));
+    prompt.push_str(prompt::DATA_NOTICE);
+    prompt.push_str(SPLIT_INSTRUCTION);
+
+    let budget = prompt::max_chars(config).map(|max| max.saturating_sub(prompt.len()));
+    prompt.push_str(&prompt::fence(&hunks::prompt_listing(files, numbered, budget, config)));
+    prompt
+}
+
+/// Reads the proposed commits, dropping hunk numbers that are out of range
+/// or already used and commits left without hunks.
+fn parse_answer(answer: &str, hunk_count: usize, config: &Config) -> Option<Vec<Proposal>> {
+    let value = output::json_object(answer)?;
+    let commits = value.get("commits")?.as_array()?;
+
+    let mut used = vec![false; hunk_count];
+    let mut proposals = Vec::new();
+    for commit in commits {
+        let message = crate::MessageFormatter::new(config).format(commit.get("message")?.as_str()?);
+        let hunks: Vec<usize> = commit
+            .get("hunks")?
+            .as_array()?
+            .iter()
+            .filter_map(Value::as_u64)
+            .filter_map(|number| (number as usize).checked_sub(1))
+            .filter(|index| *index < hunk_count && !std::mem::replace(&mut used[*index], true))
+            .collect();
+
+        if !hunks.is_empty() && !message.is_empty() {
+            proposals.push(Proposal { hunks, message });
+        }
+    }
+
+    (!proposals.is_empty()).then_some(proposals)
+}
+
+fn commit(message_file: &str, message: &str, edit: bool) -> Result<(), String> {
+    fs::write(message_file, format!("{}\n", message))
+        .map_err(|e| format!("Failed to write commit message file: {}", e))?;
+
+    let mut command = Command::new("git");
+    command.args(["commit", "-F", message_file]);
+    if edit {
+        command.arg("--edit");
+    } else {
+        command.arg("--quiet");
+    }
+
+    let status = command
+        .status()
+        .map_err(|e| format!("Failed to execute git commit: {}", e))?;
+    if !status.success() {
+        return Err("git commit failed; the remaining changes are still staged".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! Squashing several commits into one with a single synthesized message,
+//! either with `git-qwen squash <BASE>` or while `git rebase -i` combines
+//! commits.
+
+use crate::git;
+use crate::prompt;
+
+/// Header git puts at the top of the message of combined commits during
+/// an interactive rebase, after the comment character.
+const REBASE_HEADER: &str = "This is a combination of ";
+
+/// The commits after `base` on the current branch, to be replaced by one.
+#[derive(Debug)]
+pub struct Squash {
+    /// Full name of the commit the squashed commit goes on top of
+    pub base: String,
+    /// Name of HEAD before squashing, to undo it
+    pub head: String,
+    /// Messages of the commits being squashed, oldest first
+    pub messages: Vec<String>,
+}
+
+impl Squash {
+    /// Collects the commits in `base..HEAD`, failing if `base` is not an
+    /// ancestor of HEAD or there is nothing to squash.
+    pub fn load(base: &str) -> Result<Self, String> {
+        let base_commit = git::output(&["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", base)])
+            .map_err(|_| format!("Not a commit: {}", base))?
+            .trim()
+            .to_string();
+        let head = git::output(&["rev-parse", "--verify", "HEAD"])?.trim().to_string();
+
+        git::output(&["merge-base", "--is-ancestor", &base_commit, &head])
+            .map_err(|_| format!("{} is not an ancestor of HEAD", base))?;
+
+        let log = git::output(&["log", "--reverse", "--format=%B%x00", &format!("{}..{}", base_commit, head)])?;
+        let messages: Vec<String> = log
+            .split('\0')
+            .map(|message| message.trim().to_string())
+            .filter(|message| !message.is_empty())
+            .collect();
+
+        if messages.is_empty() {
+            return Err(format!("No commits to squash after {}", base));
+        }
+
+        Ok(Squash { base: base_commit, head, messages })
+    }
+
+    /// The combined change: everything between the base and the index, and
+    /// with `include_all` the unstaged changes too.
+    pub fn diff(&self, include_all: bool) -> Result<String, String> {
+        let options = crate::diff::options(&git::SystemGit);
+        let mut args = vec!["diff"];
+        args.extend(options.iter().map(String::as_str));
+        let mut diff = git::output(&[&args[..], &["--cached", &self.base]].concat())?;
+        if include_all {
+            diff.push_str(&git::output(&args)?);
+        }
+        Ok(diff)
+    }
+
+    /// Moves the branch back to the base, keeping the squashed changes
+    /// staged for the commit that replaces them.
+    pub fn reset(&self) -> Result<(), String> {
+        git::output(&["reset", "--soft", &self.base])?;
+        eprintln!(
+            "Squashed {} commit(s); to undo, run: git reset --soft {}",
+            self.messages.len(),
+            &self.head[..self.head.len().min(12)]
+        );
+        Ok(())
+    }
+}
+
+/// The prompt section listing the messages of the commits being squashed.
+pub fn prompt_section(messages: &[String]) -> String {
+    let mut section = "\nThis commit squashes the commits below into one. Write a single message that describes the combined change as a whole, as if it had been made in one step; don't list the commits one by one, don't mention that they were squashed, and leave out changes that later commits undid:\n".to_string();
+    section.push_str(&prompt::fence(&prompt::separated(messages)));
+    section
+}
+
+/// The comment string and the messages git collected in the message file
+/// of an interactive rebase's squash, or None if the file is not one. Each
+/// message follows a comment line such as `# This is the commit message
+/// #2:`; messages of fixups are commented out by git and so are skipped.
+pub fn rebase_messages(text: &str) -> Option<(String, Vec<String>)> {
+    let first = text.lines().next()?;
+    let comment = first[..first.find(REBASE_HEADER)?].trim_end();
+    if comment.is_empty() {
+        return None;
+    }
+
+    let mut messages: Vec<String> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for line in text.lines().skip(1) {
+        if line.starts_with(comment) {
+            let message = current.join("\n").trim().to_string();
+            if !message.is_empty() {
+                messages.push(message);
+            }
+            current.clear();
+        } else {
+            current.push(line);
+        }
+    }
+    let message = current.join("\n").trim().to_string();
+    if !message.is_empty() {
+        messages.push(message);
+    }
+
+    Some((comment.to_string(), messages))
+}
+
+/// The change made by the commits being combined in an interactive rebase:
+/// git amends the first of them, so it is the index compared with that
+/// commit's parent, or with the empty tree for a root commit.
+pub fn rebase_diff() -> Result<String, String> {
+    let parent = match git::output(&["rev-parse", "--verify", "--quiet", "HEAD^"]) {
+        Ok(parent) => parent,
+        Err(_) => git::output(&["hash-object", "-t", "tree", "/dev/null"])?,
+    };
+    let options = crate::diff::options(&git::SystemGit);
+    let mut args = vec!["diff"];
+    args.extend(options.iter().map(String::as_str));
+    git::output(&[&args[..], &["--cached", parent.trim()]].concat())
+}
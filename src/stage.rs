@@ -0,0 +1,193 @@
+//! `git-qwen --patch`: chooses which hunks to stage, like `git add -p`,
+//! with a one-line description of each hunk written by qwen.
+
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use crate::hunks::{self, FilePatch};
+use crate::output;
+use crate::prompt;
+use std::collections::HashSet;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+const DESCRIBE_PROMPT: &str = "Describe what each numbered hunk of the diff below does, in one short line of under 80 characters each, so that someone choosing which hunks to commit can tell them apart. Answer with only a JSON object of the form {\"hunks\": [\"description of hunk 1\", \"description of hunk 2\"]}, with one description per hunk in order, nothing else. The hunks:
+
+";
+
+const HELP: &str = "y - stage this hunk
+n - do not stage this hunk
+a - stage this hunk and the rest of this file
+d - do not stage this hunk or the rest of this file
+q - quit, leaving the index as it was
+? - print help
+Enter keeps the hunk as it is now";
+
+/// Walks through every change between HEAD and the work tree, staged or
+/// not, and lets the user choose which hunks the index should contain.
+/// Nothing changes until every hunk has been answered; quitting leaves the
+/// index as it was.
+pub fn run(config: &Config) -> Result<(), String> {
+    if !io::stdin().is_terminal() {
+        return Err("--patch needs a terminal to ask which hunks to stage".to_string());
+    }
+
+    let base = match git::output(&["rev-parse", "--verify", "--quiet", "HEAD^{tree}"]) {
+        Ok(tree) => tree,
+        Err(_) => git::output(&["hash-object", "-t", "tree", "/dev/null"])?,
+    };
+    let base = base.trim();
+    let diff_args = ["-U0", "--no-renames", "--no-color", "--no-ext-diff"];
+
+    let files = hunks::parse(&git::output(&[&["diff"][..], &diff_args, &[base]].concat())?);
+    let numbered = hunks::numbered(&files);
+    if numbered.is_empty() {
+        return Err("No changes to stage".to_string());
+    }
+
+    // A hunk is staged if the index has exactly the same change
+    let staged: HashSet<(String, usize, String)> = hunks::parse(&git::output(
+        &[&["diff", "--cached"][..], &diff_args, &[base]].concat(),
+    )?)
+    .iter()
+    .flat_map(|file| file.hunks.iter().map(move |hunk| key(file, hunk)))
+    .collect();
+    let mut chosen: Vec<bool> = numbered
+        .iter()
+        .map(|(file, hunk)| staged.contains(&key(&files[*file], &files[*file].hunks[*hunk])))
+        .collect();
+
+    eprintln!("Asking qwen to describe {} hunk(s)...", numbered.len());
+    let descriptions = describe(&files, &numbered, config).unwrap_or_else(|e| {
+        eprintln!("Warning: {}; showing the hunks without descriptions", e);
+        Vec::new()
+    });
+
+    let mut number = 0;
+    while number < numbered.len() {
+        let (file_index, hunk_index) = numbered[number];
+        let file = &files[file_index];
+        let hunk = &file.hunks[hunk_index];
+
+        println!(
+            "\n{} ({}/{}){}",
+            file.path,
+            number + 1,
+            numbered.len(),
+            if chosen[number] { " [staged]" } else { "" }
+        );
+        if let Some(description) = descriptions.get(number).filter(|d| !d.is_empty()) {
+            println!("qwen: {}", description);
+        }
+        print!("{}", hunk.text);
+
+        // Hunks of the same file after this one, for a and d
+        let rest_of_file = numbered[number..].iter().take_while(|(file, _)| *file == file_index).count();
+        let answer = ask("Stage this hunk [y,n,a,d,q,?]? ")?;
+        match answer.as_str() {
+            "" => number += 1,
+            "y" => {
+                chosen[number] = true;
+                number += 1;
+            }
+            "n" => {
+                chosen[number] = false;
+                number += 1;
+            }
+            "a" | "d" => {
+                chosen[number..number + rest_of_file].fill(answer == "a");
+                number += rest_of_file;
+            }
+            "q" => {
+                eprintln!("Quit; the index is unchanged.");
+                return Err("Nothing committed".to_string());
+            }
+            _ => println!("{}", HELP),
+        }
+    }
+
+    apply(&files, &numbered, &chosen, base)
+}
+
+/// Identifies a hunk by its file, where it starts in HEAD, and its lines,
+/// which are the same in both diffs when the change is fully staged.
+fn key(file: &FilePatch, hunk: &hunks::Hunk) -> (String, usize, String) {
+    let lines = hunk.text.split_once('\n').map_or("", |(_, lines)| lines);
+    (file.path.clone(), hunk.old_start, lines.to_string())
+}
+
+/// One description per hunk, asked for in a single qwen call.
+fn describe(files: &[FilePatch], numbered: &[(usize, usize)], config: &Config) -> Result<Vec<String>, String> {
+    let instructions = format!("{}{}", prompt::DATA_NOTICE.trim_start(), DESCRIBE_PROMPT);
+    let budget = prompt::max_chars(config).map(|max| max.saturating_sub(instructions.len()));
+    let answer = backend::generate(&format!(
+        "{}{}",
+        instructions,
+        prompt::fence(&hunks::prompt_listing(files, numbered, budget, config))
+    ))?;
+
+    let value = output::json_object(&answer).ok_or("qwen did not answer with JSON")?;
+    let descriptions = value
+        .get("hunks")
+        .and_then(|hunks| hunks.as_array())
+        .ok_or("qwen did not describe the hunks")?;
+    Ok(descriptions
+        .iter()
+        .map(|description| description.as_str().unwrap_or("").lines().next().unwrap_or("").trim().to_string())
+        .collect())
+}
+
+fn ask(question: &str) -> Result<String, String> {
+    print!("{}", question);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).map_err(|e| format!("Failed to read answer: {}", e))? == 0 {
+        return Ok("q".to_string());
+    }
+    Ok(answer.trim().to_lowercase())
+}
+
+/// Rebuilds the index as `base` plus the chosen hunks. Files without hunks,
+/// such as binary files, keep their staged state. The previous index is
+/// printed so the choice can be undone.
+fn apply(files: &[FilePatch], numbered: &[(usize, usize)], chosen: &[bool], base: &str) -> Result<(), String> {
+    let staged_tree = git::output(&["write-tree"])?;
+    let staged_tree = staged_tree.trim();
+
+    let mut patch = String::new();
+    for (file_index, file) in files.iter().enumerate() {
+        let mut hunks = numbered
+            .iter()
+            .zip(chosen)
+            .filter(|((file, _), chosen)| *file == file_index && **chosen)
+            .map(|((_, hunk), _)| &file.hunks[*hunk])
+            .peekable();
+        if hunks.peek().is_some() {
+            patch.push_str(&file.header);
+            hunks.for_each(|hunk| patch.push_str(&hunk.text));
+        }
+    }
+
+    let result = git::output(&["read-tree", base]).and_then(|_| {
+        if patch.is_empty() {
+            Ok(String::new())
+        } else {
+            git::output_with_input(&["apply", "--cached", "--unidiff-zero", "-"], &patch)
+        }
+    });
+    if let Err(e) = result {
+        git::output(&["read-tree", staged_tree])?;
+        return Err(format!("Failed to stage the chosen hunks: {}", e));
+    }
+
+    let unchanged: Vec<&str> = files
+        .iter()
+        .filter(|file| file.hunks.is_empty())
+        .map(|file| file.path.as_str())
+        .collect();
+    if !unchanged.is_empty() {
+        git::output(&[&["reset", "-q", staged_tree, "--"][..], &unchanged].concat())?;
+    }
+
+    eprintln!("Staged the chosen hunks; to restore the previous index, run: git read-tree {}", staged_tree);
+    Ok(())
+}
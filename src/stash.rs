@@ -0,0 +1,81 @@
+//! `git-qwen stash [ARGS]...`: `git stash push` with a message describing
+//! the work in progress, instead of "WIP on main".
+
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use crate::output;
+use crate::prompt;
+use crate::subject;
+use std::process::Command;
+
+/// Longest stash message, so `git stash list` stays on one line.
+const MAX_MESSAGE_LENGTH: usize = 72;
+
+const STASH_PROMPT: &str = "Describe the unfinished work in the changes below in one line of under 60 characters, for the list of stashed changes, so it can be told apart from other stashes later. Say what the work is about, such as \"Half-done retry backoff for HTTP backend\"; don't start with WIP. Answer with only that line.
+
+";
+
+/// Stashes the changes with `git stash push -m <message> ARGS...`. The
+/// message describes what would be stashed: the staged changes with
+/// `--staged`, otherwise all changes to tracked files, plus the names of
+/// untracked files with `--include-untracked` or `--all`.
+pub fn run(args: &[String]) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+
+    let has = |short: &str, long: &str| args.iter().any(|arg| arg == short || arg == long);
+    if args.iter().any(|arg| arg.starts_with("-m") || arg.starts_with("--message")) {
+        return push(args, None);
+    }
+
+    // Paths after `--` limit what is stashed
+    let pathspecs: Vec<&str> = match args.iter().position(|arg| arg == "--") {
+        Some(index) => args[index..].iter().map(String::as_str).collect(),
+        None => Vec::new(),
+    };
+    let diff_args: &[&str] = if has("-S", "--staged") { &["diff", "--cached"] } else { &["diff", "HEAD"] };
+    let mut diff = git::output(&[diff_args, &pathspecs].concat())?;
+    if has("-u", "--include-untracked") || has("-a", "--all") {
+        let untracked = git::output(&[&["ls-files", "--others", "--exclude-standard"][..], &pathspecs].concat())?;
+        for path in untracked.lines() {
+            diff.push_str(&format!("Untracked file: {}\n", path));
+        }
+    }
+    if diff.trim().is_empty() {
+        return Err("No local changes to stash".to_string());
+    }
+
+    let mut prompt = format!("{}{}", prompt::DATA_NOTICE.trim_start(), STASH_PROMPT);
+    let diff = prompt::prepare_diff(&diff, &config);
+    prompt.push_str(&prompt::fenced_fit(&diff, prompt.len(), &config));
+
+    let message = match backend::generate(&prompt) {
+        Ok(answer) => {
+            let text = output::Format::Text.parse(&answer);
+            let line = text.lines().next().unwrap_or("").trim().trim_end_matches('.').to_string();
+            Some(subject::truncate(&line, MAX_MESSAGE_LENGTH)).filter(|line| !line.is_empty())
+        }
+        Err(e) => {
+            eprintln!("Warning: {}; stashing with git's default message", e);
+            None
+        }
+    };
+    push(args, message.as_deref())
+}
+
+fn push(args: &[String], message: Option<&str>) -> Result<(), String> {
+    let mut command = Command::new("git");
+    command.args(["stash", "push"]);
+    if let Some(message) = message {
+        command.args(["--message", message]);
+    }
+    let status = command
+        .args(args)
+        .status()
+        .map_err(|e| format!("Failed to execute git stash: {}", e))?;
+    if !status.success() {
+        return Err("git stash push failed".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//! What the repository is in the middle of, checked before committing so a
+//! bare repository, unresolved conflicts, or a stopped rebase get an error
+//! that says what to do rather than a puzzling one later.
+
+use crate::error::Error;
+use crate::git;
+
+/// A multi-step operation git has stopped in the middle of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Rebase,
+    Am,
+    Merge,
+    CherryPick,
+    Revert,
+    Bisect,
+}
+
+impl Operation {
+    /// The operation in progress, if any, from the files git keeps for it
+    /// in the git directory.
+    pub fn in_progress() -> Option<Operation> {
+        let exists = |name: &str| git::path(name).is_ok_and(|path| path.exists());
+        if exists("rebase-merge") {
+            Some(Operation::Rebase)
+        } else if exists("rebase-apply") {
+            // `git am` uses the same directory as the old rebase backend
+            Some(if exists("rebase-apply/applying") { Operation::Am } else { Operation::Rebase })
+        } else if exists("MERGE_HEAD") {
+            Some(Operation::Merge)
+        } else if exists("CHERRY_PICK_HEAD") {
+            Some(Operation::CherryPick)
+        } else if exists("REVERT_HEAD") {
+            Some(Operation::Revert)
+        } else if exists("BISECT_LOG") {
+            Some(Operation::Bisect)
+        } else {
+            None
+        }
+    }
+
+    /// What to do when there is nothing to commit in the middle of the
+    /// operation.
+    pub fn nothing_to_commit_hint(self) -> String {
+        match self {
+            Operation::Rebase => continue_or_skip("rebase"),
+            Operation::Am => continue_or_skip("am"),
+            Operation::CherryPick => continue_or_skip("cherry-pick"),
+            Operation::Revert => continue_or_skip("revert"),
+            Operation::Merge => "A merge is in progress and nothing is staged.\n\
+                Stage the resolved files with 'git add', or abandon the merge with 'git merge --abort'."
+                .to_string(),
+            Operation::Bisect => "A bisect is in progress and nothing is staged.\n\
+                Use 'git add' to stage changes, or end the bisect with 'git bisect reset'."
+                .to_string(),
+        }
+    }
+}
+
+fn continue_or_skip(command: &str) -> String {
+    format!(
+        "A {0} is in progress and nothing is staged.\n\
+            If this step is done, go on with 'git {0} --continue', or drop it with 'git {0} --skip'.",
+        command
+    )
+}
+
+/// Fails if a commit cannot be made here at all: in a bare repository, or
+/// while files still have unresolved conflicts.
+pub fn check_can_commit() -> Result<(), Error> {
+    if git::output(&["rev-parse", "--is-bare-repository"]).is_ok_and(|bare| bare.trim() == "true") {
+        return Err(Error::Other(
+            "This is a bare repository, which has no work tree to commit from.\n\
+                Run git-qwen in a clone or a worktree of it."
+                .to_string(),
+        ));
+    }
+
+    let unmerged = git::output(&["diff", "--name-only", "--diff-filter=U"]).unwrap_or_default();
+    if !unmerged.trim().is_empty() {
+        let files: Vec<&str> = unmerged.lines().map(|file| file.trim()).collect();
+        return Err(Error::Other(format!(
+            "These files have unresolved conflicts:\n  {}\n\
+                Resolve them and mark them resolved with 'git add', then run git-qwen again.",
+            files.join("\n  ")
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,180 @@
+//! The repository's commit style, learned from its history by
+//! `git-qwen learn` and stored in `.git/qwen-style.json`, so every later
+//! prompt can describe it.
+
+use crate::backend;
+use crate::config::Config;
+use crate::emoji;
+use crate::git;
+use crate::output;
+use crate::prompt;
+use regex::Regex;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::PathBuf;
+
+/// Name of the profile file in the git directory.
+const PROFILE_FILE: &str = "qwen-style.json";
+
+/// Default number of recent commits analyzed.
+const DEFAULT_DEPTH: usize = 200;
+
+/// Messages shown to the model when it describes the style.
+const SAMPLE_SIZE: usize = 40;
+
+/// Matches a Conventional Commits type, e.g. `feat:` or `fix(parser)!:`.
+const CONVENTIONAL_PATTERN: &str = r"^([a-z]+)(\([^)]*\))?!?: ";
+
+const LEARN_PROMPT: &str = "Describe the conventions of the commit messages below, from this repository's history, so that new messages can follow them. Cover the language they are written in, their tone and tense, how subjects are phrased and prefixed, when and how bodies are written, and how tickets, trailers, or other references appear. Answer with at most five short sentences and nothing else.
+
+";
+
+/// Share of the messages a habit needs before the prompt calls it a
+/// convention.
+const MAJORITY: f64 = 0.6;
+
+/// Analyzes the last `qwen.learnDepth` messages and writes the profile,
+/// then prints the guidance later prompts will include.
+pub fn learn() -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+    let depth = config.get_usize("learnDepth").unwrap_or(DEFAULT_DEPTH);
+
+    let log = git::output(&["log", "--no-merges", &format!("--max-count={}", depth), "--format=%B%x00"])?;
+    let messages: Vec<&str> = log.split('\0').map(str::trim).filter(|message| !message.is_empty()).collect();
+    if messages.len() < 5 {
+        return Err("Too few commits to learn a style from".to_string());
+    }
+    eprintln!("Learning the commit style of {} commit(s)...", messages.len());
+
+    let mut profile = statistics(&messages);
+
+    let sample: Vec<String> = messages.iter().take(SAMPLE_SIZE).map(|message| message.to_string()).collect();
+    let prompt = format!(
+        "{}{}{}",
+        prompt::DATA_NOTICE.trim_start(),
+        LEARN_PROMPT,
+        prompt::fence(&prompt::separated(&sample))
+    );
+    match backend::generate(&prompt) {
+        Ok(answer) => profile["notes"] = json!(output::clean_document(&answer)),
+        Err(e) => eprintln!("Warning: {}; saving the statistics only", e),
+    }
+
+    let path = profile_path()?;
+    let text = serde_json::to_string_pretty(&profile).map_err(|e| format!("Failed to encode style profile: {}", e))?;
+    fs::write(&path, format!("{}\n", text)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    println!("{}", guidance(&profile).join("\n"));
+    eprintln!("Saved to {}; delete it to stop using the profile.", path.display());
+    Ok(())
+}
+
+/// The prompt section describing the learned style, if there is a profile.
+pub fn prompt_section() -> Option<String> {
+    let text = fs::read_to_string(profile_path().ok()?).ok()?;
+    let profile: Value = serde_json::from_str(&text).ok()?;
+    let lines = guidance(&profile);
+    if lines.is_empty() {
+        return None;
+    }
+
+    let mut section = "\nThis repository's commit style, learned from its history. Follow it where it does not conflict with the rules above:\n".to_string();
+    for line in lines {
+        section.push_str(&format!("- {}\n", line));
+    }
+    Some(section)
+}
+
+fn profile_path() -> Result<PathBuf, String> {
+    git::path(PROFILE_FILE)
+}
+
+/// Measures the habits of `messages`, newest first.
+fn statistics(messages: &[&str]) -> Value {
+    let conventional = Regex::new(CONVENTIONAL_PATTERN).expect("built-in conventional pattern is valid");
+    let subjects: Vec<&str> = messages.iter().map(|message| message.lines().next().unwrap_or("")).collect();
+    let share = |count: usize| count as f64 / messages.len() as f64;
+
+    let mut lengths: Vec<usize> = subjects.iter().map(|subject| subject.chars().count()).collect();
+    lengths.sort_unstable();
+    let average = lengths.iter().sum::<usize>() / lengths.len();
+    let p90 = lengths[(lengths.len() * 9 / 10).min(lengths.len() - 1)];
+
+    // Conventional types, most used first
+    let mut types: Vec<(String, usize)> = Vec::new();
+    for subject in &subjects {
+        if let Some(captures) = conventional.captures(subject) {
+            let kind = captures[1].to_string();
+            match types.iter_mut().find(|(name, _)| *name == kind) {
+                Some((_, count)) => *count += 1,
+                None => types.push((kind, 1)),
+            }
+        }
+    }
+    types.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let conventional_count = types.iter().map(|(_, count)| count).sum();
+
+    let emoji_count = subjects.iter().filter(|subject| emoji::split(subject).0.is_some()).count();
+    let capitalized_count = subjects
+        .iter()
+        .filter(|subject| {
+            let text = conventional.replace(emoji::split(subject).1, "");
+            text.chars().next().is_some_and(char::is_uppercase)
+        })
+        .count();
+    let body_count = messages.iter().filter(|message| message.lines().count() > 2).count();
+
+    json!({
+        "commits": messages.len(),
+        "subjectLength": { "average": average, "p90": p90 },
+        "conventionalShare": share(conventional_count),
+        "types": types.iter().take(6).map(|(name, _)| name.as_str()).collect::<Vec<_>>(),
+        "emojiShare": share(emoji_count),
+        "capitalizedShare": share(capitalized_count),
+        "bodyShare": share(body_count),
+    })
+}
+
+/// The profile as plain sentences for the prompt.
+fn guidance(profile: &Value) -> Vec<String> {
+    let number = |key: &str| profile.get(key).and_then(Value::as_f64);
+    let mut lines = Vec::new();
+
+    if let (Some(average), Some(p90)) = (
+        profile.pointer("/subjectLength/average").and_then(Value::as_u64),
+        profile.pointer("/subjectLength/p90").and_then(Value::as_u64),
+    ) {
+        lines.push(format!("Subjects are {} characters long on average, and nine in ten are at most {}.", average, p90));
+    }
+    if number("conventionalShare").is_some_and(|share| share >= MAJORITY) {
+        let types: Vec<&str> = profile
+            .get("types")
+            .and_then(Value::as_array)
+            .map(|types| types.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        lines.push(format!(
+            "Subjects start with a Conventional Commits type, most often {}.",
+            types.join(", ")
+        ));
+    }
+    if number("emojiShare").is_some_and(|share| share >= MAJORITY) {
+        lines.push("Subjects start with an emoji.".to_string());
+    }
+    match number("capitalizedShare") {
+        Some(share) if share >= MAJORITY => lines.push("The first word of the summary is capitalized.".to_string()),
+        Some(share) if share <= 1.0 - MAJORITY => lines.push("The first word of the summary is lowercase.".to_string()),
+        _ => {}
+    }
+    match number("bodyShare") {
+        Some(share) if share >= MAJORITY => lines.push("Most commits have a body explaining the change.".to_string()),
+        Some(share) if share <= 1.0 - MAJORITY => {
+            lines.push("Most commits have only a subject; write a body only when the change needs explaining.".to_string())
+        }
+        _ => {}
+    }
+    if let Some(notes) = profile.get("notes").and_then(Value::as_str).filter(|notes| !notes.trim().is_empty()) {
+        lines.push(notes.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    lines
+}
@@ -0,0 +1,106 @@
+use crate::config::Config;
+use crate::emoji;
+use crate::lint;
+
+/// Normalizes the subject of `message` unless `qwen.normalizeSubject` is
+/// false, starts it with a gitmoji as `qwen.emoji` asks, then shortens it to
+/// `qwen.truncateSubject` characters if that is set. The rest of the
+/// message is left alone.
+pub fn apply(message: &str, config: &Config) -> String {
+    let (subject, rest) = match message.split_once('\n') {
+        Some((subject, rest)) => (subject, Some(rest)),
+        None => (message, None),
+    };
+
+    let (given_emoji, subject) = emoji::split(subject);
+    let mut subject = subject.to_string();
+    if config.get_bool("normalizeSubject").unwrap_or(true) {
+        subject = lint::normalize_subject(&subject);
+    }
+
+    let emoji = match emoji::Mode::from_config(config) {
+        emoji::Mode::Off => given_emoji,
+        emoji::Mode::Map => Some(emoji::for_subject(&subject)),
+        emoji::Mode::Model => Some(given_emoji.unwrap_or_else(|| emoji::for_subject(&subject))),
+    };
+    if let Some(emoji) = emoji {
+        subject = format!("{} {}", emoji, subject);
+    }
+    let limit = config.get_usize("truncateSubject").unwrap_or(0);
+    if limit > 0 {
+        subject = truncate(&subject, limit);
+    }
+
+    match rest {
+        Some(rest) => format!("{}\n{}", subject, rest),
+        None => subject,
+    }
+}
+
+/// Cuts `subject` to at most `limit` characters, counting a character with
+/// its combining marks and joined emoji as one. The cut is made at the last
+/// word boundary that fits, or inside the first word if none does, and
+/// never splits a character.
+pub fn truncate(subject: &str, limit: usize) -> String {
+    let subject = subject.trim_end();
+    if limit == 0 {
+        return String::new();
+    }
+    let clusters = clusters(subject);
+    if clusters.len() <= limit {
+        return subject.to_string();
+    }
+
+    // Byte offset just past the last cluster that fits
+    let end = clusters[limit - 1].1;
+    let fits = &subject[..end];
+    let next_is_space = subject[end..].starts_with(char::is_whitespace);
+
+    let cut = if next_is_space {
+        fits
+    } else {
+        match fits.rfind(char::is_whitespace) {
+            Some(space) if !fits[..space].trim().is_empty() => &fits[..space],
+            _ => fits,
+        }
+    };
+
+    cut.trim_end()
+        .trim_end_matches([',', ';', ':', '-'])
+        .trim_end()
+        .to_string()
+}
+
+/// Splits `text` into user-perceived characters, as `(start, end)` byte
+/// ranges. This approximates grapheme clusters: combining marks, variation
+/// selectors, emoji modifiers, and anything after a zero-width joiner stay
+/// with the character before them.
+pub fn clusters(text: &str) -> Vec<(usize, usize)> {
+    let mut clusters: Vec<(usize, usize)> = Vec::new();
+    let mut joined = false;
+
+    for (start, c) in text.char_indices() {
+        let end = start + c.len_utf8();
+        match clusters.last_mut() {
+            Some(last) if joined || is_extending(c) => last.1 = end,
+            _ => clusters.push((start, end)),
+        }
+        joined = c == '\u{200D}';
+    }
+
+    clusters
+}
+
+fn is_extending(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{1F3FB}'..='\u{1F3FF}'
+        | '\u{E0020}'..='\u{E007F}'
+    )
+}
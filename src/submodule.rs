@@ -0,0 +1,78 @@
+//! Notes for submodule updates, whose diff is only the old and new commit
+//! IDs. The note lists the submodule's commits in between, so the message
+//! can say what the update brings.
+
+use crate::diff;
+use crate::git;
+use std::path::{Path, PathBuf};
+
+/// Commits listed per submodule; the rest are counted.
+const MAX_COMMITS: usize = 20;
+
+/// Adds the submodule's log between the old and new commits to each
+/// submodule section of `diff` whose submodule is checked out.
+pub fn summarize(diff: &str) -> String {
+    let files = diff::split_files(diff);
+    if !files.iter().any(|file| file.text.contains("Subproject commit ")) {
+        return diff.to_string();
+    }
+    let Ok(toplevel) = git::output(&["rev-parse", "--show-toplevel"]) else {
+        return diff.to_string();
+    };
+    let toplevel = PathBuf::from(toplevel.trim());
+
+    files
+        .iter()
+        .map(|file| {
+            let commit = |side: char| {
+                file.text.lines().find_map(|line| {
+                    line.strip_prefix(side)?.strip_prefix("Subproject commit ").map(|id| id.trim_end_matches("-dirty"))
+                })
+            };
+            match (commit('-'), commit('+')) {
+                (Some(old), Some(new)) => {
+                    let path = toplevel.join(&file.path);
+                    match log_note(&path, &file.path, old, new) {
+                        Some(note) => format!("{}{}", file.text, note),
+                        None => file.text.to_string(),
+                    }
+                }
+                _ => file.text.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Lists the commits that moving the submodule at `path` from `old` to
+/// `new` brings in, or takes out when it goes back.
+fn log_note(path: &Path, name: &str, old: &str, new: &str) -> Option<String> {
+    let log = |range: &str| -> Option<Vec<String>> {
+        let path = path.to_str()?;
+        let log = git::output(&["-C", path, "log", "--oneline", "--no-decorate", range]).ok()?;
+        Some(log.lines().map(String::from).collect())
+    };
+
+    let short = |id: &str| id.chars().take(7).collect::<String>();
+    let (commits, direction) = match log(&format!("{}..{}", old, new))? {
+        commits if !commits.is_empty() => (commits, "bringing in"),
+        _ => (log(&format!("{}..{}", new, old))?, "going back on"),
+    };
+    if commits.is_empty() {
+        return None;
+    }
+
+    let mut note = format!(
+        "Submodule {} moves from {} to {}, {} these commits:\n",
+        name,
+        short(old),
+        short(new),
+        direction
+    );
+    for commit in commits.iter().take(MAX_COMMITS) {
+        note.push_str(&format!("  {}\n", commit));
+    }
+    if commits.len() > MAX_COMMITS {
+        note.push_str(&format!("  and {} more\n", commits.len() - MAX_COMMITS));
+    }
+    Some(note)
+}
@@ -0,0 +1,53 @@
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use std::io::{self, Read};
+
+/// Where the diff to summarize comes from.
+pub enum DiffInput {
+    /// The changes staged in the index
+    Staged,
+    /// A diff read from standard input
+    Stdin,
+    /// The arguments for `git diff`: one ref, two refs, or a range
+    Refs(Vec<String>),
+}
+
+pub fn run(input: DiffInput) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let diff = read_diff(input)?;
+    if diff.trim().is_empty() {
+        return Err("No differences to summarize.".to_string());
+    }
+
+    let summary = summarize(&diff, &Config::load())?;
+    println!("{}", summary);
+    Ok(())
+}
+
+/// Asks the model for a natural-language summary of `diff`, grouped by
+/// area and ending with risk notes.
+pub fn summarize(diff: &str, config: &Config) -> Result<String, String> {
+    let prompt = crate::prompt::build_summary(diff, config);
+    let summary = backend::generate(&prompt)
+        .map_err(|e| format!("Failed to generate summary: {}", e))?;
+    Ok(summary.trim().to_string())
+}
+
+fn read_diff(input: DiffInput) -> Result<String, String> {
+    match input {
+        DiffInput::Staged => git::output(&["diff", "--cached"]),
+        DiffInput::Stdin => {
+            let mut diff = String::new();
+            io::stdin()
+                .read_to_string(&mut diff)
+                .map_err(|e| format!("Failed to read diff from stdin: {}", e))?;
+            Ok(diff)
+        }
+        DiffInput::Refs(refs) => {
+            let mut args = vec!["diff"];
+            args.extend(refs.iter().map(String::as_str));
+            git::output(&args)
+        }
+    }
+}
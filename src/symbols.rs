@@ -0,0 +1,281 @@
+//! The functions, types, and modules a change touches, found by matching
+//! definition lines in the changed files, so the model can name them:
+//! "Refactor retry logic in HttpClient::send" rather than "Update http.rs".
+//!
+//! A definition encloses a line when it is the nearest one above it with
+//! less indentation, which holds for conventionally formatted code in the
+//! languages below without parsing it.
+
+use crate::config::Config;
+use crate::diff::{self, FileDiff};
+use crate::git;
+use crate::prompt;
+use regex::Regex;
+use std::fs;
+use std::path::PathBuf;
+
+/// Files looked at; the rest of a huge change goes without symbols.
+const MAX_FILES: usize = 50;
+
+/// Symbols listed per file.
+const MAX_SYMBOLS: usize = 10;
+
+/// Files larger than this are not read.
+const MAX_FILE_BYTES: usize = 1024 * 1024;
+
+/// Words that look like a function name to the method patterns but are
+/// control flow.
+const KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "match", "else", "do", "try", "using", "lock", "foreach", "new",
+    "sizeof", "typeof",
+];
+
+/// A language: the extensions of its files, the separator of qualified
+/// names, and patterns for its definition lines, each capturing `name`.
+struct Language {
+    extensions: &'static [&'static str],
+    separator: &'static str,
+    patterns: &'static [&'static str],
+}
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        extensions: &["rs"],
+        separator: "::",
+        patterns: &[
+            r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe|extern\s+"[^"]*")\s+)*(?:fn|struct|enum|trait|mod|union)\s+(?P<name>[A-Za-z_]\w*)"#,
+            r"^\s*(?:unsafe\s+)?impl\b(?:\s*<[^{]*?>)?\s+(?:[^{]*?\s+for\s+)?(?P<name>[A-Za-z_][\w:]*)",
+            r"^\s*macro_rules!\s*(?P<name>[A-Za-z_]\w*)",
+        ],
+    },
+    Language {
+        extensions: &["py", "pyi"],
+        separator: ".",
+        patterns: &[r"^\s*(?:async\s+)?(?:def|class)\s+(?P<name>\w+)"],
+    },
+    Language {
+        extensions: &["go"],
+        separator: ".",
+        patterns: &[
+            r"^func\s+\(\s*\w*\s*\*?(?P<receiver>\w+)[^)]*\)\s*(?P<name>\w+)",
+            r"^func\s+(?P<name>\w+)",
+            r"^type\s+(?P<name>\w+)",
+        ],
+    },
+    Language {
+        extensions: &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"],
+        separator: ".",
+        patterns: &[
+            r"^\s*(?:export\s+)?(?:default\s+)?(?:async\s+)?function\*?\s+(?P<name>\w+)",
+            r"^\s*(?:export\s+)?(?:default\s+)?(?:abstract\s+)?(?:class|interface|enum)\s+(?P<name>\w+)",
+            r"^\s*(?:export\s+)?(?:const|let|var)\s+(?P<name>\w+)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:function\b|\([^)]*\)\s*(?::[^=]+)?=>|\w+\s*=>)",
+            r"^\s+(?:(?:public|private|protected|static|async|readonly|override|get|set)\s+)*(?P<name>\w+)\s*(?:<[^>]*>)?\([^;]*\)\s*(?::[^{;]+)?\{\s*$",
+        ],
+    },
+    Language {
+        extensions: &["java", "kt", "kts", "cs", "scala", "swift"],
+        separator: ".",
+        patterns: &[
+            r"^\s*(?:[@\w]+(?:\([^)]*\))?\s+)*(?:class|interface|enum|record|object|struct|protocol|extension|trait)\s+(?P<name>\w+)",
+            r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|override|virtual|async|synchronized|suspend|open|inline)\s+)*(?:fun|func|def)\s+(?:<[^>]*>\s*)?(?:\w+\.)?(?P<name>\w+)",
+            r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|override|virtual|async|synchronized|native)\s+)+[\w<>\[\],.?]+(?:\s+[\w<>\[\],.?]+)*\s+(?P<name>\w+)\s*\([^;]*$",
+        ],
+    },
+    Language {
+        extensions: &["c", "h", "cc", "cpp", "cxx", "hh", "hpp", "hxx"],
+        separator: "::",
+        patterns: &[
+            r"^\s*(?:template\s*<[^>]*>\s*)?(?:class|struct|namespace|union|enum(?:\s+class)?)\s+(?P<name>\w+)[^;]*$",
+            r"^(?:[\w:*&<>,]+\s+)+[*&]*(?P<name>[\w:~]+)\s*\([^;]*$",
+        ],
+    },
+    Language {
+        extensions: &["rb"],
+        separator: ".",
+        patterns: &[r"^\s*(?:def|class|module)\s+(?:self\.)?(?P<name>[\w?!]+)"],
+    },
+    Language {
+        extensions: &["php"],
+        separator: "::",
+        patterns: &[
+            r"^\s*(?:(?:public|private|protected|static|abstract|final)\s+)*function\s+(?P<name>\w+)",
+            r"^\s*(?:abstract\s+|final\s+)?(?:class|interface|trait|enum)\s+(?P<name>\w+)",
+        ],
+    },
+];
+
+/// Definition matching for one language.
+pub struct Definitions {
+    patterns: Vec<Regex>,
+    separator: &'static str,
+}
+
+impl Definitions {
+    /// The definitions of the language of `path`, if it is one of those
+    /// known.
+    pub fn for_path(path: &str) -> Option<Self> {
+        let extension = path.rsplit_once('.')?.1.to_lowercase();
+        let language = LANGUAGES.iter().find(|language| language.extensions.contains(&extension.as_str()))?;
+        Some(Definitions {
+            patterns: language
+                .patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern).expect("built-in definition pattern is valid"))
+                .collect(),
+            separator: language.separator,
+        })
+    }
+
+    /// The name `line` defines, if it is a definition.
+    pub fn name(&self, line: &str) -> Option<String> {
+        self.patterns.iter().find_map(|pattern| {
+            let captures = pattern.captures(line)?;
+            let name = captures.name("name")?.as_str();
+            if KEYWORDS.contains(&name) {
+                return None;
+            }
+            Some(match captures.name("receiver") {
+                Some(receiver) => format!("{}{}{}", receiver.as_str(), self.separator, name),
+                None => name.to_string(),
+            })
+        })
+    }
+
+    /// The qualified name of what encloses `own`, a line that would sit
+    /// just below `lines[..end]`, such as `HttpClient::send`; `own` counts
+    /// when it is a definition itself.
+    pub fn enclosing(&self, lines: &[&str], end: usize, own: &str) -> Option<String> {
+        let mut chain: Vec<String> = self.name(own).into_iter().collect();
+        let mut limit = indentation(own);
+        for line in lines[..end.min(lines.len())].iter().rev() {
+            if limit == 0 {
+                break;
+            }
+            if line.trim().is_empty() || indentation(line) >= limit {
+                continue;
+            }
+            if let Some(name) = self.name(line) {
+                chain.push(name);
+                limit = indentation(line);
+            }
+        }
+        if chain.is_empty() {
+            return None;
+        }
+        chain.reverse();
+        Some(chain.join(self.separator))
+    }
+
+    /// The index of the innermost definition enclosing `lines[index]`, or
+    /// `index` itself when it is a definition.
+    pub fn enclosing_start(&self, lines: &[&str], index: usize) -> Option<usize> {
+        let own = lines.get(index)?;
+        if self.name(own).is_some() {
+            return Some(index);
+        }
+        let limit = indentation(own);
+        (0..index)
+            .rev()
+            .find(|&i| !lines[i].trim().is_empty() && indentation(lines[i]) < limit && self.name(lines[i]).is_some())
+    }
+}
+
+/// Leading whitespace width, a tab counting as four spaces.
+pub fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Lists the symbols each changed file's hunks fall in, unless
+/// `qwen.symbols` is false.
+pub fn prompt_section(diff: &str, config: &Config) -> Option<String> {
+    if !config.get_bool("symbols").unwrap_or(true) {
+        return None;
+    }
+
+    let mut section = String::new();
+    for file in diff::split_files(diff).iter().take(MAX_FILES) {
+        let symbols = changed_symbols(file);
+        if !symbols.is_empty() {
+            section.push_str(&format!("- {}: {}\n", file.path, symbols.join(", ")));
+        }
+    }
+    if section.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "\nThe changes are in these functions, types, and modules; name them where it helps:\n{}",
+        prompt::fence(&section)
+    ))
+}
+
+/// The symbols enclosing the changed lines of `file`, in order.
+fn changed_symbols(file: &FileDiff) -> Vec<String> {
+    let Some(definitions) = Definitions::for_path(&file.path) else {
+        return Vec::new();
+    };
+    let Some(content) = new_content(file) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut symbols: Vec<String> = Vec::new();
+    let mut add = |symbol: Option<String>| {
+        if let Some(symbol) = symbol
+            && !symbols.contains(&symbol)
+        {
+            symbols.push(symbol);
+        }
+    };
+    let mut new_line = 0;
+    for line in file.text.lines().skip_while(|line| !line.starts_with("@@")) {
+        if line.starts_with("@@") {
+            new_line = hunk_new_start(line).unwrap_or(1).saturating_sub(1);
+            continue;
+        }
+        match line.chars().next() {
+            Some('+') => {
+                if !line[1..].trim().is_empty() {
+                    add(definitions.enclosing(&lines, new_line, &line[1..]));
+                }
+                new_line += 1;
+            }
+            // A removed line sat where the next line of the new file is
+            Some('-') if !line[1..].trim().is_empty() => {
+                add(definitions.enclosing(&lines, new_line, &line[1..]));
+            }
+            Some(' ') => new_line += 1,
+            _ => {}
+        }
+    }
+    symbols.truncate(MAX_SYMBOLS);
+    symbols
+}
+
+/// The first line of the new side of a hunk, from its `@@ -a,b +c,d @@`
+/// header.
+pub fn hunk_new_start(header: &str) -> Option<usize> {
+    let new = header.split_whitespace().find(|part| part.starts_with('+'))?;
+    new[1..].split(',').next()?.parse().ok()
+}
+
+/// The file as changed: its new blob, or the work tree copy when the blob
+/// is not in the object database, as for unstaged changes.
+pub fn new_content(file: &FileDiff) -> Option<String> {
+    let ids = file.text.lines().find_map(|line| line.strip_prefix("index "))?;
+    let new = ids.split(' ').next()?.split_once("..")?.1;
+    if new.bytes().all(|b| b == b'0') {
+        return None;
+    }
+
+    let content = match git::output(&["cat-file", "blob", new]) {
+        Ok(content) => content,
+        Err(_) => {
+            let toplevel = git::output(&["rev-parse", "--show-toplevel"]).ok()?;
+            fs::read_to_string(PathBuf::from(toplevel.trim()).join(&file.path)).ok()?
+        }
+    };
+    (content.len() <= MAX_FILE_BYTES).then_some(content)
+}
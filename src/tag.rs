@@ -0,0 +1,80 @@
+//! `git-qwen tag <NAME> [RANGE]`: creates an annotated tag whose message
+//! summarizes the commits since the previous tag.
+
+use crate::backend;
+use crate::config::Config;
+use crate::git;
+use crate::history;
+use crate::output;
+use crate::prompt;
+use std::fs;
+use std::process::Command;
+
+const TAG_PROMPT: &str = "Write the annotation message for the git tag below, which marks a release made of the commits listed after it. Start with a one-line summary of the release, followed by a blank line. Then describe the notable changes in a few short paragraphs, grouped by theme, in plain language for someone deciding whether to upgrade. Leave out trivial changes such as typo fixes, and don't list commit names. Answer with only the message text.
+
+";
+
+/// Tags the end of `range` (`A..B`, or `A` for `A..HEAD`; by default the
+/// commits since the latest tag) as `name`, with a generated message the
+/// user reviews in the editor. With `sign`, the tag is signed.
+pub fn run(name: &str, range: Option<&str>, sign: bool) -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+
+    if git::output(&["rev-parse", "--verify", "--quiet", &format!("refs/tags/{}", name)]).is_ok() {
+        return Err(format!("Tag {} already exists", name));
+    }
+
+    let (base, end) = match range {
+        Some(range) => {
+            let (base, end) = history::split_range(range);
+            (Some(base.to_string()), end)
+        }
+        None => (history::latest_tag("HEAD"), "HEAD"),
+    };
+    let log_range = match &base {
+        Some(base) => format!("{}..{}", base, end),
+        None => end.to_string(),
+    };
+
+    let commits = history::commits(&log_range)?;
+    if commits.is_empty() {
+        return Err(format!("No commits in {} to describe", log_range));
+    }
+    eprintln!("Summarizing {} commit(s) in {}...", commits.len(), log_range);
+
+    let mut prompt = format!("{}{}Tag: {}\n", prompt::DATA_NOTICE.trim_start(), TAG_PROMPT, name);
+    if let Some(base) = &base {
+        prompt.push_str(&format!("Previous release: {}\n", base));
+    }
+    prompt.push_str("\nCommits, oldest first:\n");
+    let budget = prompt::max_chars(&config).map(|max| max.saturating_sub(prompt.len() + prompt::FENCE_CHARS));
+    prompt.push_str(&prompt::fence(&history::prompt_listing(&commits, budget)));
+
+    let answer = backend::generate(&prompt).map_err(|e| format!("Failed to generate tag message: {}", e))?;
+    let message = output::Format::Text.parse(&answer);
+    if message.is_empty() {
+        return Err("qwen returned an empty tag message".to_string());
+    }
+
+    // git opens the editor on the message and strips its comments
+    let message_file = git::path("QWEN_TAG_EDITMSG")?;
+    fs::write(&message_file, format!("{}\n", message))
+        .map_err(|e| format!("Failed to write tag message file: {}", e))?;
+
+    let mut command = Command::new("git");
+    command.args(["tag", "--annotate", "--edit", "--file"]).arg(&message_file);
+    if sign {
+        command.arg("--sign");
+    }
+    let status = command
+        .args([name, end])
+        .status()
+        .map_err(|e| format!("Failed to execute git tag: {}", e));
+    let _ = fs::remove_file(&message_file);
+
+    if !status?.success() {
+        return Err("git tag failed; no tag was created".to_string());
+    }
+    Ok(())
+}
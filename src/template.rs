@@ -0,0 +1,27 @@
+use crate::git;
+use std::fs;
+
+/// Reads the commit template given with `-t`/`--template` (`option`), or
+/// else the one configured in `commit.template`. Templates that cannot be
+/// read are skipped with a warning, since they only guide the message.
+pub fn load(option: Option<&str>) -> Option<String> {
+    let path = match option {
+        Some(path) => path.to_string(),
+        None => git::output(&["config", "--path", "--get", "commit.template"])
+            .ok()?
+            .trim()
+            .to_string(),
+    };
+    if path.is_empty() {
+        return None;
+    }
+
+    match fs::read_to_string(&path) {
+        Ok(content) if !content.trim().is_empty() => Some(content.trim_end().to_string()),
+        Ok(_) => None,
+        Err(e) => {
+            eprintln!("Warning: could not read commit template {}: {}", path, e);
+            None
+        }
+    }
+}
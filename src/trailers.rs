@@ -0,0 +1,224 @@
+//! Footers added below the generated message. They are kept out of the
+//! model's output, so wrapping and formatting never touch them.
+
+use crate::backend;
+use crate::branch;
+use crate::config::Config;
+use crate::git;
+
+const DEFAULT_CLOSE_KEYWORD: &str = "Closes";
+
+/// Key of the trailer Gerrit identifies a change by.
+const CHANGE_ID_KEY: &str = "Change-Id:";
+
+/// Key of the attribution trailer unless `qwen.assistedByKey` says
+/// otherwise.
+const DEFAULT_ASSISTED_BY_KEY: &str = "Assisted-by";
+
+/// Closing footers such as `Closes #42` for the issues given with
+/// `--closes`, plus the branch's ticket when `qwen.closeFromBranch` is set
+/// and the ticket is an issue number. The keyword comes from
+/// `qwen.closeKeyword` (default `Closes`); GitHub and GitLab both close
+/// the issue when the commit reaches the default branch.
+pub fn closing_footers(issues: &[String], config: &Config) -> Vec<String> {
+    let keyword = config.get("closeKeyword").unwrap_or(DEFAULT_CLOSE_KEYWORD);
+
+    let mut references: Vec<String> = issues.iter().map(|issue| issue_reference(issue)).collect();
+
+    if config.get_bool("closeFromBranch").unwrap_or(false)
+        && let Some(ticket) = branch::current().and_then(|name| branch::ticket(&name, config))
+        && ticket.trim_start_matches('#').chars().all(|c| c.is_ascii_digit())
+    {
+        references.push(issue_reference(&ticket));
+    }
+
+    let mut footers: Vec<String> = Vec::new();
+    for reference in references {
+        let footer = format!("{} {}", keyword, reference);
+        if !footers.contains(&footer) {
+            footers.push(footer);
+        }
+    }
+    footers
+}
+
+/// Accepts `42`, `#42`, `owner/repo#42`, or an issue URL; bare numbers get
+/// the `#` the forges expect.
+fn issue_reference(issue: &str) -> String {
+    let issue = issue.trim();
+    let number = issue.trim_start_matches('#');
+    if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+        format!("#{}", number)
+    } else {
+        issue.to_string()
+    }
+}
+
+/// A `Change-Id` trailer for a new change, when `qwen.changeId` is set and
+/// `existing`, the trailers chosen so far, such as those kept from an
+/// amended commit, have none. Like
+/// the one Gerrit's commit-msg hook adds, it is `I` and the hash of the
+/// tree, parent, author, and committer, here with `diff` in place of the
+/// message, which is not written yet. With one in the message, the hook
+/// leaves it alone.
+pub fn change_id(existing: &[String], diff: &str, config: &Config) -> Result<Option<String>, String> {
+    if !config.get_bool("changeId").unwrap_or(false) || existing.iter().any(|line| line.starts_with(CHANGE_ID_KEY)) {
+        return Ok(None);
+    }
+
+    let mut input = format!("tree {}\n", git::output(&["write-tree"])?.trim());
+    if let Ok(parent) = git::output(&["rev-parse", "--verify", "-q", "HEAD^0"]) {
+        input.push_str(&format!("parent {}\n", parent.trim()));
+    }
+    input.push_str(&format!("author {}\n", git::output(&["var", "GIT_AUTHOR_IDENT"])?.trim()));
+    input.push_str(&format!("committer {}\n", git::output(&["var", "GIT_COMMITTER_IDENT"])?.trim()));
+    input.push_str(&format!("\n{}", diff));
+    let hash = git::output_with_input(&["hash-object", "-t", "blob", "--stdin"], &input)?;
+    Ok(Some(format!("{} I{}", CHANGE_ID_KEY, hash.trim())))
+}
+
+/// An `Assisted-by: git-qwen (<model>)` trailer naming the model that
+/// answered `prompt`, when `qwen.assistedBy` is set. `qwen.assistedByKey`
+/// changes the key for projects that ask for another, like `Generated-by`.
+pub fn assisted_by(prompt: &str, config: &Config) -> Option<String> {
+    if !config.get_bool("assistedBy").unwrap_or(false) {
+        return None;
+    }
+    let key = config.get("assistedByKey").map(str::trim).filter(|key| !key.is_empty());
+    Some(format!(
+        "{}: git-qwen ({})",
+        key.unwrap_or(DEFAULT_ASSISTED_BY_KEY),
+        backend::answering_model(prompt)
+    ))
+}
+
+/// Trailers given on the command line: `Co-authored-by` for each
+/// `--co-author "Name <email>"`, and each `--trailer` as `Key: value`.
+pub fn user_trailers(co_authors: &[String], trailers: &[String]) -> Result<Vec<String>, String> {
+    let mut result = Vec::new();
+
+    for co_author in co_authors {
+        let co_author = co_author.trim();
+        if !(co_author.contains('<') && co_author.ends_with('>')) {
+            return Err(format!(
+                "Invalid --co-author '{}': expected \"Name <email>\"",
+                co_author
+            ));
+        }
+        result.push(format!("Co-authored-by: {}", co_author));
+    }
+
+    for trailer in trailers {
+        // git accepts both key=value and key: value
+        let Some((key, value)) = trailer.split_once(['=', ':']) else {
+            return Err(format!("Invalid --trailer '{}': expected key=value", trailer));
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if key.is_empty() || key.contains(char::is_whitespace) || value.is_empty() {
+            return Err(format!("Invalid --trailer '{}': expected key=value", trailer));
+        }
+        result.push(format!("{}: {}", key, value));
+    }
+
+    Ok(result)
+}
+
+/// Assembles the trailer block that follows `message`: `before` (footers
+/// and probes), then the signoff, then `after` (trailers from the command
+/// line). Lines already present in the message or earlier in the block are
+/// left out.
+pub fn block(message: &str, before: &[String], signoff: Option<&str>, after: &[String]) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    let all = before
+        .iter()
+        .map(String::as_str)
+        .chain(signoff)
+        .chain(after.iter().map(String::as_str));
+
+    for line in all {
+        let line = line.trim();
+        let present = message.lines().any(|existing| existing.trim() == line);
+        if !line.is_empty() && !present && !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// Removes `Signed-off-by` and `Change-Id` lines from model output. A
+/// signoff certifies the Developer Certificate of Origin, so only `-s` or
+/// the user may add one; a Change-Id copied from an earlier message would
+/// make Gerrit take the commit for a new version of that change.
+pub fn strip_reserved(message: &str) -> String {
+    message
+        .lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            !line.starts_with("Signed-off-by:") && !line.starts_with(CHANGE_ID_KEY)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Keeps exactly one copy of `signoff` in the edited message, in its final
+/// trailer block where git and other tools look for it. A message from
+/// which the user deleted the signoff is left alone, as git does.
+pub fn place_signoff(message: &str, signoff: &str) -> String {
+    let signoff = signoff.trim();
+    let lines: Vec<&str> = message.lines().collect();
+    let Some(first) = lines.iter().position(|line| line.trim() == signoff) else {
+        return message.to_string();
+    };
+
+    // The final paragraph, if it consists only of trailers
+    let last_paragraph = lines.iter().rposition(|line| line.trim().is_empty()).map_or(0, |i| i + 1);
+    let trailers_start = (last_paragraph > 0
+        && lines[last_paragraph..].iter().all(|line| is_trailer(line)))
+    .then_some(last_paragraph);
+
+    let mut kept: Vec<&str> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let in_trailers = trailers_start.is_some_and(|start| index >= start);
+        if line.trim() != signoff || (index == first && in_trailers) {
+            kept.push(line);
+        }
+    }
+
+    let mut result = kept.join("\n").trim_end().to_string();
+    if trailers_start.is_none_or(|start| first < start) {
+        // The signoff ended up in the body; move it to the trailers
+        let separator = match (trailers_start, result.is_empty()) {
+            (_, true) => "",
+            (Some(_), false) => "\n",
+            (None, false) => "\n\n",
+        };
+        result.push_str(separator);
+        result.push_str(signoff);
+    }
+    result
+}
+
+/// Splits the final paragraph off `message` if it consists only of
+/// trailers, returning the rest of the message and the trailer lines.
+pub fn split(message: &str) -> (String, Vec<String>) {
+    let message = message.trim_end();
+    let Some((body, last)) = message.rsplit_once("\n\n") else {
+        return (message.to_string(), Vec::new());
+    };
+
+    if last.lines().all(is_trailer) {
+        (body.trim_end().to_string(), last.lines().map(String::from).collect())
+    } else {
+        (message.to_string(), Vec::new())
+    }
+}
+
+/// Whether `line` looks like a `Key: value` trailer.
+fn is_trailer(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(key, _)| {
+        !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
@@ -0,0 +1,129 @@
+use crate::diff;
+
+/// Rough number of characters per token for code and English text; close
+/// enough to keep prompts within a model's context window.
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// A run of lines belonging to one hunk, excluding its `@@` header.
+struct Hunk {
+    first_line: usize,
+    line_count: usize,
+    size: usize,
+}
+
+/// Shrinks a diff to at most `max_chars` characters.
+///
+/// Small diffs are returned unchanged. Otherwise file and hunk headers are
+/// kept and the bodies of the largest hunks are replaced with a short note
+/// until the diff fits. If even the headers alone are too large, the text
+/// is cut off at the limit.
+pub fn fit(diff: &str, max_chars: usize) -> String {
+    if diff.len() <= max_chars {
+        return diff.to_string();
+    }
+
+    let lines: Vec<&str> = diff.split_inclusive('\n').collect();
+    let mut hunks = find_hunks(&lines);
+
+    let mut total = diff.len();
+    hunks.sort_by_key(|hunk| std::cmp::Reverse(hunk.size));
+
+    // Largest hunks go first; omitted[i] holds the length of the hunk body
+    // starting at line i that is replaced by a note
+    let mut omitted: Vec<Option<usize>> = vec![None; lines.len()];
+    for hunk in &hunks {
+        if total <= max_chars {
+            break;
+        }
+        let note = omission_note(hunk.line_count);
+        total = total - hunk.size + note.len();
+        omitted[hunk.first_line] = Some(hunk.line_count);
+    }
+
+    let mut result = String::with_capacity(max_chars);
+    let mut index = 0;
+    while index < lines.len() {
+        if let Some(count) = omitted[index] {
+            result.push_str(&omission_note(count));
+            index += count;
+        } else {
+            result.push_str(lines[index]);
+            index += 1;
+        }
+    }
+
+    if result.len() > max_chars {
+        let mut cut = max_chars.saturating_sub(100);
+        while !result.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        result.truncate(cut);
+        result.push_str("\n[... rest of the diff omitted to fit the prompt size limit ...]\n");
+    }
+
+    result
+}
+
+fn omission_note(line_count: usize) -> String {
+    format!("[... {} lines omitted to fit the prompt size limit ...]\n", line_count)
+}
+
+/// Finds the body of every hunk: the lines after an `@@` header that are
+/// context, additions, removals, or "\ No newline" markers.
+fn find_hunks(lines: &[&str]) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+    let mut index = 0;
+
+    while index < lines.len() {
+        if !lines[index].starts_with("@@") {
+            index += 1;
+            continue;
+        }
+
+        let first_line = index + 1;
+        let mut end = first_line;
+        while end < lines.len() && lines[end].starts_with([' ', '+', '-', '\\']) {
+            end += 1;
+        }
+
+        if end > first_line {
+            hunks.push(Hunk {
+                first_line,
+                line_count: end - first_line,
+                size: lines[first_line..end].iter().map(|l| l.len()).sum(),
+            });
+        }
+        index = end.max(index + 1);
+    }
+
+    hunks
+}
+
+/// Summarizes added and removed lines per file, like `git diff --stat`.
+pub fn stat(diff: &str) -> String {
+    let files = diff::split_files(diff);
+    let width = files.iter().map(|f| f.path.len()).max().unwrap_or(0);
+
+    let mut result = String::new();
+    let (mut total_added, mut total_removed) = (0, 0);
+    for file in &files {
+        let (added, removed) = diff::count_changes(file.text);
+        total_added += added;
+        total_removed += removed;
+        result.push_str(&format!(
+            " {:width$} | {:>5} {}{}\n",
+            file.path,
+            added + removed,
+            "+".repeat(added.min(20)),
+            "-".repeat(removed.min(20)),
+            width = width
+        ));
+    }
+    result.push_str(&format!(
+        " {} file(s) changed, {} insertion(s)(+), {} deletion(s)(-)\n",
+        files.len(),
+        total_added,
+        total_removed
+    ));
+    result
+}
@@ -0,0 +1,75 @@
+//! Untracked files, which a commit leaves out even with `-a`. The model is
+//! told about them so it does not describe them as part of the commit.
+
+use crate::config::Config;
+use crate::exclude;
+use crate::git;
+use crate::prompt;
+use crate::redact;
+use std::fs;
+use std::path::PathBuf;
+
+/// Files listed in the prompt, so a build directory that escaped
+/// `.gitignore` does not crowd out the change.
+const MAX_FILES: usize = 50;
+
+/// With `qwen.untracked=content`, files up to this size have their content
+/// shown.
+const MAX_CONTENT_BYTES: usize = 1000;
+
+/// The untracked files that are not ignored, relative to the top of the
+/// work tree.
+pub fn files() -> Vec<String> {
+    let Ok(status) = git::output(&["status", "--porcelain", "-z", "--untracked-files=all"]) else {
+        return Vec::new();
+    };
+    status
+        .split('\0')
+        .filter_map(|entry| entry.strip_prefix("?? "))
+        .map(String::from)
+        .collect()
+}
+
+/// Lists `files` for the model as context that is not part of the commit,
+/// unless `qwen.untracked` is `false`. With `qwen.untracked=content`, the
+/// content of small text files is shown too, with secrets masked. Files
+/// excluded by `.gitqwenignore` or `qwen.exclude` are left out.
+pub fn prompt_section(files: &[String], config: &Config) -> Option<String> {
+    let mode = config.get("untracked").unwrap_or("names");
+    let patterns = exclude::patterns(config);
+    let files: Vec<&String> = files.iter().filter(|file| !exclude::is_excluded(&patterns, file)).collect();
+    if files.is_empty() || matches!(mode, "false" | "no" | "off" | "0") {
+        return None;
+    }
+
+    let mut section = String::from(
+        "\nThese files are untracked, so they are not part of this commit. Do not describe them as changes; they are listed only as context:\n",
+    );
+    let toplevel = git::output(&["rev-parse", "--show-toplevel"]).map(|toplevel| PathBuf::from(toplevel.trim()));
+    let mut listing = String::new();
+    for file in files.iter().take(MAX_FILES) {
+        listing.push_str(&format!("- {}\n", file));
+        if mode == "content"
+            && let Ok(toplevel) = &toplevel
+            && let Some(content) = small_text(&toplevel.join(file))
+        {
+            for line in redact::redact_diff(&content, config).lines() {
+                listing.push_str(&format!("    {}\n", line));
+            }
+        }
+    }
+    if files.len() > MAX_FILES {
+        listing.push_str(&format!("- and {} more\n", files.len() - MAX_FILES));
+    }
+    section.push_str(&prompt::fence(&listing));
+    Some(section)
+}
+
+/// The content of the file at `path` if it is small and text.
+fn small_text(path: &PathBuf) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    if bytes.len() > MAX_CONTENT_BYTES || bytes.contains(&0) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
@@ -0,0 +1,108 @@
+//! Checks a generated message against the repository's own hooks before
+//! the user sees it, so a rejected message can be regenerated right away
+//! instead of failing after editing.
+
+use crate::git;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+
+/// What to do with a message the hooks rejected.
+pub enum AfterRejection {
+    Regenerate,
+    Edit,
+    Abort,
+}
+
+/// Runs the `prepare-commit-msg` and `commit-msg` hooks on a scratch copy
+/// of `message`, as `git commit -F` will later. Changes the hooks make are
+/// discarded; git makes them again when committing. Returns the hook's
+/// output when one of them rejects the message.
+pub fn run_hooks(message: &str) -> Result<(), String> {
+    let hooks_dir = git::path("hooks")?;
+    let scratch = git::path("QWEN_VERIFY_MSG")?;
+
+    fs::write(&scratch, message).map_err(|e| format!("Failed to write {}: {}", scratch.display(), e))?;
+    let result = run_hook(&hooks_dir, "prepare-commit-msg", &scratch, &["message"])
+        .and_then(|()| run_hook(&hooks_dir, "commit-msg", &scratch, &[]));
+    let _ = fs::remove_file(&scratch);
+    result
+}
+
+fn run_hook(hooks_dir: &Path, name: &str, message_file: &Path, extra_args: &[&str]) -> Result<(), String> {
+    let hook = hooks_dir.join(name);
+    if !is_executable(&hook) {
+        return Ok(());
+    }
+
+    // Hooks are shell scripts; on Windows they need git's sh to run
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("sh");
+        command.arg(&hook);
+        command
+    } else {
+        Command::new(&hook)
+    };
+
+    let toplevel = git::output(&["rev-parse", "--show-toplevel"])?;
+    let output = command
+        .arg(message_file)
+        .args(extra_args)
+        .current_dir(toplevel.trim())
+        .output()
+        .map_err(|e| format!("Failed to run the {} hook: {}", name, e))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let mut report = format!("The {} hook rejected the generated message", name);
+    for stream in [&output.stdout, &output.stderr] {
+        let text = String::from_utf8_lossy(stream);
+        if !text.trim().is_empty() {
+            report.push_str(&format!(":\n{}", text.trim_end()));
+        }
+    }
+    Err(report)
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Asks the user what to do about a rejected message. Without a terminal
+/// the message is opened in the editor anyway, and git's own hook run
+/// decides.
+pub fn ask_after_rejection() -> AfterRejection {
+    if !io::stdin().is_terminal() {
+        return AfterRejection::Edit;
+    }
+
+    loop {
+        eprint!("[r]egenerate, [e]dit anyway, or [a]bort? ");
+        let _ = io::stderr().flush();
+
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer).unwrap_or(0) == 0 {
+            return AfterRejection::Abort;
+        }
+
+        match answer.trim().to_lowercase().as_str() {
+            "r" | "regenerate" => return AfterRejection::Regenerate,
+            "e" | "edit" => return AfterRejection::Edit,
+            "a" | "abort" | "" => return AfterRejection::Abort,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! `git-qwen watch`: drafts a message in the background whenever the
+//! staged changes change, so a plain `git-qwen` finds it in the cache and
+//! opens the editor at once.
+
+use crate::cancel;
+use crate::config::Config;
+use crate::git;
+use crate::pipeline::{DiffSource, MessageGenerator};
+use crate::prompt::PromptOptions;
+use crate::template;
+use crate::untracked;
+use std::thread;
+use std::time::Duration;
+
+/// Milliseconds between looks at the index unless `qwen.watchInterval`
+/// says otherwise.
+const DEFAULT_INTERVAL_MS: u64 = 1000;
+
+/// Watches the index until interrupted, drafting a message for each new
+/// set of staged changes.
+pub fn run() -> Result<(), String> {
+    crate::capabilities::require_backend()?;
+    let config = Config::load();
+    if !config.get_bool("cache").unwrap_or(true) {
+        return Err("qwen.cache is off, so drafted messages could not be reused".to_string());
+    }
+    let interval = config.get_usize("watchInterval").map_or(DEFAULT_INTERVAL_MS, |ms| ms as u64);
+
+    eprintln!("Drafting messages as the staged changes change (Ctrl+C to stop)...");
+    let mut drafted_tree = String::new();
+    loop {
+        // The index's tree changes whenever the staged content does; it
+        // cannot be written while conflicts are unresolved
+        if let Ok(tree) = git::output(&["write-tree"])
+            && tree != drafted_tree
+        {
+            draft(&config);
+            drafted_tree = tree;
+        }
+        thread::sleep(Duration::from_millis(interval));
+    }
+}
+
+/// Generates and caches the message a plain `git-qwen` would ask for.
+fn draft(config: &Config) {
+    let diff = match DiffSource::Staged.read(&[]) {
+        Ok(diff) if diff.trim().is_empty() => return,
+        Ok(diff) => diff,
+        Err(e) => {
+            eprintln!("Warning: {}", e);
+            return;
+        }
+    };
+
+    let options = PromptOptions {
+        template: template::load(None),
+        untracked: untracked::files(),
+        ..Default::default()
+    };
+    match MessageGenerator::new(config, &options).generate(&diff) {
+        Ok(message) => eprintln!("Drafted: {}", message.lines().next().unwrap_or("")),
+        // Ctrl+C during a generation cancels it; stop watching then too
+        Err(_) if cancel::was_cancelled() => cancel::exit_interrupted(),
+        Err(e) => eprintln!("Warning: {}", e),
+    }
+}
@@ -0,0 +1,156 @@
+//! Wraps message bodies without mangling the structure the model gave
+//! them: list items are wrapped one by one under a hanging indent, and code
+//! is left exactly as written.
+
+/// A run of lines that is wrapped as one unit.
+struct Item {
+    /// Marker of a list item (`- `, `1. `, ...) with its leading indent, or
+    /// empty for a plain paragraph
+    marker: String,
+    words: Vec<String>,
+}
+
+/// Wraps `text` at `width` characters. Paragraphs are reflowed, each list
+/// item (`-`, `*`, `+`, `1.`, `1)`) is reflowed on its own with its
+/// continuation lines indented under its text, and fenced or indented code
+/// blocks are kept verbatim.
+pub fn wrap(text: &str, width: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut item: Option<Item> = None;
+    let mut fence: Option<&str> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        if let Some(marker) = fence {
+            lines.push(line.trim_end().to_string());
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|marker| trimmed.starts_with(marker)) {
+            flush(&mut item, width, &mut lines);
+            lines.push(line.trim_end().to_string());
+            fence = Some(marker);
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            flush(&mut item, width, &mut lines);
+            if lines.last().is_some_and(|last| !last.is_empty()) {
+                lines.push(String::new());
+            }
+            continue;
+        }
+
+        let in_list = item.as_ref().is_some_and(|item| !item.marker.is_empty());
+
+        if let Some(marker) = list_marker(line) {
+            flush(&mut item, width, &mut lines);
+            item = Some(Item {
+                words: words(&line[marker.len()..]),
+                marker,
+            });
+        } else if is_code(line) && !in_list {
+            flush(&mut item, width, &mut lines);
+            lines.push(line.trim_end().to_string());
+        } else if let Some(current) = item.as_mut() {
+            current.words.extend(words(line));
+        } else {
+            flush(&mut item, width, &mut lines);
+            item = Some(Item {
+                marker: String::new(),
+                words: words(line),
+            });
+        }
+    }
+
+    flush(&mut item, width, &mut lines);
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace().map(String::from).collect()
+}
+
+/// Lines indented by four spaces or a tab, as Markdown and git's own
+/// examples use for code.
+fn is_code(line: &str) -> bool {
+    line.starts_with("    ") || line.starts_with('\t')
+}
+
+/// The list marker starting `line`, including its indent and the space
+/// after it.
+fn list_marker(line: &str) -> Option<String> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+
+    let marker_len = if rest.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 || digits > 3 || !rest[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+
+    let spaces = rest[marker_len..].len() - rest[marker_len..].trim_start().len();
+    if spaces == 0 || rest[marker_len..].trim().is_empty() {
+        return None;
+    }
+    Some(line[..indent + marker_len + spaces].to_string())
+}
+
+/// Writes the wrapped lines of the pending item, if any. A word that cannot
+/// fit within `width` on any line, such as a URL or a long path, gets a line
+/// of its own instead of being broken or pushing its neighbours past the
+/// limit.
+fn flush(item: &mut Option<Item>, width: usize, lines: &mut Vec<String>) {
+    let Some(item) = item.take() else {
+        return;
+    };
+
+    let hanging = " ".repeat(item.marker.chars().count());
+    let mut current = item.marker.clone();
+    let mut current_len = current.chars().count();
+    let mut empty = true;
+
+    for word in item.words {
+        let word_len = word.chars().count();
+        if hanging.len() + word_len > width {
+            if !empty {
+                lines.push(current);
+                current = hanging.clone();
+            }
+            current.push_str(&word);
+            lines.push(current);
+            current = hanging.clone();
+            current_len = hanging.len();
+            empty = true;
+            continue;
+        }
+        if !empty && current_len + 1 + word_len > width {
+            lines.push(current);
+            current = hanging.clone();
+            current_len = hanging.len();
+            empty = true;
+        }
+        if !empty {
+            current.push(' ');
+            current_len += 1;
+        }
+        current.push_str(&word);
+        current_len += word_len;
+        empty = false;
+    }
+
+    if !empty {
+        lines.push(current);
+    }
+}
@@ -0,0 +1,71 @@
+//! `git-qwen absorb` turns staged hunks into fixup commits for the recent
+//! commits whose lines they change.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+fn repo_with_fixes() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "one\ntwo\nthree\n");
+    repo.git(&["commit", "-q", "-m", "Add a"]);
+    repo.stage("b.txt", "four\nfive\nsix\n");
+    repo.git(&["commit", "-q", "-m", "Add b"]);
+
+    repo.stage("a.txt", "one\nTWO\nthree\n");
+    repo.stage("b.txt", "four\nfive\nSIX\n");
+    repo.stage("c.txt", "new\n");
+    repo
+}
+
+#[test]
+fn hunks_become_fixups_of_the_commits_they_change() {
+    let repo = repo_with_fixes();
+
+    let output = repo.git_qwen(&["absorb", "--yes"], "unused");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 hunk(s) match no recent commit and stay staged."), "{}", stdout);
+
+    assert_eq!(repo.git(&["log", "-3", "--format=%s"]), "fixup! Add b\nfixup! Add a\nAdd b\n");
+    assert_eq!(repo.last_files(), vec!["b.txt"]);
+    assert_eq!(repo.git(&["show", "--name-only", "--format=", "HEAD~1"]), "a.txt\n");
+    assert_eq!(repo.git(&["diff", "--cached", "--name-only"]), "c.txt\n");
+}
+
+#[test]
+fn dry_run_only_prints_the_plan() {
+    let repo = repo_with_fixes();
+    let head = repo.git(&["rev-parse", "HEAD"]);
+
+    let output = repo.git_qwen(&["absorb", "--dry-run"], "unused");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(" Add a\n    a.txt (1 hunk(s))\n"), "{}", stdout);
+    assert!(stdout.contains(" Add b\n    b.txt (1 hunk(s))\n"), "{}", stdout);
+    assert_eq!(repo.git(&["rev-parse", "HEAD"]), head);
+}
+
+#[test]
+fn hunks_older_than_the_depth_stay_staged() {
+    let repo = repo_with_fixes();
+    repo.git(&["config", "qwen.absorbDepth", "1"]);
+
+    let output = repo.git_qwen(&["absorb", "--yes"], "unused");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.git(&["log", "-1", "--format=%s"]), "fixup! Add b\n");
+    assert_eq!(repo.git(&["diff", "--cached", "--name-only"]), "a.txt\nc.txt\n");
+}
+
+#[test]
+fn nothing_staged_is_an_error() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "one\n");
+    repo.git(&["commit", "-q", "-m", "Add a"]);
+
+    let output = repo.git_qwen(&["absorb", "--yes"], "unused");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No staged changes to absorb"));
+}
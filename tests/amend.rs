@@ -0,0 +1,37 @@
+//! `--amend` regenerates the HEAD commit's message, even for the first
+//! commit of a repository.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn root_commit_can_be_amended() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["--amend"], "Add README");
+    assert!(
+        output.status.success(),
+        "git-qwen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(repo.last_message(), "Add README");
+    assert_eq!(repo.git(&["rev-list", "--count", "HEAD"]).trim(), "1");
+}
+
+#[test]
+fn no_edit_keeps_the_message() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&["--amend", "--no-edit"], "Add file");
+    assert!(
+        output.status.success(),
+        "git-qwen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(repo.last_message(), "Initial commit");
+    assert!(repo.git(&["show", "--name-only", "--format="]).contains("file.txt"));
+}
@@ -0,0 +1,38 @@
+//! The optional trailer saying which model helped write the message.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn trailer_names_the_model() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.assistedBy", "true"]);
+    repo.git(&["config", "qwen.modelName", "qwen2.5-coder"]);
+    repo.stage("a.txt", "a\n");
+
+    assert!(repo.git_qwen(&[], "Add a").status.success());
+    assert_eq!(repo.last_message(), "Add a\n\nAssisted-by: git-qwen (qwen2.5-coder)");
+}
+
+#[test]
+fn key_can_be_changed() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.assistedBy", "true"]);
+    repo.git(&["config", "qwen.assistedByKey", "Generated-by"]);
+    repo.stage("a.txt", "a\n");
+
+    assert!(repo.git_qwen(&[], "Add a").status.success());
+    assert_eq!(repo.last_message(), "Add a\n\nGenerated-by: git-qwen (qwen)");
+}
+
+#[test]
+fn no_trailer_by_default() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "a\n");
+
+    assert!(repo.git_qwen(&[], "Add a").status.success());
+    assert_eq!(repo.last_message(), "Add a");
+}
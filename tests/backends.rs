@@ -0,0 +1,90 @@
+//! `qwen.backends` tries each backend in turn until one answers.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn next_backend_answers_when_the_first_fails() {
+    let repo = TestRepo::new();
+    repo.install_program("ollama", "echo 'model not found' >&2; exit 1");
+    repo.git(&["config", "qwen.backends", "ollama,qwen"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], "Add file from qwen");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "git-qwen failed: {}", stderr);
+    assert_eq!(repo.last_message(), "Add file from qwen");
+    assert!(stderr.contains("ollama failed"), "no fallback note in: {}", stderr);
+    assert!(stderr.contains("model not found); trying qwen"), "{}", stderr);
+    assert!(stderr.contains("Answer written by qwen."));
+}
+
+#[test]
+fn first_backend_answers_without_a_note() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.backends", "qwen,mock"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], "Add file from qwen");
+    assert!(output.status.success());
+    assert_eq!(repo.last_message(), "Add file from qwen");
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Answer written by"));
+}
+
+#[test]
+fn openai_backend_posts_to_the_chat_completions_api() {
+    let repo = TestRepo::new();
+    repo.install_program(
+        "curl",
+        "cat > \"$QWEN_STUB_PROMPT.request\"\necho '{\"choices\": [{\"message\": {\"content\": \"Add file from the API\"}}]}'",
+    );
+    repo.git(&["config", "qwen.backends", "openai"]);
+    repo.git(&["config", "qwen.modelName", "qwen3-coder"]);
+    repo.stage("file.txt", "content\n");
+
+    let vars = [("OPENAI_BASE_URL", "http://127.0.0.1:8000/v1/"), ("OPENAI_API_KEY", "sk-test")];
+    let output = repo.git_qwen_env(&[], "unused", &vars);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add file from the API");
+    let request = std::fs::read_to_string(repo.path().parent().unwrap().join("prompt.request")).unwrap();
+    assert!(request.contains("url = \"http://127.0.0.1:8000/v1/chat/completions\"\n"), "{}", request);
+    assert!(request.contains("header = \"Authorization: Bearer sk-test\"\n"), "{}", request);
+    assert!(request.contains("\\\"model\\\":\\\"qwen3-coder\\\""), "{}", request);
+    assert!(request.contains("file.txt"), "{}", request);
+}
+
+#[test]
+fn openai_key_is_read_from_the_keyring_when_not_in_the_environment() {
+    let repo = TestRepo::new();
+    repo.install_program(
+        "curl",
+        "cat > \"$QWEN_STUB_PROMPT.request\"\necho '{\"choices\": [{\"message\": {\"content\": \"Add file\"}}]}'",
+    );
+    repo.install_program(
+        "secret-tool",
+        "[ \"$*\" = 'lookup service git-qwen account openai' ] && echo sk-from-keyring",
+    );
+    repo.git(&["config", "qwen.backends", "openai"]);
+    repo.git(&["config", "qwen.modelName", "qwen3-coder"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen_env(&[], "unused", &[("OPENAI_BASE_URL", "http://127.0.0.1:8000/v1/")]);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let request = std::fs::read_to_string(repo.path().parent().unwrap().join("prompt.request")).unwrap();
+    assert!(request.contains("header = \"Authorization: Bearer sk-from-keyring\"\n"), "{}", request);
+}
+
+#[test]
+fn ollama_runs_the_model_given_with_dash_dash_model() {
+    let repo = TestRepo::new();
+    repo.install_program("ollama", "printf '%s\\n' \"$@\" > \"$QWEN_STUB_PROMPT.args\"\ncat > /dev/null\necho 'Add file'");
+    repo.git(&["config", "qwen.backends", "ollama"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&["--model", "llama3.1"], "unused");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_args(), ["run", "llama3.1"]);
+}
@@ -0,0 +1,85 @@
+//! Batch commands run their qwen calls through the job scheduler: several
+//! at once, within each backend's limit, and resuming after a failure.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+
+/// A qwen that fails for the second commit until `prompt.allow` exists,
+/// logs which commit each prompt was about, and notes calls that overlap.
+const FLAKY_QWEN: &str = "prompt=$(cat)
+dir=$(dirname \"$QWEN_STUB_PROMPT\")
+printf '%s\\n' \"$prompt\" > \"$QWEN_STUB_PROMPT\"
+case \"$prompt\" in *'Second change'*) [ -e \"$dir/prompt.allow\" ] || { echo 'server error' >&2; exit 1; };; esac
+mkdir \"$dir/running\" 2>/dev/null || echo overlap >> \"$dir/prompt.overlaps\"
+sleep 0.2
+printf '%s\\n' \"$prompt\" | grep -o -m 1 'First change\\|Second change\\|Third change' >> \"$dir/prompt.calls\"
+rmdir \"$dir/running\" 2>/dev/null
+printf '%s\\n' \"$QWEN_STUB_OUTPUT\"";
+
+fn repo_with_commits() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.install_program("qwen", FLAKY_QWEN);
+    for (file, message) in [("a.txt", "First change"), ("b.txt", "Second change"), ("c.txt", "Third change")] {
+        repo.stage(file, "content\n");
+        repo.git(&["commit", "-q", "-m", message]);
+    }
+    repo
+}
+
+fn stub_file(repo: &TestRepo, name: &str) -> String {
+    fs::read_to_string(repo.path().parent().unwrap().join(name)).unwrap_or_default()
+}
+
+#[test]
+fn reword_resumes_after_a_failed_message() {
+    let repo = repo_with_commits();
+    let head = repo.git(&["rev-parse", "HEAD"]);
+
+    let output = repo.git_qwen_with_editor(&["reword", "HEAD~3"], "Reworded", "true");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("2 of 3 done and kept"), "{}", stderr);
+    assert_eq!(repo.git(&["rev-parse", "HEAD"]), head);
+
+    fs::write(repo.path().parent().unwrap().join("prompt.allow"), "").unwrap();
+    let output = repo.git_qwen_with_editor(&["reword", "HEAD~3"], "Reworded", "true");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("resuming, 2 of 3 already done"), "{}", stderr);
+    assert_eq!(stub_file(&repo, "prompt.calls").matches("First change").count(), 1);
+    assert_eq!(repo.git(&["log", "-3", "--format=%s"]), "Reworded\nReworded\nReworded\n");
+}
+
+#[test]
+fn backend_limit_keeps_calls_apart() {
+    let repo = repo_with_commits();
+    fs::write(repo.path().parent().unwrap().join("prompt.allow"), "").unwrap();
+    repo.git(&["config", "qwen.jobs", "3"]);
+    repo.git(&["config", "qwen.qwen.jobs", "1"]);
+
+    let output = repo.git_qwen_with_editor(&["reword", "HEAD~3"], "Reworded", "true");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stub_file(&repo, "prompt.calls").lines().count(), 3);
+    assert_eq!(stub_file(&repo, "prompt.overlaps"), "");
+}
+
+#[test]
+fn long_changelog_is_summarized_in_batches() {
+    let repo = repo_with_commits();
+    fs::write(repo.path().parent().unwrap().join("prompt.allow"), "").unwrap();
+    repo.git(&["config", "qwen.maxPromptTokens", "500"]);
+    for i in 0..20 {
+        repo.stage("log.txt", &format!("{}\n", i));
+        repo.git(&["commit", "-q", "-m", &format!("Record entry {}\n\n{}", i, "Details of the entry. ".repeat(10))]);
+    }
+
+    let output = repo.git_qwen(&["changelog", "HEAD~23"], "### Added\n- Entries");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("notes on their changes"), "{}", prompt);
+    assert!(!prompt.contains("Details of the entry"), "{}", prompt);
+}
@@ -0,0 +1,36 @@
+//! Binary files and Git LFS pointers reach the model as short notes.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+fn lfs_pointer(size: u64) -> String {
+    format!("version https://git-lfs.github.com/spec/v1\noid sha256:{}\nsize {}\n", "a".repeat(64), size)
+}
+
+#[test]
+fn binary_file_is_noted_with_its_type_and_size() {
+    let repo = TestRepo::new();
+    repo.stage("logo.png", &"\0".repeat(2048));
+    assert!(repo.git_qwen(&[], "Add the logo").status.success());
+    assert!(repo.last_prompt().contains("Binary image added (2.0 KB)"), "{}", repo.last_prompt());
+
+    repo.stage("logo.png", &"\0".repeat(3072));
+    assert!(repo.git_qwen(&[], "Enlarge the logo").status.success());
+    assert!(repo.last_prompt().contains("Binary image modified (2.0 KB -> 3.0 KB)"), "{}", repo.last_prompt());
+}
+
+#[test]
+fn lfs_pointer_is_noted_with_the_real_size() {
+    let repo = TestRepo::new();
+    repo.stage("model.onnx", &lfs_pointer(1024 * 1024));
+    repo.git(&["commit", "-q", "-m", "Add the model"]);
+    repo.stage("model.onnx", &lfs_pointer(3 * 1024 * 1024));
+
+    assert!(repo.git_qwen(&[], "Retrain the model").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Git LFS model modified (1.0 MB -> 3.0 MB)"), "{}", prompt);
+    assert!(!prompt.contains("oid sha256"), "{}", prompt);
+}
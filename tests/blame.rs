@@ -0,0 +1,40 @@
+//! The prompt carries the messages of the commits that wrote the lines a
+//! change rewrites.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const REASON: &str = "The upstream server disconnects clients that retry more than three times.";
+
+fn repo_with_history() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.stage("client.py", "def connect():\n    retries = 5\n    return retries\n");
+    repo.git(&["commit", "-q", "-m", "Add the client"]);
+    repo.stage("client.py", "def connect():\n    retries = 3\n    return retries\n");
+    repo.git(&["commit", "-q", "-m", &format!("Lower the retry limit\n\n{}", REASON)]);
+    repo.stage("client.py", "def connect():\n    retries = 4\n    return retries\n");
+    repo
+}
+
+#[test]
+fn messages_of_rewritten_lines_are_included() {
+    let repo = repo_with_history();
+
+    assert!(repo.git_qwen(&[], "Allow one more retry").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Lower the retry limit"), "{}", prompt);
+    assert!(prompt.contains(REASON), "{}", prompt);
+    assert!(!prompt.contains("Add the client"), "{}", prompt);
+}
+
+#[test]
+fn blame_can_be_turned_off() {
+    let repo = repo_with_history();
+    repo.git(&["config", "qwen.blame", "false"]);
+
+    assert!(repo.git_qwen(&[], "Allow one more retry").status.success());
+    assert!(!repo.last_prompt().contains(REASON));
+}
@@ -0,0 +1,88 @@
+//! Body wrapping reflows prose but keeps lists and code intact.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+/// Commits a change with the stub answering `answer` and returns the
+/// resulting commit message.
+fn commit_with_answer(answer: &str) -> String {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], answer);
+    assert!(
+        output.status.success(),
+        "git-qwen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    repo.last_message()
+}
+
+#[test]
+fn long_paragraph_is_wrapped() {
+    assert_eq!(
+        commit_with_answer(
+            "Add file\n\nThe new file holds the content that the rest of the project reads at startup, so it must exist."
+        ),
+        "Add file\n\nThe new file holds the content that the rest of the project reads at\nstartup, so it must exist."
+    );
+}
+
+#[test]
+fn bullet_items_are_wrapped_separately() {
+    assert_eq!(
+        commit_with_answer(
+            "Add file\n\nChanges:\n- Add the file that the rest of the project reads at startup, so it always exists\n- Keep it short"
+        ),
+        "Add file\n\nChanges:\n- Add the file that the rest of the project reads at startup, so it\n  always exists\n- Keep it short"
+    );
+}
+
+#[test]
+fn numbered_items_are_kept() {
+    assert_eq!(
+        commit_with_answer("Add file\n\n1. Create it\n2) Fill it"),
+        "Add file\n\n1. Create it\n2) Fill it"
+    );
+}
+
+#[test]
+fn code_block_inside_body_is_kept() {
+    assert_eq!(
+        commit_with_answer(
+            "Add file\n\nRead it with:\n\n```\nlet content = fs::read_to_string(\"file.txt\")?;\n\nprintln!(\"{}\", content);\n```"
+        ),
+        "Add file\n\nRead it with:\n\n```\nlet content = fs::read_to_string(\"file.txt\")?;\n\nprintln!(\"{}\", content);\n```"
+    );
+}
+
+#[test]
+fn indented_code_is_kept() {
+    assert_eq!(
+        commit_with_answer("Add file\n\nRun it with:\n\n    cargo run --release -- --input file.txt --output out.txt --verbose"),
+        "Add file\n\nRun it with:\n\n    cargo run --release -- --input file.txt --output out.txt --verbose"
+    );
+}
+
+#[test]
+fn long_url_gets_its_own_line() {
+    assert_eq!(
+        commit_with_answer(
+            "Add file\n\nSee https://example.com/a/very/long/path/to/the/design/document/for/this/change.html for details."
+        ),
+        "Add file\n\nSee\nhttps://example.com/a/very/long/path/to/the/design/document/for/this/change.html\nfor details."
+    );
+}
+
+#[test]
+fn long_path_in_bullet_keeps_hanging_indent() {
+    assert_eq!(
+        commit_with_answer(
+            "Add file\n\n- Move src/some/deeply/nested/module/with/a/long/name/implementation_details.rs here"
+        ),
+        "Add file\n\n- Move\n  src/some/deeply/nested/module/with/a/long/name/implementation_details.rs\n  here"
+    );
+}
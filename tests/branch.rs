@@ -0,0 +1,67 @@
+//! `git-qwen branch` suggests a branch name for the uncommitted changes or
+//! a description of the work, and can switch to it.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn name_is_suggested_for_the_changes() {
+    let repo = TestRepo::new();
+    repo.write("README", "retry with backoff\n");
+
+    let output = repo.git_qwen(&["branch"], "Sure! `feat/Add Retry Backoff`");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "feat/add-retry-backoff\n");
+    assert!(repo.last_prompt().contains("+retry with backoff"), "{}", repo.last_prompt());
+    assert_eq!(repo.git(&["branch", "--show-current"]), "main\n");
+}
+
+#[test]
+fn ticket_in_the_description_is_kept_in_the_name() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["branch", "JIRA-42", "retry", "requests"], "feat/jira-42-retry-requests");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "feat/JIRA-42-retry-requests\n");
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("The work: JIRA-42 retry requests\n"), "{}", prompt);
+    assert!(prompt.contains("Include the ticket JIRA-42 after the slash"), "{}", prompt);
+}
+
+#[test]
+fn taken_names_get_a_number() {
+    let repo = TestRepo::new();
+    repo.git(&["branch", "fix/crash"]);
+
+    let output = repo.git_qwen(&["branch", "fix", "the", "crash"], "fix/crash");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "fix/crash-2\n");
+}
+
+#[test]
+fn create_switches_to_the_branch_keeping_the_changes() {
+    let repo = TestRepo::new();
+    repo.write("README", "retry with backoff\n");
+
+    let output = repo.git_qwen(&["branch", "--create"], "feat/add-retry-backoff");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.git(&["branch", "--show-current"]), "feat/add-retry-backoff\n");
+    assert_eq!(repo.git(&["status", "--porcelain"]), " M README\n");
+}
+
+#[test]
+fn nothing_to_name_and_unusable_answers_are_errors() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["branch"], "feat/anything");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No changes to name a branch after; describe the work instead"));
+    assert!(!repo.path().with_file_name("prompt").exists(), "the model was asked");
+
+    let output = repo.git_qwen(&["branch", "add", "retry"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("qwen did not suggest a usable branch name"));
+}
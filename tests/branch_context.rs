@@ -0,0 +1,68 @@
+//! The branch name, and the ticket found in it with `qwen.ticketPattern`,
+//! are given to the model, and `qwen.ticketFooter` adds a reference.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn branch_name_and_ticket_are_context() {
+    let repo = TestRepo::new();
+    repo.git(&["checkout", "-q", "-b", "feature/JIRA-1234-add-retry"]);
+    repo.stage("retry.rs", "fn retry() {}\n");
+
+    assert!(repo.git_qwen(&[], "Add retry").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("<data>\nfeature/JIRA-1234-add-retry\n</data>\n"), "{}", prompt);
+    assert!(prompt.contains("It belongs to ticket JIRA-1234.\n"), "{}", prompt);
+    assert_eq!(repo.last_message(), "Add retry", "no footer unless qwen.ticketFooter is set");
+}
+
+#[test]
+fn mainline_branches_are_not_context() {
+    let repo = TestRepo::new();
+    repo.stage("retry.rs", "fn retry() {}\n");
+
+    assert!(repo.git_qwen(&[], "Add retry").status.success());
+    assert!(!repo.last_prompt().contains("The change was made on the branch"), "{}", repo.last_prompt());
+}
+
+#[test]
+fn ticket_footer_is_added_below_the_message() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.ticketFooter", "Refs: {ticket}"]);
+    repo.git(&["checkout", "-q", "-b", "feature/JIRA-1234-add-retry"]);
+    repo.stage("retry.rs", "fn retry() {}\n");
+
+    assert!(repo.git_qwen(&[], "Add retry").status.success());
+    assert_eq!(repo.last_message(), "Add retry\n\nRefs: JIRA-1234");
+    assert!(repo.last_prompt().contains("a reference to it is added automatically, so do not mention it"));
+}
+
+#[test]
+fn ticket_pattern_capture_group_is_the_ticket() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.ticketPattern", "^fix/([0-9]+)"]);
+    repo.git(&["config", "qwen.ticketFooter", "Refs #{ticket}"]);
+    repo.git(&["checkout", "-q", "-b", "fix/123-crash"]);
+    repo.stage("crash.rs", "fn fixed() {}\n");
+
+    assert!(repo.git_qwen(&[], "Fix the crash").status.success());
+    assert_eq!(repo.last_message(), "Fix the crash\n\nRefs #123");
+}
+
+#[test]
+fn invalid_ticket_pattern_is_ignored_with_a_warning() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.ticketPattern", "(unclosed"]);
+    repo.git(&["config", "qwen.ticketFooter", "Refs: {ticket}"]);
+    repo.git(&["checkout", "-q", "-b", "feature/JIRA-1234-add-retry"]);
+    repo.stage("retry.rs", "fn retry() {}\n");
+
+    let output = repo.git_qwen(&[], "Add retry");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: ignoring invalid qwen.ticketPattern '(unclosed'"));
+    assert_eq!(repo.last_message(), "Add retry");
+}
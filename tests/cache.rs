@@ -0,0 +1,34 @@
+//! Running again on the same changes reuses the message generated before.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn message_is_reused_after_aborting_the_editor() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen_with_editor(&[], "Add file", ": > \"$1\"");
+    assert_eq!(output.status.code(), Some(8));
+
+    let output = repo.git_qwen(&[], "Add something else");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add file");
+}
+
+#[test]
+fn cache_can_be_turned_off() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.cache", "false"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen_with_editor(&[], "Add file", ": > \"$1\"");
+    assert_eq!(output.status.code(), Some(8));
+
+    let output = repo.git_qwen(&[], "Add something else");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add something else");
+}
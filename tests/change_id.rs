@@ -0,0 +1,61 @@
+//! Gerrit Change-Id trailers.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+fn change_ids(message: &str) -> Vec<String> {
+    message.lines().filter(|line| line.starts_with("Change-Id: ")).map(String::from).collect()
+}
+
+#[test]
+fn new_commit_gets_a_change_id() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.changeId", "true"]);
+    repo.stage("a.txt", "a\n");
+
+    assert!(repo.git_qwen(&[], "Add a").status.success());
+    let ids = change_ids(&repo.last_message());
+    assert_eq!(ids.len(), 1, "{:?}", ids);
+    let id = ids[0].trim_start_matches("Change-Id: ");
+    assert!(id.len() == 41 && id.starts_with('I') && id[1..].bytes().all(|b| b.is_ascii_hexdigit()), "{}", id);
+}
+
+#[test]
+fn amending_keeps_the_change_id() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.changeId", "true"]);
+    repo.stage("a.txt", "a\n");
+    assert!(repo.git_qwen(&[], "Add a").status.success());
+    let ids = change_ids(&repo.last_message());
+
+    repo.stage("a.txt", "a\nb\n");
+    assert!(repo.git_qwen(&["--amend"], "Add a and b").status.success());
+    assert_eq!(change_ids(&repo.last_message()), ids);
+}
+
+#[test]
+fn change_id_written_by_the_model_is_dropped() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.jsonOutput", "false"]);
+    repo.stage("a.txt", "a\n");
+
+    let answer = "Add a\n\nChange-Id: I0123456789abcdef0123456789abcdef01234567";
+    assert!(repo.git_qwen(&[], answer).status.success());
+    assert!(change_ids(&repo.last_message()).is_empty(), "{}", repo.last_message());
+}
+
+#[test]
+fn printed_message_has_no_change_id() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.changeId", "true"]);
+    repo.stage("a.txt", "a\n");
+
+    let output = repo.git_qwen(&["--print"], "Add a");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Add a"), "{}", stdout);
+    assert!(change_ids(&stdout).is_empty(), "{}", stdout);
+}
@@ -0,0 +1,82 @@
+//! `git-qwen changelog` writes a CHANGELOG section for a range of commits,
+//! printed or inserted into CHANGELOG.md.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+
+const ANSWER: &str = "### Added\n- Failed requests are retried.";
+
+fn repo_with_release() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.stage("old.txt", "old\n");
+    repo.git(&["commit", "-q", "-m", "feat: add the old feature"]);
+    repo.git(&["tag", "v1.0"]);
+    repo.stage("retry.txt", "retry\n");
+    repo.git(&["commit", "-q", "-m", "feat(http): retry failed requests"]);
+    repo
+}
+
+#[test]
+fn section_for_the_commits_since_the_latest_tag_is_printed() {
+    let repo = repo_with_release();
+
+    let output = repo.git_qwen(&["changelog"], ANSWER);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), format!("## [Unreleased]\n\n{}\n", ANSWER));
+
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Headings: ### Added, ### Changed"), "{}", prompt);
+    assert!(prompt.contains("feat(http): retry failed requests"), "{}", prompt);
+    assert!(!prompt.contains("add the old feature"), "{}", prompt);
+}
+
+#[test]
+fn conventional_style_and_version_shape_the_heading() {
+    let repo = repo_with_release();
+    let date = repo.git(&["log", "-1", "--format=%cs"]);
+
+    let output = repo.git_qwen(&["changelog", "v1.0..HEAD", "--version", "1.1.0", "--style", "conventional"], "### Features\n- **http:** retry");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with(&format!("## 1.1.0 ({})\n\n### Features\n", date.trim())));
+    assert!(repo.last_prompt().contains("Headings: ### ⚠ BREAKING CHANGES, ### Features"));
+
+    let output = repo.git_qwen(&["changelog", "--style", "gnu"], ANSWER);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown changelog style 'gnu'"));
+}
+
+#[test]
+fn write_inserts_the_section_above_the_latest_release() {
+    let repo = repo_with_release();
+    repo.write("CHANGELOG.md", "# Changelog\n\nAll notable changes.\n\n## [1.0] - 2024-01-01\n\n- First release.\n");
+
+    let output = repo.git_qwen(&["changelog", "--write", "--version", "1.1"], ANSWER);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stdout.is_empty());
+    let date = repo.git(&["log", "-1", "--format=%cs"]);
+    assert_eq!(
+        fs::read_to_string(repo.path().join("CHANGELOG.md")).unwrap(),
+        format!(
+            "# Changelog\n\nAll notable changes.\n\n## [1.1] - {}\n\n{}\n\n## [1.0] - 2024-01-01\n\n- First release.\n",
+            date.trim(),
+            ANSWER
+        )
+    );
+}
+
+#[test]
+fn empty_range_and_empty_answer_are_errors() {
+    let repo = repo_with_release();
+
+    let output = repo.git_qwen(&["changelog", "HEAD"], ANSWER);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No commits in HEAD..HEAD to describe"));
+
+    let output = repo.git_qwen(&["changelog"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("qwen returned an empty changelog"));
+}
@@ -0,0 +1,43 @@
+//! `git-qwen pr --copy` copies the description with the clipboard tool
+//! found up front, and fails before asking the model when there is none.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+
+const ANSWER: &str = r#"{"title": "Add a feature", "body": "Adds a feature."}"#;
+
+fn feature_branch() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.stage("feature.txt", "feature\n");
+    repo.git(&["commit", "-q", "-m", "Add a feature"]);
+    repo.install_program("wl-copy", "cat > \"$QWEN_STUB_PROMPT.clipboard\"");
+    repo
+}
+
+#[test]
+fn pr_description_is_copied_to_the_clipboard() {
+    let repo = feature_branch();
+
+    let output = repo.git_qwen_env(&["pr", "--copy"], ANSWER, &[("WAYLAND_DISPLAY", "wayland-0")]);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Add a feature\n"));
+    let copied = fs::read_to_string(repo.path().with_file_name("prompt.clipboard")).unwrap();
+    assert_eq!(copied, "Add a feature\n\nAdds a feature.");
+}
+
+#[test]
+fn local_clipboard_is_not_used_over_ssh() {
+    let repo = feature_branch();
+
+    let vars = [("WAYLAND_DISPLAY", "wayland-0"), ("SSH_CONNECTION", "10.0.0.1 22 10.0.0.2 22")];
+    let output = repo.git_qwen_env(&["pr", "--copy"], ANSWER, &vars);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No clipboard to copy to"));
+    assert!(!repo.path().with_file_name("prompt.clipboard").exists());
+    assert!(!repo.path().with_file_name("prompt").exists(), "the model was asked before the clipboard was checked");
+}
@@ -0,0 +1,65 @@
+//! `--closes` and `qwen.closeFromBranch` add closing footers below the
+//! wrapped body, where wrapping never touches them.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn closes_adds_a_footer_per_issue() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&["--closes", "42", "--closes", "#42", "--closes", "owner/repo#7"], "Fix the crash");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Fix the crash\n\nCloses #42\nCloses owner/repo#7");
+}
+
+#[test]
+fn footers_are_not_wrapped_with_the_body() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+    let url = "https://github.com/example-organization/example-repository/issues/4242";
+    let body = "The parser crashed on empty input because it read past the end of the buffer, so it now checks the length first.";
+
+    let output = repo.git_qwen(&["--closes", url], &format!("Fix the crash\n\n{}", body));
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let message = repo.last_message();
+    assert!(message.ends_with(&format!("\n\nCloses {}", url)), "{}", message);
+    assert!(message.lines().filter(|line| !line.starts_with("Closes")).all(|line| line.chars().count() <= 72), "{}", message);
+}
+
+#[test]
+fn close_keyword_is_configurable() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.closeKeyword", "Fixes"]);
+    repo.stage("file.txt", "content\n");
+
+    assert!(repo.git_qwen(&["--closes", "42"], "Fix the crash").status.success());
+    assert_eq!(repo.last_message(), "Fix the crash\n\nFixes #42");
+}
+
+#[test]
+fn issue_number_in_the_branch_closes_the_issue() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.closeFromBranch", "true"]);
+    repo.git(&["config", "qwen.ticketPattern", "^fix/([0-9]+)"]);
+    repo.git(&["checkout", "-q", "-b", "fix/42-crash"]);
+    repo.stage("file.txt", "content\n");
+
+    assert!(repo.git_qwen(&["--closes", "42"], "Fix the crash").status.success());
+    assert_eq!(repo.last_message(), "Fix the crash\n\nCloses #42", "the duplicate is dropped");
+}
+
+#[test]
+fn tickets_that_are_not_issue_numbers_close_nothing() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.closeFromBranch", "true"]);
+    repo.git(&["checkout", "-q", "-b", "feature/JIRA-1234-add-retry"]);
+    repo.stage("file.txt", "content\n");
+
+    assert!(repo.git_qwen(&[], "Add retry").status.success());
+    assert_eq!(repo.last_message(), "Add retry");
+}
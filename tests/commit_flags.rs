@@ -0,0 +1,122 @@
+//! `git commit` options shape what is described and what is committed.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::process::Output;
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "git-qwen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn all_commits_and_describes_unstaged_changes() {
+    let repo = TestRepo::new();
+    repo.write("README", "changed\n");
+
+    assert_success(&repo.git_qwen(&["-a"], "Update README"));
+    assert_eq!(repo.last_message(), "Update README");
+    assert_eq!(repo.last_files(), ["README"]);
+    assert!(repo.last_prompt().contains("+changed"));
+}
+
+#[test]
+fn signoff_is_added_once() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    assert_success(&repo.git_qwen(&["-s"], "Add file\n\nSigned-off-by: Someone Else <else@example.com>"));
+    assert_eq!(repo.last_message(), "Add file\n\nSigned-off-by: Test User <test@example.com>");
+}
+
+#[test]
+fn amend_describes_the_head_commit_and_added_changes() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+    assert_success(&repo.git_qwen(&[], "Add file"));
+    repo.stage("other.txt", "more\n");
+
+    assert_success(&repo.git_qwen(&["--amend"], "Add two files"));
+    assert_eq!(repo.last_message(), "Add two files");
+    assert_eq!(repo.last_files(), ["file.txt", "other.txt"]);
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Add file"));
+    assert!(prompt.contains("+more"));
+}
+
+#[test]
+fn pathspecs_limit_the_commit_and_the_prompt() {
+    let repo = TestRepo::new();
+    repo.stage("one.txt", "first\n");
+    repo.stage("two.txt", "second\n");
+
+    assert_success(&repo.git_qwen(&["--", "one.txt"], "Add one"));
+    assert_eq!(repo.last_files(), ["one.txt"]);
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("+first"));
+    assert!(!prompt.contains("+second"));
+}
+
+#[test]
+fn nothing_staged_is_an_error() {
+    let repo = TestRepo::new();
+    repo.write("README", "changed\n");
+
+    let output = repo.git_qwen(&[], "Update README");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No changes staged for commit"));
+    assert_eq!(repo.last_message(), "Initial commit");
+}
+
+#[test]
+fn nothing_modified_with_all_is_an_error() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["-a"], "Update README");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no modified tracked files"));
+}
+
+#[test]
+fn empty_message_aborts_the_commit() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen_with_editor(&[], "Add file", ": > \"$1\"");
+    assert_eq!(output.status.code(), Some(8));
+    assert_eq!(repo.last_message(), "Initial commit");
+}
+
+#[test]
+fn commit_editmsg_is_kept_after_committing() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    assert_success(&repo.git_qwen(&[], "Add file"));
+    let kept = std::fs::read_to_string(repo.path().join(".git/COMMIT_EDITMSG")).expect("COMMIT_EDITMSG is kept");
+    assert_eq!(kept, "Add file\n");
+}
+
+#[test]
+fn help_and_version_are_git_qwens_own() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    for flag in ["-h", "--help"] {
+        let output = repo.git_qwen(&[flag], "Add file");
+        assert_success(&output);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Usage: git-qwen"), "{}", flag);
+    }
+    let output = repo.git_qwen(&["--version"], "Add file");
+    assert_success(&output);
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("git-qwen "));
+
+    assert_eq!(repo.last_message(), "Initial commit");
+    assert!(!repo.path().with_file_name("prompt").exists());
+}
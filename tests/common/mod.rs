@@ -0,0 +1,259 @@
+//! A scratch repository with a stub `qwen` that prints a canned answer, for
+//! running git-qwen end to end.
+
+// Each test file uses only some of the helpers
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use tempfile::TempDir;
+
+const STUB_QWEN: &str = "#!/bin/sh\nprintf '%s\\n' \"$@\" > \"$QWEN_STUB_PROMPT.args\"\ncat > \"$QWEN_STUB_PROMPT\"\nprintf '%s\\n' \"$QWEN_STUB_OUTPUT\"\n";
+
+pub struct TestRepo {
+    dir: TempDir,
+}
+
+impl TestRepo {
+    /// Creates a repository with one commit and git-qwen's retries turned
+    /// off, so each run calls the stub exactly once, where sending the
+    /// changes to qwen was confirmed.
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let repo = TestRepo { dir };
+
+        fs::create_dir(repo.bin_dir()).expect("create stub dir");
+        let stub = repo.bin_dir().join("qwen");
+        fs::write(&stub, STUB_QWEN).expect("write stub qwen");
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).expect("make stub executable");
+        fs::write(repo.home().join(".gitconfig"), "").expect("write empty global config");
+
+        fs::create_dir(repo.path()).expect("create work tree");
+        repo.git(&["init", "-q", "-b", "main"]);
+        repo.git(&["config", "user.name", "Test User"]);
+        repo.git(&["config", "user.email", "test@example.com"]);
+        repo.git(&["config", "qwen.formatRetries", "0"]);
+        repo.git(&["config", "qwen.retries", "0"]);
+        repo.git(&["config", "qwen.recentCommits", "0"]);
+        // As if sending to the stub had been confirmed on a terminal
+        repo.git(&["config", "qwen.remoteConfirmed", "true"]);
+        repo.stage("README", "initial\n");
+        repo.git(&["commit", "-q", "-m", "Initial commit"]);
+        repo
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.dir.path().join("work")
+    }
+
+    fn home(&self) -> &Path {
+        self.dir.path()
+    }
+
+    fn prompt_file(&self) -> PathBuf {
+        self.dir.path().join("prompt")
+    }
+
+    fn bin_dir(&self) -> PathBuf {
+        self.dir.path().join("bin")
+    }
+
+    /// A command run in the work tree, isolated from the user's git config.
+    fn command(&self, program: &str) -> Command {
+        self.command_in(&self.path(), program)
+    }
+
+    /// A command run in `dir`, isolated from the user's git config.
+    fn command_in(&self, dir: &Path, program: &str) -> Command {
+        let path = format!("{}:{}", self.bin_dir().display(), std::env::var("PATH").unwrap_or_default());
+        let mut command = Command::new(program);
+        command
+            .current_dir(dir)
+            .env("PATH", path)
+            .env("HOME", self.home())
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_EDITOR", "true")
+            .env("QWEN_STUB_PROMPT", self.prompt_file())
+            // Keeps the daemon's socket private to the test
+            .env("XDG_RUNTIME_DIR", self.home())
+            .env_remove("OPENAI_BASE_URL")
+            .env_remove("OPENAI_API_KEY")
+            .env_remove("OLLAMA_HOST")
+            // Leaves the clipboard to the tests that set one up
+            .env_remove("SSH_CONNECTION")
+            .env_remove("SSH_TTY")
+            .env_remove("DISPLAY")
+            .env_remove("WAYLAND_DISPLAY")
+            .env_remove("GIT_DIR")
+            .env_remove("GIT_WORK_TREE");
+        command
+    }
+
+    /// Runs git and returns its stdout, panicking if it fails.
+    pub fn git(&self, args: &[&str]) -> String {
+        self.git_in(&self.path(), args)
+    }
+
+    /// Runs git, which may fail, such as a merge stopping on conflicts.
+    pub fn try_git(&self, args: &[&str]) -> Output {
+        self.command("git").args(args).output().expect("run git")
+    }
+
+    /// Like `git`, run in `dir`.
+    pub fn git_in(&self, dir: &Path, args: &[&str]) -> String {
+        let output = self.command_in(dir, "git").args(args).output().expect("run git");
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).expect("git output is UTF-8")
+    }
+
+    /// Writes `content` to `file` and stages it.
+    pub fn stage(&self, file: &str, content: &str) {
+        self.write(file, content);
+        self.git(&["add", file]);
+    }
+
+    /// Writes `content` to `file` without staging it.
+    pub fn write(&self, file: &str, content: &str) {
+        fs::write(self.path().join(file), content).expect("write file");
+    }
+
+    /// Puts a program named `name` running the shell `script` in the
+    /// stubs' PATH.
+    pub fn install_program(&self, name: &str, script: &str) {
+        let program = self.bin_dir().join(name);
+        fs::write(&program, format!("#!/bin/sh\n{}\n", script)).expect("write program");
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).expect("make program executable");
+    }
+
+    /// Runs git-qwen with `args`, with the stub answering `answer`.
+    pub fn git_qwen(&self, args: &[&str], answer: &str) -> Output {
+        self.command(env!("CARGO_BIN_EXE_git-qwen"))
+            .args(args)
+            .env("QWEN_STUB_OUTPUT", answer)
+            .output()
+            .expect("run git-qwen")
+    }
+
+    /// Like `git_qwen`, with the environment variables `vars` set.
+    pub fn git_qwen_env(&self, args: &[&str], answer: &str, vars: &[(&str, &str)]) -> Output {
+        self.command(env!("CARGO_BIN_EXE_git-qwen"))
+            .args(args)
+            .env("QWEN_STUB_OUTPUT", answer)
+            .envs(vars.iter().copied())
+            .output()
+            .expect("run git-qwen")
+    }
+
+    /// Like `git_qwen`, run in `dir`, such as a subdirectory or another
+    /// worktree.
+    pub fn git_qwen_in(&self, dir: &Path, args: &[&str], answer: &str) -> Output {
+        self.command_in(dir, env!("CARGO_BIN_EXE_git-qwen"))
+            .args(args)
+            .env("QWEN_STUB_OUTPUT", answer)
+            .output()
+            .expect("run git-qwen")
+    }
+
+    /// Starts git-qwen with `args` in the background, with the stub
+    /// answering `answer`, capturing its stderr. Its stdin is not a
+    /// terminal, so it never stops to ask.
+    pub fn spawn_git_qwen(&self, args: &[&str], answer: &str) -> Child {
+        self.command(env!("CARGO_BIN_EXE_git-qwen"))
+            .args(args)
+            .env("QWEN_STUB_OUTPUT", answer)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("start git-qwen")
+    }
+
+    /// Like `git_qwen`, run on a terminal that is given `input`, for the
+    /// questions git-qwen only asks on one. What the terminal showed,
+    /// stdout and stderr together, is in the output's stdout.
+    pub fn git_qwen_in_terminal(&self, args: &[&str], answer: &str, input: &str) -> Output {
+        let program = env!("CARGO_BIN_EXE_git-qwen");
+        let mut command = self.command("script");
+        if cfg!(target_os = "linux") {
+            let quote = |arg: &str| format!("'{}'", arg.replace('\'', "'\\''"));
+            let line: Vec<String> = std::iter::once(program).chain(args.iter().copied()).map(quote).collect();
+            command.args(["-qec", &line.join(" "), "/dev/null"]);
+        } else {
+            command.args(["-q", "/dev/null", program]).args(args);
+        }
+        let mut child = command
+            .env("QWEN_STUB_OUTPUT", answer)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("start git-qwen on a terminal");
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(input.as_bytes())
+            .expect("write terminal input");
+        child.wait_with_output().expect("run git-qwen on a terminal")
+    }
+
+    /// The daemon's socket.
+    pub fn daemon_socket(&self) -> PathBuf {
+        self.home().join("git-qwen.sock")
+    }
+
+    /// Like `git_qwen`, with an editor that runs the shell `script` on the
+    /// message file, given as `$1`.
+    pub fn git_qwen_with_editor(&self, args: &[&str], answer: &str, script: &str) -> Output {
+        let editor = self.bin_dir().join("editor");
+        fs::write(&editor, format!("#!/bin/sh\n{}\n", script)).expect("write editor");
+        fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).expect("make editor executable");
+        self.command(env!("CARGO_BIN_EXE_git-qwen"))
+            .args(args)
+            .env("QWEN_STUB_OUTPUT", answer)
+            .env("GIT_EDITOR", editor)
+            .output()
+            .expect("run git-qwen")
+    }
+
+    /// Like `git_qwen`, with `GIT_EDITOR` set to `editor`, or unset for
+    /// None.
+    pub fn git_qwen_with_editor_command(&self, args: &[&str], answer: &str, editor: Option<&str>) -> Output {
+        let mut command = self.command(env!("CARGO_BIN_EXE_git-qwen"));
+        command.args(args).env("QWEN_STUB_OUTPUT", answer);
+        match editor {
+            Some(editor) => command.env("GIT_EDITOR", editor),
+            None => command.env_remove("GIT_EDITOR"),
+        };
+        command.output().expect("run git-qwen")
+    }
+
+    /// The full message of the HEAD commit.
+    pub fn last_message(&self) -> String {
+        self.git(&["log", "-1", "--format=%B"]).trim_end().to_string()
+    }
+
+    /// The prompt the stub was last given.
+    pub fn last_prompt(&self) -> String {
+        fs::read_to_string(self.prompt_file()).expect("the stub was run")
+    }
+
+    /// The arguments the stub was last given.
+    pub fn last_args(&self) -> Vec<String> {
+        let args = fs::read_to_string(self.prompt_file().with_extension("args")).expect("the stub was run");
+        args.lines().map(String::from).collect()
+    }
+
+    /// The files changed by the HEAD commit.
+    pub fn last_files(&self) -> Vec<String> {
+        self.git(&["show", "--name-only", "--format=", "HEAD"]).lines().map(String::from).collect()
+    }
+}
@@ -0,0 +1,83 @@
+//! `git-qwen daemon` runs generations, keeping the next qwen started.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+use std::io::Read;
+use std::process::Child;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A running daemon, killed if the test fails before stopping it.
+struct Daemon(Child);
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[test]
+fn commits_are_generated_by_a_running_daemon() {
+    let repo = TestRepo::new();
+    // The daemon runs qwen, so its environment has the stub's answer
+    let mut daemon = Daemon(repo.spawn_git_qwen(&["daemon"], "Add from the daemon"));
+    let started = Instant::now();
+    while !repo.daemon_socket().exists() {
+        assert!(started.elapsed() < Duration::from_secs(10), "the daemon did not start");
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    repo.stage("a.txt", "a\n");
+    let output = repo.git_qwen(&[], "Add locally");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add from the daemon");
+    repo.stage("b.txt", "b\n");
+    let output = repo.git_qwen(&[], "Add locally");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let output = repo.git_qwen(&["daemon", "--stop"], "");
+    assert!(output.status.success());
+    daemon.0.wait().expect("wait for the daemon");
+    let mut log = String::new();
+    daemon.0.stderr.take().unwrap().read_to_string(&mut log).unwrap();
+
+    assert!(log.contains("Running a newly started qwen"), "{}", log);
+    assert!(log.contains("Running a warm qwen"), "{}", log);
+    assert!(!repo.daemon_socket().exists());
+}
+
+#[test]
+fn backend_run_by_the_daemon_uses_the_clients_server() {
+    let repo = TestRepo::new();
+    repo.install_program(
+        "qwen",
+        "env > \"$QWEN_STUB_PROMPT.env\"\ncat > \"$QWEN_STUB_PROMPT\"\nprintf '%s\\n' \"$QWEN_STUB_OUTPUT\"",
+    );
+    repo.git(&["config", "qwen.localOnly", "true"]);
+    let _daemon = Daemon(repo.spawn_git_qwen(&["daemon"], "Add from the daemon"));
+    let started = Instant::now();
+    while !repo.daemon_socket().exists() {
+        assert!(started.elapsed() < Duration::from_secs(10), "the daemon did not start");
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    repo.stage("a.txt", "a\n");
+    let output = repo.git_qwen_env(&[], "Add locally", &[("OPENAI_BASE_URL", "http://127.0.0.1:8080/v1")]);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add from the daemon");
+    let env = fs::read_to_string(repo.path().parent().unwrap().join("prompt.env")).unwrap();
+    assert!(env.contains("OPENAI_BASE_URL=http://127.0.0.1:8080/v1\n"), "{}", env);
+}
+
+#[test]
+fn stop_without_a_daemon_fails() {
+    let repo = TestRepo::new();
+    let output = repo.git_qwen(&["daemon", "--stop"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No daemon is running"));
+}
@@ -0,0 +1,62 @@
+//! The diff the model reads finds renames and copies, and is shaped by
+//! `qwen.diffArgs`.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const CONTENT: &str = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\n";
+
+#[test]
+fn renamed_file_is_shown_as_a_rename() {
+    let repo = TestRepo::new();
+    repo.stage("old.txt", CONTENT);
+    repo.git(&["commit", "-q", "-m", "Add old.txt"]);
+    repo.git(&["mv", "old.txt", "new.txt"]);
+
+    let output = repo.git_qwen(&[], "Rename old.txt to new.txt");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("- new.txt (renamed from old.txt)"), "{}", prompt);
+    assert!(!prompt.contains("-three"), "{}", prompt);
+}
+
+#[test]
+fn renames_can_be_turned_off() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.renames", "false"]);
+    repo.stage("old.txt", CONTENT);
+    repo.git(&["commit", "-q", "-m", "Add old.txt"]);
+    repo.git(&["mv", "old.txt", "new.txt"]);
+
+    assert!(repo.git_qwen(&[], "Rename old.txt to new.txt").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("- old.txt (deleted)"), "{}", prompt);
+}
+
+#[test]
+fn whitespace_is_ignored_with_diff_args() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.diffArgs", "-w"]);
+    repo.stage("old.txt", CONTENT);
+    repo.git(&["commit", "-q", "-m", "Add old.txt"]);
+    repo.stage("old.txt", &CONTENT.replace("two", "  two").replace("six", "6"));
+
+    assert!(repo.git_qwen(&[], "Spell out six").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("+6\n"), "{}", prompt);
+    assert!(!prompt.contains("+  two"), "{}", prompt);
+}
+
+#[test]
+fn whitespace_only_change_is_still_described() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.diffArgs", "-w"]);
+    repo.stage("README", "  initial\n");
+
+    let output = repo.git_qwen(&[], "Indent the README");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(repo.last_prompt().contains("+  initial"));
+}
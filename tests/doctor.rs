@@ -0,0 +1,87 @@
+//! `git-qwen doctor` checks the environment and suggests fixes.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn reports_each_check_with_fixes_for_failures() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["doctor"], "OK");
+    assert!(output.status.success(), "doctor failed: {}", String::from_utf8_lossy(&output.stdout));
+    let report = String::from_utf8_lossy(&output.stdout);
+    for check in ["git", "repository", "backend", "model", "editor"] {
+        assert!(report.contains(&format!("[ok]   {}", check)), "{} not ok in:\n{}", check, report);
+    }
+    assert!(report.contains("[warn] hook: not installed"));
+    assert!(report.contains("fix: Run `git-qwen hook install`"));
+    assert!(repo.last_prompt().contains("OK"));
+
+    repo.git_qwen(&["hook", "install"], "");
+    let output = repo.git_qwen(&["doctor"], "OK");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("[ok]   hook"));
+}
+
+#[test]
+fn empty_model_answer_fails() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["doctor", "--json"], "");
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("report is JSON");
+    let model = report["checks"].as_array().unwrap().iter().find(|check| check["name"] == "model").unwrap();
+    assert_eq!(model["ok"], false);
+    assert!(model["fix"].as_str().unwrap().contains("qwen.timeout"));
+}
+
+#[test]
+fn backend_servers_are_checked_for_reachability() {
+    let repo = TestRepo::new();
+    repo.install_program("ollama", "cat > /dev/null; echo OK");
+    repo.git(&["config", "qwen.backends", "ollama"]);
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+
+    let output = repo.git_qwen_env(&["doctor", "--json"], "OK", &[("OLLAMA_HOST", address.as_str())]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("report is JSON");
+    assert_eq!(report["capabilities"]["network"][0]["address"], address.as_str());
+    assert_eq!(report["capabilities"]["network"][0]["reachable"], true);
+
+    drop(listener);
+    let output = repo.git_qwen_env(&["doctor"], "OK", &[("OLLAMA_HOST", address.as_str())]);
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(report.contains(&format!("[warn] network: cannot connect to ollama at {}", address)), "{}", report);
+}
+
+#[test]
+fn configured_backends_and_optional_capabilities_are_reported() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.backends", "ollama,mock"]);
+
+    let output = repo.git_qwen(&["doctor", "--json"], "OK");
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("report is JSON");
+    let capabilities = &report["capabilities"];
+    assert_eq!(capabilities["backends"][0]["name"], "ollama");
+    assert_eq!(capabilities["backends"][0]["path"], serde_json::Value::Null);
+    assert_eq!(capabilities["backends"][1]["name"], "mock");
+    assert_eq!(capabilities["clipboard"], serde_json::Value::Null);
+    let notes = report["notes"].as_array().unwrap();
+    assert!(notes.iter().any(|note| note == "clipboard unavailable; pr --copy cannot be used"), "{:?}", notes);
+}
+
+#[test]
+fn model_is_not_asked_without_remote_confirmation() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "--unset", "qwen.remoteConfirmed"]);
+
+    let output = repo.git_qwen(&["doctor", "--json"], "OK");
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("report is JSON");
+    let model = report["checks"].as_array().unwrap().iter().find(|check| check["name"] == "model").unwrap();
+    assert_eq!(model["ok"], false);
+    assert!(model["detail"].as_str().unwrap().contains("without confirmation"), "{}", model);
+    assert!(!repo.path().with_file_name("prompt").exists(), "the test question was sent");
+}
@@ -0,0 +1,44 @@
+//! The editor comes from git's settings and may have arguments.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+/// An editor that replaces the message with its first argument.
+const EDITOR: &str = "printf '%s\\n' \"$1\" > \"$2\"";
+
+#[test]
+fn editor_command_with_arguments() {
+    let repo = TestRepo::new();
+    repo.install_program("my-editor", EDITOR);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen_with_editor_command(&[], "Add file", Some("my-editor 'Add it by hand'"));
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add it by hand");
+}
+
+#[test]
+fn core_editor_is_used() {
+    let repo = TestRepo::new();
+    repo.install_program("my-editor", EDITOR);
+    repo.git(&["config", "core.editor", "my-editor 'From core.editor'"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen_with_editor_command(&[], "Add file", None);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "From core.editor");
+}
+
+#[test]
+fn crlf_and_byte_order_mark_from_the_editor_are_dropped() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let editor = "printf '\\357\\273\\277Add file\\r\\n\\r\\nWritten on Windows\\r\\n# a comment\\r\\n' > \"$1\"";
+    let output = repo.git_qwen_with_editor(&[], "Add file", editor);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add file\n\nWritten on Windows");
+}
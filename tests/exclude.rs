@@ -0,0 +1,55 @@
+//! Files matched by `.gitqwenignore` or `qwen.exclude` are committed as
+//! usual but their diffs stay out of the prompt.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+
+#[test]
+fn ignore_file_and_config_keep_diffs_out_of_the_prompt() {
+    let repo = TestRepo::new();
+    fs::create_dir(repo.path().join("vendor")).unwrap();
+    repo.write(".gitqwenignore", "# third-party code\nvendor/*\n");
+    repo.git(&["config", "qwen.exclude", "*.pb.go"]);
+    repo.stage("vendor/lib.js", "vendored body\n");
+    repo.stage("api.pb.go", "generated body\n");
+    repo.stage("main.rs", "fn main() {}\n");
+
+    let output = repo.git_qwen(&[], "Add main");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("fn main() {}"), "{}", prompt);
+    assert!(!prompt.contains("vendored body") && !prompt.contains("generated body"), "{}", prompt);
+    assert_eq!(repo.last_files(), vec!["api.pb.go", "main.rs", "vendor/lib.js"]);
+}
+
+#[test]
+fn later_negated_pattern_includes_a_file_again() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "--add", "qwen.exclude", "*.json"]);
+    repo.git(&["config", "--add", "qwen.exclude", "!package.json"]);
+    repo.stage("package.json", "{\"name\": \"kept\"}\n");
+    repo.stage("data.json", "{\"name\": \"dropped\"}\n");
+
+    assert!(repo.git_qwen(&[], "Add package").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("kept"), "{}", prompt);
+    assert!(!prompt.contains("dropped"), "{}", prompt);
+}
+
+#[test]
+fn only_names_are_sent_when_every_file_is_excluded() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.exclude", "*.txt"]);
+    repo.stage("notes.txt", "private notes\n");
+
+    let output = repo.git_qwen(&[], "Add notes");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("every changed file is excluded from the prompt"));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Changed files (contents excluded):\nnotes.txt\n"), "{}", prompt);
+    assert!(!prompt.contains("private notes"), "{}", prompt);
+}
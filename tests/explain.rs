@@ -0,0 +1,76 @@
+//! `git-qwen explain` explains the staged changes, a commit, or a range.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const EXPLANATION: &str = "The change adds a retry loop around requests.";
+
+#[test]
+fn staged_changes_are_explained() {
+    let repo = TestRepo::new();
+    repo.stage("retry.rs", "fn retry() {}\n");
+
+    let output = repo.git_qwen(&["explain"], EXPLANATION);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), format!("{}\n", EXPLANATION));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("The change is staged and not committed yet."), "{}", prompt);
+    assert!(prompt.contains("+fn retry() {}"), "{}", prompt);
+    assert_eq!(repo.git(&["rev-list", "--count", "HEAD"]), "1\n", "explain does not commit");
+}
+
+#[test]
+fn commit_is_explained_with_its_message() {
+    let repo = TestRepo::new();
+    repo.stage("retry.rs", "fn retry() {}\n");
+    repo.git(&["commit", "-q", "-m", "Retry requests"]);
+
+    let output = repo.git_qwen(&["explain", "HEAD"], EXPLANATION);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("The change is commit HEAD, whose message is:\n<data>\nRetry requests"), "{}", prompt);
+    assert!(prompt.contains("+fn retry() {}"), "{}", prompt);
+}
+
+#[test]
+fn range_is_explained_with_its_commits() {
+    let repo = TestRepo::new();
+    repo.stage("retry.rs", "fn retry() {}\n");
+    repo.git(&["commit", "-q", "-m", "Retry requests"]);
+    repo.stage("log.rs", "fn log() {}\n");
+    repo.git(&["commit", "-q", "-m", "Log requests"]);
+
+    let output = repo.git_qwen(&["explain", "HEAD~2..HEAD"], EXPLANATION);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("The change is made by these commits, oldest first:"), "{}", prompt);
+    assert!(prompt.contains("Retry requests") && prompt.contains("Log requests"), "{}", prompt);
+    assert!(prompt.contains("+fn retry() {}") && prompt.contains("+fn log() {}"), "{}", prompt);
+}
+
+#[test]
+fn nothing_to_explain_and_unknown_commits_are_errors() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["explain"], EXPLANATION);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No staged changes to explain"));
+
+    let output = repo.git_qwen(&["explain", "no-such-commit"], EXPLANATION);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Not a commit: no-such-commit"));
+    assert!(!repo.path().with_file_name("prompt").exists(), "the model was asked");
+}
+
+#[test]
+fn empty_answer_is_an_error() {
+    let repo = TestRepo::new();
+    repo.stage("retry.rs", "fn retry() {}\n");
+
+    let output = repo.git_qwen(&["explain"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("qwen returned an empty explanation"));
+}
@@ -0,0 +1,50 @@
+//! With `qwen.failSafe`, a failure to generate the message falls back to
+//! a plain `git commit`, but aborting the commit still aborts it.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+
+#[test]
+fn backend_failure_falls_back_to_git_commit() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.failSafe", "true"]);
+    repo.install_program("qwen", "exit 1");
+    repo.stage("a.txt", "a\n");
+
+    let output = repo.git_qwen_with_editor(&[], "unused", "echo 'Written by hand' > \"$1\"");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "git-qwen failed: {}", stderr);
+    assert!(stderr.contains("Falling back to plain git commit"), "{}", stderr);
+    assert_eq!(repo.last_message(), "Written by hand");
+}
+
+#[test]
+fn emptied_message_aborts_without_falling_back() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.failSafe", "true"]);
+    repo.stage("a.txt", "a\n");
+    let runs = repo.path().with_file_name("editor-runs");
+
+    let script = format!("echo run >> '{}'; : > \"$1\"", runs.display());
+    let output = repo.git_qwen_with_editor(&[], "Add a", &script);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(8), "{}", stderr);
+    assert!(!stderr.contains("Falling back"), "{}", stderr);
+    assert_eq!(fs::read_to_string(&runs).unwrap(), "run\n", "the editor opened once");
+    assert_eq!(repo.last_message(), "Initial commit");
+}
+
+#[test]
+fn without_fail_safe_backend_failure_aborts() {
+    let repo = TestRepo::new();
+    repo.install_program("qwen", "exit 1");
+    repo.stage("a.txt", "a\n");
+
+    let output = repo.git_qwen(&[], "unused");
+    assert_eq!(output.status.code(), Some(5), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Initial commit");
+}
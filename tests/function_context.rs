@@ -0,0 +1,55 @@
+//! Small hunks are widened to the whole function they change.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const PYTHON: &str = "import time
+
+
+def fetch(url, retries):
+    session = open_session()
+    for attempt in range(retries):
+        response = session.get(url)
+        if response.ok:
+            return response
+        log(attempt)
+        log(response.status)
+        log(url)
+        time.sleep(1)
+    raise TimeoutError(url)
+
+
+def unrelated():
+    return 42
+";
+
+#[test]
+fn one_line_change_shows_its_function() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.functionContext", "3"]);
+    repo.stage("fetch.py", PYTHON);
+    repo.git(&["commit", "-q", "-m", "Add fetch"]);
+    repo.stage("fetch.py", &PYTHON.replace("time.sleep(1)", "time.sleep(2 ** attempt)"));
+
+    assert!(repo.git_qwen(&[], "Back off exponentially between retries").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains(" def fetch(url, retries):\n"), "{}", prompt);
+    assert!(prompt.contains("     session = open_session()\n"), "{}", prompt);
+    assert!(prompt.contains("     raise TimeoutError(url)\n"), "{}", prompt);
+    assert!(!prompt.contains("return 42"), "{}", prompt);
+}
+
+#[test]
+fn hunks_keep_their_context_by_default() {
+    let repo = TestRepo::new();
+    repo.stage("fetch.py", PYTHON);
+    repo.git(&["commit", "-q", "-m", "Add fetch"]);
+    repo.stage("fetch.py", &PYTHON.replace("time.sleep(1)", "time.sleep(2 ** attempt)"));
+
+    assert!(repo.git_qwen(&[], "Back off exponentially between retries").status.success());
+    let prompt = repo.last_prompt();
+    assert!(!prompt.contains("session = open_session()"), "{}", prompt);
+}
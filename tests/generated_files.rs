@@ -0,0 +1,47 @@
+//! Lockfiles and generated files are collapsed to a one-line note in the
+//! prompt, so their bulk does not drown out the real change.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn lockfiles_are_collapsed_to_a_note() {
+    let repo = TestRepo::new();
+    repo.stage("Cargo.lock", "[[package]]\nname = \"lockfile-entry\"\n");
+    repo.stage("src.rs", "fn main() {}\n");
+
+    let output = repo.git_qwen(&[], "Add main");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("lockfile updated (+2 -0 lines, diff omitted)"), "{}", prompt);
+    assert!(!prompt.contains("lockfile-entry"), "{}", prompt);
+    assert!(prompt.contains("fn main() {}"), "{}", prompt);
+    assert_eq!(repo.last_files(), vec!["Cargo.lock", "src.rs"]);
+}
+
+#[test]
+fn minified_and_marked_files_are_collapsed() {
+    let repo = TestRepo::new();
+    repo.stage("app.min.js", "var minifiedBody=1;\n");
+    repo.stage("schema.rs", "// @generated by a tool\npub struct GeneratedBody;\n");
+    repo.stage("lib.rs", "pub fn real() {}\n");
+
+    assert!(repo.git_qwen(&[], "Add lib").status.success());
+    let prompt = repo.last_prompt();
+    assert_eq!(prompt.matches("generated file updated").count(), 2, "{}", prompt);
+    assert!(!prompt.contains("minifiedBody") && !prompt.contains("GeneratedBody"), "{}", prompt);
+    assert!(prompt.contains("pub fn real() {}"), "{}", prompt);
+}
+
+#[test]
+fn collapsing_can_be_turned_off() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.collapseGenerated", "false"]);
+    repo.stage("Cargo.lock", "name = \"lockfile-entry\"\n");
+
+    assert!(repo.git_qwen(&[], "Update the lockfile").status.success());
+    assert!(repo.last_prompt().contains("lockfile-entry"));
+}
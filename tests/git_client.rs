@@ -0,0 +1,71 @@
+//! The diff, amend, and commit stages run against a scripted git.
+
+use git_qwen::amend::{Amend, Mode};
+use git_qwen::config::Config;
+use git_qwen::git::MockGit;
+use git_qwen::{CommitExecutor, DiffSource};
+use std::path::Path;
+
+#[test]
+fn all_tracked_combines_staged_and_unstaged() {
+    let git = MockGit::new()
+        .respond(&["diff", "-M", "-C", "--cached", "--", "src"], "staged\n")
+        .respond(&["diff", "-M", "-C", "--", "src"], "unstaged\n");
+    let diff = DiffSource::AllTracked.read_with(&git, &["src".to_string()]).unwrap();
+    assert_eq!(diff, "staged\nunstaged\n");
+}
+
+#[test]
+fn amending_a_root_commit_shows_the_whole_commit() {
+    let git = MockGit::new()
+        .fail(&["rev-parse", "--verify", "--quiet", "HEAD^"], "no parent")
+        .respond(&["show", "-M", "-C", "--format=", "HEAD"], "root\n")
+        .respond(&["diff", "-M", "-C", "--cached"], "staged\n");
+    let diff = DiffSource::Amend { include_all: false }.read_with(&git, &[]).unwrap();
+    assert_eq!(diff, "root\nstaged\n");
+    assert!(!git.calls().contains(&vec!["diff".to_string(), "-M".to_string(), "-C".to_string()]));
+}
+
+#[test]
+fn amend_keeps_the_original_trailers() {
+    let git = MockGit::new()
+        .respond(&["log", "-1", "--format=%B", "HEAD"], "Fix parser\n\nSigned-off-by: A <a@example.com>\n")
+        .respond(&["diff", "-M", "-C", "--cached"], "");
+    let amend = Amend::load_with(&git, false, &[], &Config::default()).unwrap();
+    assert_eq!(amend.message, "Fix parser");
+    assert_eq!(amend.trailers, vec!["Signed-off-by: A <a@example.com>".to_string()]);
+    assert_eq!(amend.mode, Mode::Revise);
+}
+
+#[test]
+fn commit_passes_the_message_file_and_exit_code() {
+    let git = MockGit::new().exit_code(1);
+    let executor = CommitExecutor::new(&["--signoff".to_string()]).with_config(Some("core.commentChar=;".to_string()));
+    let code = executor.execute(&git, Some(Path::new("MSG"))).unwrap();
+    assert_eq!(code, 1);
+    assert_eq!(
+        git.calls(),
+        vec![vec!["-c", "core.commentChar=;", "commit", "-F", "MSG", "--signoff"]]
+    );
+}
+
+#[test]
+fn rename_detection_follows_the_config() {
+    let git = MockGit::new()
+        .respond(&["config", "qwen.renames"], "false\n")
+        .respond(&["diff", "--no-renames", "--cached"], "staged\n");
+    assert_eq!(DiffSource::Staged.read_with(&git, &[]).unwrap(), "staged\n");
+
+    let git = MockGit::new()
+        .respond(&["config", "qwen.renameThreshold"], "70%\n")
+        .respond(&["diff", "-M70%", "-C70%", "--cached"], "staged\n");
+    assert_eq!(DiffSource::Staged.read_with(&git, &[]).unwrap(), "staged\n");
+}
+
+#[test]
+fn diff_args_add_only_display_options() {
+    let git = MockGit::new()
+        .respond(&["config", "qwen.diffArgs"], "-U5 --histogram --output=leak -w\n")
+        .respond(&["diff", "-M", "-C", "-U5", "--histogram", "-w", "--cached"], "staged\n");
+    assert_eq!(DiffSource::Staged.read_with(&git, &[]).unwrap(), "staged\n");
+}
@@ -0,0 +1,56 @@
+//! Generated messages are logged to `.git/qwen-history.jsonl` with what
+//! became of them, and `git-qwen history` lists them.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use serde_json::Value;
+use std::fs;
+
+fn entries(repo: &TestRepo) -> Vec<Value> {
+    let log = fs::read_to_string(repo.path().join(".git/qwen-history.jsonl")).expect("read history log");
+    log.lines().map(|line| serde_json::from_str(line).expect("entry is JSON")).collect()
+}
+
+#[test]
+fn accepted_and_edited_messages_are_logged() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "a\n");
+    let output = repo.git_qwen(&[], "Add a");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    repo.stage("b.txt", "b\n");
+    let output = repo.git_qwen_with_editor(&[], "Add b", "echo 'Add the b file' > \"$1\"");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let entries = entries(&repo);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["outcome"], "accepted");
+    assert_eq!(entries[0]["generated"].as_str().unwrap().trim(), "Add a");
+    assert!(entries[0]["prompt"].as_str().unwrap().contains("a.txt"));
+    assert_eq!(entries[1]["outcome"], "edited");
+    assert_eq!(entries[1]["final"], "Add the b file");
+    assert_eq!(entries[1]["commit"].as_str().unwrap(), repo.git(&["rev-parse", "HEAD"]).trim());
+
+    let output = repo.git_qwen(&["history"], "");
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = listing.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("edited") && lines[0].ends_with("Add the b file"));
+    assert!(lines[1].contains("accepted") && lines[1].ends_with("Add a"));
+}
+
+#[test]
+fn emptied_message_is_logged_as_rejected() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "a\n");
+    let output = repo.git_qwen_with_editor(&[], "Add a", ": > \"$1\"");
+    assert!(!output.status.success());
+
+    let entries = entries(&repo);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["outcome"], "rejected");
+    assert!(entries[0]["commit"].is_null());
+}
@@ -0,0 +1,50 @@
+//! `git-qwen hook run`, git's prepare-commit-msg hook, writes the message
+//! into the file git opens, and never stops the commit.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+
+const TEMPLATE: &str = "\n# Please enter the commit message for your changes.\n";
+
+#[test]
+fn generated_message_goes_above_git_template() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+    let file = repo.path().with_file_name("COMMIT_EDITMSG");
+    fs::write(&file, TEMPLATE).unwrap();
+
+    let output = repo.git_qwen(&["hook", "run", file.to_str().unwrap()], "Add file");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(&file).unwrap(), format!("Add file\n{}", TEMPLATE));
+}
+
+#[test]
+fn message_file_is_untouched_when_the_backend_fails() {
+    let repo = TestRepo::new();
+    repo.install_program("qwen", "cat > /dev/null; echo 'quota exceeded' >&2; exit 1");
+    repo.stage("file.txt", "content\n");
+    let file = repo.path().with_file_name("COMMIT_EDITMSG");
+    fs::write(&file, TEMPLATE).unwrap();
+
+    let output = repo.git_qwen(&["hook", "run", file.to_str().unwrap()], "unused");
+    assert!(output.status.success(), "a failing hook would abort the commit");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("git-qwen: "), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(&file).unwrap(), TEMPLATE);
+}
+
+#[test]
+fn message_git_already_has_is_left_alone() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+    let file = repo.path().with_file_name("COMMIT_EDITMSG");
+    fs::write(&file, "Message from -m\n").unwrap();
+
+    let output = repo.git_qwen(&["hook", "run", file.to_str().unwrap(), "message"], "Add file");
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "Message from -m\n");
+    assert!(!repo.path().with_file_name("prompt").exists(), "the model was asked");
+}
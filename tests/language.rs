@@ -0,0 +1,41 @@
+//! `qwen.language` and `--lang` ask for the message in another language.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn configured_language_is_asked_for() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.language", "Japanese"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], "ファイルを追加する\n\n空の入力で落ちないように、読む前に長さを確かめる。");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(repo.last_prompt().contains("\nWrite the commit message in Japanese. Keep code identifiers"));
+    assert_eq!(repo.last_message(), "ファイルを追加する\n\n空の入力で落ちないように、読む前に長さを確かめる。");
+}
+
+#[test]
+fn lang_overrides_the_setting() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.language", "Japanese"]);
+    repo.stage("file.txt", "content\n");
+
+    assert!(repo.git_qwen(&["--lang", "German"], "Datei hinzufügen").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Write the commit message in German."), "{}", prompt);
+    assert!(!prompt.contains("Japanese"), "{}", prompt);
+    assert_eq!(repo.last_message(), "Datei hinzufügen");
+}
+
+#[test]
+fn english_is_the_default() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    assert!(repo.git_qwen(&[], "Add file").status.success());
+    assert!(!repo.last_prompt().contains("Write the commit message in"));
+}
@@ -0,0 +1,67 @@
+//! `git-qwen learn` stores the commit style of the history in
+//! `.git/qwen-style.json`, and later prompts describe it.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+fn repo_with_history() -> TestRepo {
+    let repo = TestRepo::new();
+    for (file, subject) in [
+        ("a.rs", "feat: add the parser"),
+        ("b.rs", "fix: handle empty input"),
+        ("c.rs", "feat: add the printer"),
+        ("d.rs", "docs: describe the options"),
+        ("e.rs", "fix(parser): reject stray commas"),
+    ] {
+        repo.stage(file, "content\n");
+        repo.git(&["commit", "-q", "-m", subject]);
+    }
+    repo
+}
+
+#[test]
+fn learn_saves_the_profile_and_later_prompts_follow_it() {
+    let repo = repo_with_history();
+
+    let output = repo.git_qwen(&["learn"], "Subjects are written in English, in the imperative.");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Subjects start with a Conventional Commits type, most often fix, feat, docs."), "{}", stdout);
+    assert!(stdout.contains("The first word of the summary is lowercase."), "{}", stdout);
+    assert!(stdout.contains("Subjects are written in English, in the imperative."), "{}", stdout);
+    assert!(repo.last_prompt().contains("feat: add the parser"));
+    assert!(repo.path().join(".git/qwen-style.json").exists());
+
+    repo.stage("f.rs", "content\n");
+    assert!(repo.git_qwen(&[], "feat: add f").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("This repository's commit style, learned from its history."), "{}", prompt);
+    assert!(prompt.contains("- Subjects are written in English, in the imperative.\n"), "{}", prompt);
+}
+
+#[test]
+fn backend_failure_saves_the_statistics_only() {
+    let repo = repo_with_history();
+    repo.install_program("qwen", "cat > /dev/null; echo 'quota exceeded' >&2; exit 1");
+
+    let output = repo.git_qwen(&["learn"], "unused");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("saving the statistics only"));
+    let profile = std::fs::read_to_string(repo.path().join(".git/qwen-style.json")).unwrap();
+    assert!(profile.contains("\"conventionalShare\": 0.83"), "{}", profile);
+    assert!(!profile.contains("\"notes\""), "{}", profile);
+}
+
+#[test]
+fn too_few_commits_is_an_error() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["learn"], "unused");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Too few commits to learn a style from"));
+    assert!(!repo.path().join(".git/qwen-style.json").exists());
+    assert!(!repo.path().with_file_name("prompt").exists());
+}
@@ -0,0 +1,69 @@
+//! `qwen.subjectLimit` and `qwen.wrapWidth` set the subject length asked
+//! for and checked, and the width the body is wrapped at.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const BODY: &str = "The parser read past the end of the buffer on empty input and crashed, so it now checks the length before reading anything at all.";
+
+#[test]
+fn defaults_are_fifty_and_seventy_two() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    assert!(repo.git_qwen(&[], &format!("Fix the crash\n\n{}", BODY)).status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("aim for 50 characters max"), "{}", prompt);
+    assert!(prompt.contains("wrap all lines at 72 characters"), "{}", prompt);
+    let message = repo.last_message();
+    assert!(message.lines().count() > 3, "{}", message);
+    assert!(message.lines().all(|line| line.chars().count() <= 72), "{}", message);
+}
+
+#[test]
+fn limits_are_configurable() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.subjectLimit", "72"]);
+    repo.git(&["config", "qwen.wrapWidth", "40"]);
+    repo.stage("file.txt", "content\n");
+    let subject = "Fix the crash of the parser on empty input from the network";
+
+    let output = repo.git_qwen(&[], &format!("{}\n\n{}", subject, BODY));
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("subject-max-length"));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("aim for 72 characters max"), "{}", prompt);
+    assert!(prompt.contains("wrap all lines at 40 characters"), "{}", prompt);
+    let message = repo.last_message();
+    assert!(message.starts_with(&format!("{}\n\n", subject)), "{}", message);
+    assert!(message.lines().skip(2).all(|line| line.chars().count() <= 40), "{}", message);
+}
+
+#[test]
+fn none_turns_the_limits_off() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.subjectLimit", "none"]);
+    repo.git(&["config", "qwen.wrapWidth", "0"]);
+    repo.stage("file.txt", "content\n");
+
+    assert!(repo.git_qwen(&[], &format!("Fix the crash\n\n{}", BODY)).status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("1. First line is the subject: a short summary in the imperative mood"), "{}", prompt);
+    assert!(prompt.contains("write each paragraph on a single line"), "{}", prompt);
+    assert_eq!(repo.last_message(), format!("Fix the crash\n\n{}", BODY));
+}
+
+#[test]
+fn invalid_limit_falls_back_to_the_default_with_a_warning() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.wrapWidth", "wide"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], "Fix the crash");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: ignoring qwen.wrapWidth: expected a number or 'none', got 'wide'"));
+    assert!(repo.last_prompt().contains("wrap all lines at 72 characters"));
+}
@@ -0,0 +1,58 @@
+//! Two git-qwen commits in one repository do not share the message file,
+//! and the repository lock keeps them from running at once.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn editor_gets_a_private_message_file() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+    fs::write(repo.path().join(".git/COMMIT_EDITMSG"), "Someone else's message\n").unwrap();
+
+    // The editor makes the file's path the message
+    let output = repo.git_qwen_with_editor(&[], "Add file", "echo \"$1\" > \"$1\"");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let path = repo.last_message();
+    assert!(path.ends_with("/COMMIT_EDITMSG"), "{}", path);
+    assert!(!path.ends_with(".git/COMMIT_EDITMSG"), "{}", path);
+    let leftovers: Vec<_> = fs::read_dir(repo.path().join(".git"))
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("qwen-edit-"))
+        .collect();
+    assert!(leftovers.is_empty(), "the message file was left behind");
+    assert!(!repo.path().join(".git/qwen.lock").exists());
+}
+
+#[test]
+fn running_git_qwen_holds_the_lock() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+    fs::write(repo.path().join(".git/qwen.lock"), std::process::id().to_string()).unwrap();
+
+    let output = repo.git_qwen(&[], "Add file");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Another git-qwen is committing"));
+    assert_eq!(repo.last_message(), "Initial commit");
+}
+
+#[test]
+fn lock_of_a_killed_git_qwen_is_taken_over() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+    let mut exited = Command::new("true").spawn().unwrap();
+    exited.wait().unwrap();
+    fs::write(repo.path().join(".git/qwen.lock"), exited.id().to_string()).unwrap();
+
+    let output = repo.git_qwen(&[], "Add file");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add file");
+    assert!(!repo.path().join(".git/qwen.lock").exists());
+}
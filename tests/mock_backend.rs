@@ -0,0 +1,32 @@
+//! The mock backend writes a message from the diff without running qwen.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn mock_message_is_derived_from_the_diff() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "one\ntwo\n");
+
+    let output = repo.git_qwen(&["--backend", "mock"], "Answer from qwen");
+    assert!(
+        output.status.success(),
+        "git-qwen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(repo.last_message(), "Add file.txt\n\n- file.txt: 2 insertion(s), 0 deletion(s)");
+    assert!(!repo.path().join("../prompt").exists(), "qwen was run");
+}
+
+#[test]
+fn unknown_backend_is_an_error() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&["--backend", "other"], "Add file");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown backend 'other'"));
+}
@@ -0,0 +1,58 @@
+//! The model is chosen with `--model`, `qwen.modelName`, or by the size of
+//! the diff, and passed to qwen with `-m`.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+fn model_arg(repo: &TestRepo) -> Option<String> {
+    let args = repo.last_args();
+    let position = args.iter().position(|arg| arg == "-m")?;
+    args.get(position + 1).cloned()
+}
+
+#[test]
+fn no_model_is_passed_by_default() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "a\n");
+    let output = repo.git_qwen(&[], "Add a");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(model_arg(&repo), None);
+}
+
+#[test]
+fn model_flag_overrides_config() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.modelName", "qwen-configured"]);
+    repo.stage("a.txt", "a\n");
+    repo.git_qwen(&[], "Add a");
+    assert_eq!(model_arg(&repo).as_deref(), Some("qwen-configured"));
+
+    repo.stage("b.txt", "b\n");
+    repo.git_qwen(&["--model", "qwen-flag"], "Add b");
+    assert_eq!(model_arg(&repo).as_deref(), Some("qwen-flag"));
+}
+
+#[test]
+fn diff_size_picks_the_small_or_large_model() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.modelName", "qwen-medium"]);
+    repo.git(&["config", "qwen.smallModel", "qwen-small"]);
+    repo.git(&["config", "qwen.largeModel", "qwen-large"]);
+    repo.git(&["config", "qwen.smallDiffLines", "2"]);
+    repo.git(&["config", "qwen.largeDiffLines", "10"]);
+
+    repo.stage("small.txt", "one\n");
+    repo.git_qwen(&[], "Add small");
+    assert_eq!(model_arg(&repo).as_deref(), Some("qwen-small"));
+
+    repo.stage("medium.txt", "1\n2\n3\n4\n5\n");
+    repo.git_qwen(&[], "Add medium");
+    assert_eq!(model_arg(&repo).as_deref(), Some("qwen-medium"));
+
+    repo.stage("large.txt", &"line\n".repeat(20));
+    repo.git_qwen(&[], "Add large");
+    assert_eq!(model_arg(&repo).as_deref(), Some("qwen-large"));
+}
@@ -0,0 +1,61 @@
+//! `git-qwen note` attaches an explanation of a commit as a git note.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const EXPLANATION: &str = "The retry loop backs off exponentially.";
+
+fn repo_with_commit() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.stage("retry.rs", "fn retry() {}\n");
+    repo.git(&["commit", "-q", "-m", "Retry requests"]);
+    repo
+}
+
+#[test]
+fn explanation_is_added_as_a_note() {
+    let repo = repo_with_commit();
+
+    let output = repo.git_qwen(&["note"], EXPLANATION);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.git(&["notes", "show", "HEAD"]), format!("{}\n", EXPLANATION));
+    assert_eq!(repo.last_message(), "Retry requests");
+    assert!(repo.last_prompt().contains("+fn retry() {}"));
+}
+
+#[test]
+fn existing_note_is_kept_unless_forced() {
+    let repo = repo_with_commit();
+    repo.git(&["notes", "add", "-m", "Written by hand", "HEAD"]);
+
+    let output = repo.git_qwen(&["note", "HEAD"], EXPLANATION);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("HEAD already has a note; use --force to replace it"));
+    assert_eq!(repo.git(&["notes", "show", "HEAD"]), "Written by hand\n");
+
+    assert!(repo.git_qwen(&["note", "HEAD", "--force"], EXPLANATION).status.success());
+    assert_eq!(repo.git(&["notes", "show", "HEAD"]), format!("{}\n", EXPLANATION));
+}
+
+#[test]
+fn no_note_is_added_when_the_backend_fails() {
+    let repo = repo_with_commit();
+    repo.install_program("qwen", "cat > /dev/null; echo 'quota exceeded' >&2; exit 1");
+
+    let output = repo.git_qwen(&["note"], "unused");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to generate explanation"));
+    assert!(repo.try_git(&["notes", "show", "HEAD"]).stdout.is_empty());
+}
+
+#[test]
+fn unknown_commit_is_an_error() {
+    let repo = repo_with_commit();
+
+    let output = repo.git_qwen(&["note", "no-such-commit"], EXPLANATION);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Not a commit: no-such-commit"));
+}
@@ -0,0 +1,104 @@
+//! The model's raw answer is cleaned up into a bare commit message.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+/// Commits a change with the stub answering `answer` and returns the
+/// resulting commit message.
+fn commit_with_answer(answer: &str) -> String {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], answer);
+    assert!(
+        output.status.success(),
+        "git-qwen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    repo.last_message()
+}
+
+#[test]
+fn plain_message_is_kept() {
+    assert_eq!(
+        commit_with_answer("Add file\n\nThe file holds the content."),
+        "Add file\n\nThe file holds the content."
+    );
+}
+
+#[test]
+fn reasoning_block_is_removed() {
+    assert_eq!(
+        commit_with_answer("<think>\nThe diff adds a file.\n\nSo: add.\n</think>\n\nAdd file\n\nIt is new."),
+        "Add file\n\nIt is new."
+    );
+}
+
+#[test]
+fn fence_with_language_tag_is_unwrapped() {
+    assert_eq!(
+        commit_with_answer("Here is the commit message:\n\n```text\nAdd file\n\nIt is new.\n```\n\nLet me know if you want changes."),
+        "Add file\n\nIt is new."
+    );
+}
+
+#[test]
+fn short_single_word_subject_survives_fence_handling() {
+    assert_eq!(commit_with_answer("```\nRefactor\n```"), "Refactor");
+}
+
+#[test]
+fn chat_preamble_is_removed() {
+    assert_eq!(
+        commit_with_answer("Sure! Here's a commit message for these changes:\n\nAdd file\n\nIt is new."),
+        "Add file\n\nIt is new."
+    );
+}
+
+#[test]
+fn label_before_subject_is_removed() {
+    assert_eq!(
+        commit_with_answer("**Commit message:** Add file\n\nIt is new."),
+        "Add file\n\nIt is new."
+    );
+}
+
+#[test]
+fn trailing_explanation_is_removed() {
+    assert_eq!(
+        commit_with_answer("Add file\n\nIt is new.\n\nThis commit message follows the conventional format."),
+        "Add file\n\nIt is new."
+    );
+}
+
+#[test]
+fn bold_subject_is_unwrapped() {
+    assert_eq!(commit_with_answer("**Add file**\n\nIt is new."), "Add file\n\nIt is new.");
+}
+
+#[test]
+fn json_answer_is_parsed() {
+    assert_eq!(
+        commit_with_answer("{\"subject\": \"Add file\", \"body\": \"It is new.\\n\\nIt holds the content.\"}"),
+        "Add file\n\nIt is new.\n\nIt holds the content."
+    );
+}
+
+#[test]
+fn fenced_json_with_empty_body_is_parsed() {
+    assert_eq!(
+        commit_with_answer("```json\n{\"subject\": \"Add file\", \"body\": \"\"}\n```"),
+        "Add file"
+    );
+}
+
+#[test]
+fn json_body_given_as_paragraph_list_is_joined() {
+    assert_eq!(
+        commit_with_answer("{\"subject\": \"Add file\", \"body\": [\"It is new.\", \"It holds the content.\"]}"),
+        "Add file\n\nIt is new.\n\nIt holds the content."
+    );
+}
@@ -0,0 +1,47 @@
+//! `--patch-file` describes a patch from a file instead of the index.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const PATCH: &str = "From 1234 Mon Sep 17 00:00:00 2001
+From: Someone <someone@example.com>
+Subject: [PATCH] Old subject
+
+Old body.
+---
+ greeting.txt | 1 +
+ 1 file changed, 1 insertion(+)
+
+diff --git a/greeting.txt b/greeting.txt
+new file mode 100644
+--- /dev/null
++++ b/greeting.txt
+@@ -0,0 +1 @@
++hello
+";
+
+#[test]
+fn message_is_printed_for_the_patch() {
+    let repo = TestRepo::new();
+    repo.write("change.patch", PATCH);
+
+    let output = repo.git_qwen(&["--patch-file", "change.patch"], "Add a greeting");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Add a greeting\n");
+    assert_eq!(repo.last_message(), "Initial commit");
+
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("+hello"));
+    assert!(!prompt.contains("Old subject"));
+}
+
+#[test]
+fn missing_patch_file_is_an_error() {
+    let repo = TestRepo::new();
+    let output = repo.git_qwen(&["--patch-file", "missing.patch"], "Add a greeting");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to read missing.patch"));
+}
@@ -0,0 +1,47 @@
+//! `--patch` sends the hunks to be described through the same exclusion,
+//! masking, and fencing as the commit prompt.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn hunks_are_masked_excluded_and_fenced() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.exclude", "*.key"]);
+    repo.stage("config.env", "name=app\n");
+    repo.stage("server.key", "old\n");
+    repo.git(&["commit", "-q", "-m", "Add config"]);
+    repo.write("config.env", "name=app\npassword=hunter2hunter2\n");
+    repo.write("server.key", "private stuff\n");
+
+    let answer = r#"{"hunks": ["Set the password", "Replace the key"]}"#;
+    let output = repo.git_qwen_in_terminal(&["--patch"], answer, "q\n");
+    let shown = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "quitting commits nothing: {}", shown);
+    assert!(shown.contains("qwen: Set the password"), "{}", shown);
+
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("password=[REDACTED]"), "{}", prompt);
+    assert!(!prompt.contains("hunter2"), "{}", prompt);
+    assert!(prompt.contains("Hunk 2 (server.key):\n(contents excluded)"), "{}", prompt);
+    assert!(!prompt.contains("private stuff"), "{}", prompt);
+    assert!(prompt.ends_with("(contents excluded)\n</data>\n"), "{}", prompt);
+    assert!(prompt.contains("<data>\nHunk 1 (config.env):"), "{}", prompt);
+    assert!(prompt.starts_with("Text between <data> and </data>"), "{}", prompt);
+}
+
+#[test]
+fn hunks_are_not_sent_before_the_remote_confirmation() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "--unset", "qwen.remoteConfirmed"]);
+    repo.write("README", "changed\n");
+
+    let output = repo.git_qwen_in_terminal(&["--patch"], r#"{"hunks": ["Change"]}"#, "n\n");
+    let shown = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success());
+    assert!(shown.contains("Send this repository's changes?"), "{}", shown);
+    assert!(!repo.path().with_file_name("prompt").exists(), "nothing was sent");
+}
@@ -0,0 +1,26 @@
+//! The library's pipeline stages work on their own, without the command.
+
+use git_qwen::config::Config;
+use git_qwen::{DiffSource, MessageFormatter};
+
+#[test]
+fn formatter_strips_fences_and_separates_the_body() {
+    let config = Config::default();
+    let message = MessageFormatter::new(&config).format("```\nAdd parser\nIt reads the config file.\n```");
+    assert_eq!(message, "Add parser\n\nIt reads the config file.");
+}
+
+#[test]
+fn layout_wraps_the_body_only() {
+    let message = MessageFormatter::layout("Add a subject that is longer than the width\n\n\none two three four", Some(10));
+    assert_eq!(message, "Add a subject that is longer than the width\n\none two\nthree four");
+}
+
+#[test]
+fn diff_source_follows_commit_options() {
+    assert_eq!(DiffSource::for_commit(false, false), DiffSource::Staged);
+    assert_eq!(DiffSource::for_commit(true, false), DiffSource::AllTracked);
+    assert_eq!(DiffSource::for_commit(true, true), DiffSource::Amend { include_all: true });
+    let text = DiffSource::Text("diff --git a/x b/x\n".to_string());
+    assert_eq!(text.read(&[]).unwrap(), "diff --git a/x b/x\n");
+}
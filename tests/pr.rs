@@ -0,0 +1,92 @@
+//! `git-qwen pr` writes a pull request title and description for the
+//! current branch, and can open the pull request with gh or glab.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+
+const ANSWER: &str = r#"{"title": "Retry failed requests", "body": "Requests are retried.\n\n## Testing\nRun the tests."}"#;
+
+fn feature_branch() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.git(&["checkout", "-q", "-b", "retry"]);
+    repo.stage("retry.txt", "retry body\n");
+    repo.git(&["commit", "-q", "-m", "Retry failed requests"]);
+    repo.install_program("gh", "printf '%s\\n' \"$@\" > \"$QWEN_STUB_PROMPT.forge\"");
+    repo.install_program("glab", "printf '%s\\n' glab \"$@\" > \"$QWEN_STUB_PROMPT.forge\"");
+    repo
+}
+
+fn forge_args(repo: &TestRepo) -> String {
+    fs::read_to_string(repo.path().with_file_name("prompt.forge")).unwrap()
+}
+
+#[test]
+fn title_and_description_are_printed() {
+    let repo = feature_branch();
+
+    let output = repo.git_qwen(&["pr"], ANSWER);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Retry failed requests\n\nRequests are retried.\n\n## Testing\nRun the tests.\n"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Describing 1 commit(s) since main..."));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Retry failed requests") && prompt.contains("+retry body"), "{}", prompt);
+    assert!(!repo.path().with_file_name("prompt.forge").exists());
+}
+
+#[test]
+fn plain_answer_is_split_into_title_and_body() {
+    let repo = feature_branch();
+
+    let output = repo.git_qwen(&["pr", "--base", "main"], "# Retry failed requests\n\nRequests are retried.");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Retry failed requests\n\nRequests are retried.\n");
+}
+
+#[test]
+fn create_opens_the_reviewed_pull_request_with_gh() {
+    let repo = feature_branch();
+
+    let output = repo.git_qwen_with_editor(&["pr", "--create", "--draft"], ANSWER, "sed -i '1s/.*/Retry requests/' \"$1\"");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        forge_args(&repo),
+        "pr\ncreate\n--title\nRetry requests\n--body\nRequests are retried.\n\n## Testing\nRun the tests.\n--base\nmain\n--draft\n"
+    );
+}
+
+#[test]
+fn gitlab_remotes_use_glab() {
+    let repo = feature_branch();
+    repo.git(&["remote", "add", "origin", "https://gitlab.com/example/project.git"]);
+
+    let output = repo.git_qwen(&["pr", "--create"], ANSWER);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(forge_args(&repo).starts_with("glab\nmr\ncreate\n--title\nRetry failed requests\n"), "{}", forge_args(&repo));
+    assert!(forge_args(&repo).ends_with("--target-branch\nmain\n"), "{}", forge_args(&repo));
+}
+
+#[test]
+fn emptied_title_creates_nothing() {
+    let repo = feature_branch();
+
+    let output = repo.git_qwen_with_editor(&["pr", "--create"], ANSWER, ": > \"$1\"");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Empty pull request title; not creating it"));
+    assert!(!repo.path().with_file_name("prompt.forge").exists());
+}
+
+#[test]
+fn branch_without_new_commits_is_an_error() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["pr", "--base", "main"], ANSWER);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("The current branch has no commits that are not on main"));
+}
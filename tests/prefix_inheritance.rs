@@ -0,0 +1,58 @@
+//! The subject prefix shared by recent commits on the same files is asked
+//! for, unless it is overridden with `--prefix` or turned off.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const INHERITED: &str = "Start the subject with the prefix \"net/http:\" followed by a space";
+
+fn repo_with_prefixed_history() -> TestRepo {
+    let repo = TestRepo::new();
+    for (n, subject) in ["net/http: add client", "net/http: retry requests", "net/http: log errors"].iter().enumerate() {
+        repo.stage("client.go", &format!("version {}\n", n));
+        repo.git(&["commit", "-q", "-m", subject]);
+    }
+    repo.stage("client.go", "version 3\n");
+    repo
+}
+
+#[test]
+fn prefix_of_recent_commits_on_the_same_files_is_reused() {
+    let repo = repo_with_prefixed_history();
+
+    assert!(repo.git_qwen(&[], "net/http: bump version").status.success());
+    assert!(repo.last_prompt().contains(INHERITED), "{}", repo.last_prompt());
+}
+
+#[test]
+fn files_without_a_convention_get_no_prefix_rule() {
+    let repo = repo_with_prefixed_history();
+    repo.git(&["reset", "-q"]);
+    repo.stage("other.txt", "unrelated\n");
+
+    assert!(repo.git_qwen(&[], "Add other").status.success());
+    assert!(!repo.last_prompt().contains("Start the subject with the prefix"), "{}", repo.last_prompt());
+}
+
+#[test]
+fn prefix_can_be_overridden_or_turned_off() {
+    let repo = repo_with_prefixed_history();
+
+    assert!(repo.git_qwen(&["--prefix", "[http]", "--print"], "[http] Bump version").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Start the subject with the prefix \"[http]\""), "{}", prompt);
+    assert!(!prompt.contains(INHERITED), "{}", prompt);
+
+    assert!(repo.git_qwen(&["--no-prefix", "--print"], "Bump version").status.success());
+    assert!(!repo.last_prompt().contains("Start the subject with the prefix"));
+
+    repo.git(&["config", "qwen.inheritPrefix", "false"]);
+    assert!(repo.git_qwen(&["--print"], "Bump version").status.success());
+    assert!(!repo.last_prompt().contains("Start the subject with the prefix"));
+
+    let output = repo.git_qwen(&["--prefix", "x:", "--no-prefix"], "unused");
+    assert!(!output.status.success(), "--prefix and --no-prefix conflict");
+}
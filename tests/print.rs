@@ -0,0 +1,51 @@
+//! `--print` and `--output json` show the message without committing.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use serde_json::Value;
+
+#[test]
+fn print_shows_the_message_without_committing() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&["--print", "--co-author", "Other <other@example.com>"], "Add file");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Add file\n\nCo-authored-by: Other <other@example.com>\n"
+    );
+    assert_eq!(repo.last_message(), "Initial commit");
+}
+
+#[test]
+fn json_output_has_the_parts_of_the_message() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.modelName", "qwen-test"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&["--output", "json", "--trailer", "Reviewed-by=Someone"], "Add file\n\nIt holds content.");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let result: Value = serde_json::from_slice(&output.stdout).expect("output is JSON");
+    assert_eq!(result["subject"], "Add file");
+    assert_eq!(result["body"], "It holds content.");
+    assert_eq!(result["trailers"], serde_json::json!(["Reviewed-by: Someone"]));
+    assert_eq!(result["backend"], "qwen");
+    assert_eq!(result["model"], "qwen-test");
+    assert!(result["tokens"]["prompt"].as_u64().unwrap() > 0);
+    assert!(result["duration_ms"].is_u64());
+    assert_eq!(repo.last_message(), "Initial commit");
+}
+
+#[test]
+fn unknown_output_format_is_an_error() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&["--output", "yaml"], "Add file");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected text or json"));
+}
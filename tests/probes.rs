@@ -0,0 +1,38 @@
+//! `qwen.probe` commands record environment facts as trailers.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn probe_output_becomes_a_trailer() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "--add", "qwen.probe", "Built-with=printf 'rustc 1.79.0\\nmore\\n'"]);
+    repo.git(&["config", "--add", "qwen.probe", "Config-profile=echo release"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], "Add file");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add file\n\nBuilt-with: rustc 1.79.0\nConfig-profile: release");
+}
+
+#[test]
+fn failing_and_malformed_probes_are_skipped_with_a_warning() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "--add", "qwen.probe", "Broken=exit 1"]);
+    repo.git(&["config", "--add", "qwen.probe", "Silent=true"]);
+    repo.git(&["config", "--add", "qwen.probe", "no separator"]);
+    repo.git(&["config", "--add", "qwen.probe", "Bad key=echo x"]);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], "Add file");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add file");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Warning: probe for Broken failed: 'exit 1' exited with"), "{}", stderr);
+    assert!(stderr.contains("Warning: probe for Silent failed: 'true' printed nothing"), "{}", stderr);
+    assert!(stderr.contains("Warning: ignoring qwen.probe without '=': no separator"), "{}", stderr);
+    assert!(stderr.contains("Warning: ignoring qwen.probe with invalid trailer key: Bad key=echo x"), "{}", stderr);
+}
@@ -0,0 +1,46 @@
+//! Proxy and TLS settings reach the backend.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+
+/// A qwen that also records its environment.
+const ENV_QWEN: &str = "env > \"$QWEN_STUB_PROMPT.env\"\ncat > \"$QWEN_STUB_PROMPT\"\nprintf '%s\\n' \"$QWEN_STUB_OUTPUT\"";
+
+fn backend_env(repo: &TestRepo) -> String {
+    fs::read_to_string(repo.path().parent().unwrap().join("prompt.env")).expect("read the backend's environment")
+}
+
+#[test]
+fn settings_set_the_proxy_and_certificates() {
+    let repo = TestRepo::new();
+    repo.install_program("qwen", ENV_QWEN);
+    repo.git(&["config", "qwen.proxy", "http://proxy.corp:3128"]);
+    repo.git(&["config", "qwen.caBundle", "/etc/corp-ca.pem"]);
+    repo.git(&["config", "qwen.sslVerify", "false"]);
+    repo.stage("a.txt", "a\n");
+
+    assert!(repo.git_qwen(&[], "Add a").status.success());
+    let env = backend_env(&repo);
+    assert!(env.contains("HTTPS_PROXY=http://proxy.corp:3128\n"), "{}", env);
+    assert!(env.contains("NODE_EXTRA_CA_CERTS=/etc/corp-ca.pem\n"), "{}", env);
+    assert!(env.contains("NODE_TLS_REJECT_UNAUTHORIZED=0\n"), "{}", env);
+}
+
+#[test]
+fn environment_is_kept_unless_the_backend_has_its_own_setting() {
+    let repo = TestRepo::new();
+    repo.install_program("qwen", ENV_QWEN);
+    repo.git(&["config", "qwen.qwen.proxy", "http://qwen-proxy:8080"]);
+    repo.git(&["config", "qwen.ollama.proxy", "http://ollama-proxy:8080"]);
+    repo.stage("a.txt", "a\n");
+
+    let vars = [("HTTPS_PROXY", "http://shell-proxy:3128"), ("NO_PROXY", "localhost,.corp")];
+    assert!(repo.git_qwen_env(&[], "Add a", &vars).status.success());
+    let env = backend_env(&repo);
+    assert!(env.contains("HTTPS_PROXY=http://qwen-proxy:8080\n"), "{}", env);
+    assert!(env.contains("NO_PROXY=localhost,.corp\n"), "{}", env);
+}
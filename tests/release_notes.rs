@@ -0,0 +1,60 @@
+//! `git-qwen release-notes <TAG>` prints release notes for the commits
+//! since the tag before it.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const NOTES: &str = "## Highlights\n\nFailed requests are retried.";
+
+fn repo_with_releases() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.stage("old.txt", "old\n");
+    repo.git(&["commit", "-q", "-m", "Add the old feature"]);
+    repo.git(&["tag", "v1.0"]);
+    repo.stage("retry.txt", "retry body\n");
+    repo.git(&["commit", "-q", "-m", "Retry failed requests"]);
+    repo.git(&["tag", "v1.1"]);
+    repo.stage("log.txt", "log body\n");
+    repo.git(&["commit", "-q", "-m", "Log slow requests"]);
+    repo
+}
+
+#[test]
+fn notes_cover_the_commits_since_the_previous_tag() {
+    let repo = repo_with_releases();
+
+    let output = repo.git_qwen(&["release-notes", "v1.1"], NOTES);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), format!("{}\n", NOTES));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Summarizing 1 commit(s) in v1.0..v1.1"));
+
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Release: v1.1\nPrevious release: v1.0\n"), "{}", prompt);
+    assert!(prompt.contains("Retry failed requests") && prompt.contains("+retry body"), "{}", prompt);
+    assert!(!prompt.contains("Log slow requests") && !prompt.contains("log body"), "{}", prompt);
+}
+
+#[test]
+fn new_tag_covers_the_commits_up_to_head() {
+    let repo = repo_with_releases();
+
+    let output = repo.git_qwen(&["release-notes", "v1.2"], NOTES);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Tag v1.2 does not exist yet; describing the commits up to HEAD"), "{}", stderr);
+    assert!(stderr.contains("in v1.1..HEAD"), "{}", stderr);
+    assert!(repo.last_prompt().contains("+log body"));
+}
+
+#[test]
+fn empty_answer_is_an_error() {
+    let repo = repo_with_releases();
+
+    let output = repo.git_qwen(&["release-notes", "v1.1"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("qwen returned empty release notes"));
+    assert!(output.stdout.is_empty());
+}
@@ -0,0 +1,94 @@
+//! Sending the changes to a backend that does not run on this machine.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const LOCAL_SERVER: (&str, &str) = ("OPENAI_BASE_URL", "http://localhost:8080/v1");
+
+fn stderr(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// A repository where sending to a remote backend was never confirmed.
+fn unconfirmed_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.git(&["config", "--unset", "qwen.remoteConfirmed"]);
+    repo
+}
+
+#[test]
+fn remote_backend_is_refused_without_a_terminal() {
+    let repo = unconfirmed_repo();
+    repo.stage("a.txt", "a\n");
+
+    let output = repo.git_qwen(&[], "Add a");
+    assert_eq!(output.status.code(), Some(4), "{}", stderr(&output));
+    assert!(stderr(&output).contains("git config qwen.remoteConfirmed true"), "{}", stderr(&output));
+    assert!(!repo.path().parent().unwrap().join("prompt").exists(), "nothing was sent");
+    assert_eq!(repo.last_message(), "Initial commit");
+}
+
+#[test]
+fn prepare_commit_msg_hook_does_not_send_without_confirmation() {
+    let repo = unconfirmed_repo();
+    repo.install_program("git-qwen", &format!("exec '{}' \"$@\"", env!("CARGO_BIN_EXE_git-qwen")));
+    assert!(repo.git_qwen(&["hook", "install"], "unused").status.success());
+    repo.stage("a.txt", "a\n");
+
+    // git's editor leaves the message empty, so the commit is aborted
+    let output = repo.try_git(&["commit"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("git-qwen: Not sending the changes"), "{}", stderr(&output));
+    assert!(!repo.path().parent().unwrap().join("prompt").exists(), "nothing was sent");
+}
+
+#[test]
+fn confirmation_is_asked_on_a_terminal_and_remembered() {
+    let repo = unconfirmed_repo();
+    repo.stage("a.txt", "a\n");
+
+    let output = repo.git_qwen_in_terminal(&[], "Add a", "y\n");
+    let shown = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", shown);
+    assert!(shown.contains("Send this repository's changes?"), "{}", shown);
+    assert_eq!(repo.git(&["config", "qwen.remoteConfirmed"]), "true\n");
+    assert_eq!(repo.last_message(), "Add a");
+}
+
+#[test]
+fn confirmation_can_be_turned_off() {
+    let repo = unconfirmed_repo();
+    repo.git(&["config", "qwen.confirmRemote", "false"]);
+    repo.stage("a.txt", "a\n");
+
+    let output = repo.git_qwen(&[], "Add a");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(repo.last_message(), "Add a");
+}
+
+#[test]
+fn local_only_refuses_a_remote_backend() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.localOnly", "true"]);
+    repo.stage("a.txt", "a\n");
+
+    let output = repo.git_qwen(&[], "Add a");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("no backend runs locally"), "{}", stderr(&output));
+    assert!(!repo.path().parent().unwrap().join("prompt").exists());
+}
+
+#[test]
+fn local_only_allows_qwen_on_a_local_server() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.localOnly", "true"]);
+    repo.stage("a.txt", "a\n");
+
+    let output = repo.git_qwen_env(&[], "Add a", &[LOCAL_SERVER]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stderr(&output).contains("qwen.localOnly"), "{}", stderr(&output));
+    assert_eq!(repo.last_message(), "Add a");
+}
@@ -0,0 +1,78 @@
+//! Repositories git-qwen cannot commit in, or that are in the middle of
+//! something, get errors that say what to do.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::process::{Command, Output};
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn bare_repository_is_refused() {
+    let repo = TestRepo::new();
+    let bare = repo.path().with_file_name("bare.git");
+    let status = Command::new("git").args(["init", "-q", "--bare"]).arg(&bare).status().unwrap();
+    assert!(status.success());
+
+    let output = repo.git_qwen_in(&bare, &[], "Add file");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("bare repository"), "{}", stderr(&output));
+}
+
+#[test]
+fn unresolved_conflicts_are_listed() {
+    let repo = TestRepo::new();
+    repo.git(&["checkout", "-q", "-b", "other"]);
+    repo.stage("README", "theirs\n");
+    repo.git(&["commit", "-q", "-m", "Change README on other"]);
+    repo.git(&["checkout", "-q", "main"]);
+    repo.stage("README", "ours\n");
+    repo.git(&["commit", "-q", "-m", "Change README on main"]);
+    assert!(!repo.try_git(&["merge", "-q", "other"]).status.success());
+
+    let output = repo.git_qwen(&[], "Merge other");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("unresolved conflicts:\n  README"), "{}", stderr(&output));
+    assert_eq!(repo.last_message(), "Change README on main");
+}
+
+#[test]
+fn stopped_rebase_with_nothing_staged_suggests_continuing() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+    repo.git(&["commit", "-q", "-m", "Add file"]);
+    repo.git(&["-c", "sequence.editor=sed -i s/^pick/edit/", "rebase", "-q", "-i", "HEAD~1"]);
+
+    let output = repo.git_qwen(&[], "Add file");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("A rebase is in progress"), "{}", stderr(&output));
+    assert!(stderr(&output).contains("git rebase --continue"), "{}", stderr(&output));
+}
+
+#[test]
+fn resolved_conflict_diff_is_masked_and_excluded() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.exclude", "private.txt"]);
+    repo.git(&["checkout", "-q", "-b", "other"]);
+    repo.stage("README", "theirs\n");
+    repo.stage("private.txt", "theirs\n");
+    repo.git(&["commit", "-q", "-m", "Change README on other"]);
+    repo.git(&["checkout", "-q", "main"]);
+    repo.stage("README", "ours\n");
+    repo.stage("private.txt", "ours\n");
+    repo.git(&["commit", "-q", "-m", "Change README on main"]);
+    assert!(!repo.try_git(&["merge", "-q", "other"]).status.success());
+    repo.stage("README", "ours\napi_key = \"s3cr3t-value\"\n");
+    repo.stage("private.txt", "internal notes\n");
+
+    assert!(repo.git_qwen(&[], "Merge other").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("how the resolved files differ"), "{}", prompt);
+    assert!(!prompt.contains("s3cr3t-value"), "{}", prompt);
+    assert!(!prompt.contains("internal notes"), "{}", prompt);
+}
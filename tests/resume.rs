@@ -0,0 +1,38 @@
+//! `--resume` reopens the editor on the last message after a failed commit.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+#[test]
+fn edited_message_is_resumed_after_a_rejected_commit() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+    let hook = repo.path().join(".git/hooks/commit-msg");
+    fs::write(&hook, "#!/bin/sh\nexit 1\n").expect("write hook");
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).expect("make hook executable");
+
+    let output = repo.git_qwen_with_editor(&[], "Add file", "echo 'Add the file by hand' > \"$1\"");
+    assert!(!output.status.success());
+    assert_eq!(repo.last_message(), "Initial commit");
+
+    fs::remove_file(&hook).expect("remove hook");
+    let output = repo.git_qwen(&["--resume"], "Add something else");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add the file by hand");
+    assert!(!repo.path().join(".git/QWEN_LASTMSG").exists());
+}
+
+#[test]
+fn resume_without_a_saved_message_fails() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&["--resume"], "Add file");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No message to resume"));
+}
@@ -0,0 +1,54 @@
+//! Failed backend calls are retried with backoff before giving up.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+/// A qwen that fails its first `failures` runs, counting them in a file
+/// next to the work tree.
+fn install_flaky_qwen(repo: &TestRepo, failures: usize) {
+    repo.install_program(
+        "qwen",
+        &format!(
+            "cat > /dev/null\ncount=$(cat ../runs 2>/dev/null || echo 0)\necho $((count + 1)) > ../runs\n\
+             if [ \"$count\" -lt {} ]; then echo 'server busy (429)' >&2; exit 1; fi\necho 'Add file'",
+            failures
+        ),
+    );
+}
+
+fn runs(repo: &TestRepo) -> String {
+    std::fs::read_to_string(repo.path().join("../runs")).unwrap_or_default().trim().to_string()
+}
+
+#[test]
+fn transient_failures_are_retried() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.retryDelay", "0"]);
+    repo.git(&["config", "qwen.retries", "2"]);
+    install_flaky_qwen(&repo, 2);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], "");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "git-qwen failed: {}", stderr);
+    assert_eq!(repo.last_message(), "Add file");
+    assert_eq!(runs(&repo), "3");
+    assert!(stderr.contains("server busy (429)); retrying in"), "{}", stderr);
+}
+
+#[test]
+fn generation_fails_after_the_last_retry() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.retryDelay", "0"]);
+    repo.git(&["config", "qwen.retries", "1"]);
+    install_flaky_qwen(&repo, 5);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], "");
+    assert!(!output.status.success());
+    assert_eq!(runs(&repo), "2");
+    assert_eq!(repo.last_message(), "Initial commit");
+}
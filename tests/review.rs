@@ -0,0 +1,50 @@
+//! `git-qwen review` prints a review of the staged changes and changes
+//! nothing.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const REVIEW: &str = "## Bugs\n- retry.rs:1: the loop never ends.";
+
+#[test]
+fn staged_changes_are_reviewed_without_side_effects() {
+    let repo = TestRepo::new();
+    repo.stage("retry.rs", "fn retry() { loop {} }\n");
+    repo.write("README", "unstaged change\n");
+    let status = repo.git(&["status", "--porcelain"]);
+
+    let output = repo.git_qwen(&["review"], REVIEW);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), format!("{}\n", REVIEW));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("+fn retry() { loop {} }"), "{}", prompt);
+    assert!(!prompt.contains("unstaged change"), "{}", prompt);
+    assert_eq!(repo.git(&["status", "--porcelain"]), status);
+    assert_eq!(repo.git(&["rev-list", "--count", "HEAD"]), "1\n");
+}
+
+#[test]
+fn all_reviews_unstaged_changes_too() {
+    let repo = TestRepo::new();
+    repo.write("README", "unstaged change\n");
+
+    assert!(repo.git_qwen(&["review", "--all"], REVIEW).status.success());
+    assert!(repo.last_prompt().contains("+unstaged change"));
+}
+
+#[test]
+fn nothing_to_review_and_empty_answers_are_errors() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["review"], REVIEW);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No changes to review"));
+
+    repo.stage("retry.rs", "fn retry() {}\n");
+    let output = repo.git_qwen(&["review"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("qwen returned an empty review"));
+}
@@ -0,0 +1,69 @@
+//! `--temperature`, `--seed`, and `qwen.deterministic` are passed to qwen
+//! when it accepts them, and said to be ignored otherwise.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+/// A qwen whose `--help` lists the sampling options.
+const SAMPLING_QWEN: &str = "case \"$1\" in --help) echo '  --temperature  Sampling temperature'; echo '  --seed  Sampling seed'; exit 0;; esac
+printf '%s\\n' \"$@\" > \"$QWEN_STUB_PROMPT.args\"
+cat > \"$QWEN_STUB_PROMPT\"
+printf '%s\\n' \"$QWEN_STUB_OUTPUT\"";
+
+fn arg_after(repo: &TestRepo, flag: &str) -> Option<String> {
+    let args = repo.last_args();
+    let position = args.iter().position(|arg| arg == flag)?;
+    args.get(position + 1).cloned()
+}
+
+#[test]
+fn sampling_is_left_to_qwen_by_default() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "a\n");
+    repo.git_qwen(&[], "Add a");
+    assert_eq!(arg_after(&repo, "--temperature"), None);
+    assert_eq!(arg_after(&repo, "--seed"), None);
+}
+
+#[test]
+fn deterministic_mode_fixes_temperature_and_seed() {
+    let repo = TestRepo::new();
+    repo.install_program("qwen", SAMPLING_QWEN);
+    repo.git(&["config", "qwen.deterministic", "true"]);
+    repo.stage("a.txt", "a\n");
+    let output = repo.git_qwen(&[], "Add a");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(arg_after(&repo, "--temperature").as_deref(), Some("0"));
+    assert_eq!(arg_after(&repo, "--seed").as_deref(), Some("0"));
+
+    repo.stage("b.txt", "b\n");
+    repo.git_qwen(&["--temperature", "0.2", "--seed", "42"], "Add b");
+    assert_eq!(arg_after(&repo, "--temperature").as_deref(), Some("0.2"));
+    assert_eq!(arg_after(&repo, "--seed").as_deref(), Some("42"));
+}
+
+#[test]
+fn options_qwen_does_not_accept_are_left_out_with_a_warning() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "a\n");
+    let output = repo.git_qwen(&["--temperature", "0.2"], "Add a");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(arg_after(&repo, "--temperature"), None);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("this qwen does not accept --temperature"), "{}", stderr);
+}
+
+#[test]
+fn ollama_says_it_ignores_sampling() {
+    let repo = TestRepo::new();
+    repo.install_program("ollama", "cat > /dev/null; echo 'Add a'");
+    repo.git(&["config", "qwen.backends", "ollama"]);
+    repo.stage("a.txt", "a\n");
+    let output = repo.git_qwen(&["--seed", "7"], "Add a");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ollama run takes no sampling options"), "{}", stderr);
+}
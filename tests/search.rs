@@ -0,0 +1,75 @@
+//! `git-qwen search` lists the commits the model finds related to a query,
+//! most relevant first.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+fn repo_with_history() -> TestRepo {
+    let repo = TestRepo::new();
+    for (file, subject) in [("retry.rs", "Retry failed requests"), ("log.rs", "Log slow requests"), ("docs.md", "Fix a typo")] {
+        repo.stage(file, "content\n");
+        repo.git(&["commit", "-q", "-m", subject]);
+    }
+    repo
+}
+
+fn short(repo: &TestRepo, rev: &str) -> String {
+    repo.git(&["rev-parse", "--short", rev]).trim().to_string()
+}
+
+#[test]
+fn matches_are_printed_most_relevant_first() {
+    let repo = repo_with_history();
+    let (retry, log) = (short(&repo, "HEAD~2"), short(&repo, "HEAD~1"));
+    let answer = format!(
+        r#"{{"matches": [{{"commit": "{}", "score": 4, "reason": "Touches request handling"}}, {{"commit": "{}", "score": 9, "reason": "Adds the retries"}}, {{"commit": "0000000", "score": 10, "reason": "Not listed"}}]}}"#,
+        log, retry
+    );
+
+    let output = repo.git_qwen(&["search", "request", "retries"], &answer);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{} Retry failed requests\n    Adds the retries\n{} Log slow requests\n    Touches request handling\n",
+            retry, log
+        )
+    );
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Search query: request retries\n"), "{}", prompt);
+    assert!(prompt.contains(&format!("{} Fix a typo", short(&repo, "HEAD"))), "{}", prompt);
+}
+
+#[test]
+fn max_count_limits_the_commits_searched() {
+    let repo = repo_with_history();
+
+    let output = repo.git_qwen(&["search", "-n", "1", "typo"], r#"{"matches": []}"#);
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "No commits match \"typo\".\n");
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Fix a typo") && !prompt.contains("Log slow requests"), "{}", prompt);
+}
+
+#[test]
+fn backend_failure_is_an_error() {
+    let repo = repo_with_history();
+    repo.install_program("qwen", "cat > /dev/null; echo 'quota exceeded' >&2; exit 1");
+
+    let output = repo.git_qwen(&["search", "retries"], "unused");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn blank_query_is_rejected_without_asking_the_model() {
+    let repo = repo_with_history();
+
+    let output = repo.git_qwen(&["search", " "], "unused");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Give a query to search for"));
+    assert!(!repo.path().with_file_name("prompt").exists());
+}
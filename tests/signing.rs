@@ -0,0 +1,114 @@
+//! Signed commits: checked before generating, kept through to git, and
+//! explained when signing fails.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+/// A gpg with a key for everyone that signs anything.
+const SIGNING_GPG: &str = "case \"$*\" in *--list-secret-keys*) exit 0;; esac
+cat > /dev/null
+echo '[GNUPG:] SIG_CREATED ' >&2
+printf -- '-----BEGIN PGP SIGNATURE-----\\nstub\\n-----END PGP SIGNATURE-----\\n'";
+
+/// A gpg with a key that cannot sign, as when pinentry has no terminal.
+const FAILING_GPG: &str = "case \"$*\" in *--list-secret-keys*) exit 0;; esac
+echo 'gpg: signing failed: Inappropriate ioctl for device' >&2
+exit 2";
+
+/// A gpg without secret keys.
+const KEYLESS_GPG: &str = "exit 2";
+
+/// A gpg whose only secret key is KEY1.
+const ONE_KEY_GPG: &str = "case \"$*\" in *--list-secret-keys\\ KEY1) exit 0;; *--list-secret-keys*) exit 2;; esac
+cat > /dev/null
+echo '[GNUPG:] SIG_CREATED ' >&2
+printf -- '-----BEGIN PGP SIGNATURE-----\\nstub\\n-----END PGP SIGNATURE-----\\n'";
+
+fn signing_repo(gpg: &str) -> TestRepo {
+    let repo = TestRepo::new();
+    repo.install_program("stub-gpg", gpg);
+    repo.git(&["config", "gpg.program", "stub-gpg"]);
+    repo.stage("a.txt", "a\n");
+    repo
+}
+
+fn is_signed(repo: &TestRepo) -> bool {
+    repo.git(&["cat-file", "commit", "HEAD"]).contains("gpgsig ")
+}
+
+#[test]
+fn dash_s_signs_the_commit() {
+    let repo = signing_repo(SIGNING_GPG);
+
+    let output = repo.git_qwen(&["-S"], "Add a");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(is_signed(&repo));
+}
+
+#[test]
+fn missing_key_fails_before_generating() {
+    let repo = signing_repo(KEYLESS_GPG);
+    repo.git(&["config", "commit.gpgSign", "true"]);
+
+    let output = repo.git_qwen(&[], "Add a");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("has no secret key"), "{}", stderr);
+    assert!(!repo.path().parent().unwrap().join("prompt").exists());
+}
+
+#[test]
+fn key_given_with_dash_s_is_the_one_checked() {
+    let repo = signing_repo(ONE_KEY_GPG);
+    repo.git(&["config", "user.signingKey", "KEY1"]);
+
+    let output = repo.git_qwen(&["-SKEY2"], "Add a");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("has no secret key for KEY2"), "{}", stderr);
+
+    repo.git(&["config", "--unset", "user.signingKey"]);
+    let output = repo.git_qwen(&["--gpg-sign=KEY1"], "Add a");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(is_signed(&repo));
+}
+
+#[test]
+fn missing_x509_certificate_fails_before_generating() {
+    let repo = signing_repo(SIGNING_GPG);
+    repo.install_program("stub-gpgsm", KEYLESS_GPG);
+    repo.git(&["config", "gpg.format", "x509"]);
+    repo.git(&["config", "gpg.x509.program", "stub-gpgsm"]);
+
+    let output = repo.git_qwen(&["-S"], "Add a");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("stub-gpgsm has no secret key"), "{}", stderr);
+    assert!(!repo.path().parent().unwrap().join("prompt").exists());
+}
+
+#[test]
+fn failed_signature_is_explained() {
+    let repo = signing_repo(FAILING_GPG);
+    repo.git(&["config", "commit.gpgSign", "true"]);
+
+    let output = repo.git_qwen(&[], "Add a");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("GPG_TTY"), "{}", stderr);
+    assert!(stderr.contains("git-qwen --resume"), "{}", stderr);
+}
+
+#[test]
+fn no_sign_commits_without_a_signature() {
+    let repo = signing_repo(FAILING_GPG);
+    repo.git(&["config", "commit.gpgSign", "true"]);
+
+    let output = repo.git_qwen(&["--no-sign"], "Add a");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add a");
+    assert!(!is_signed(&repo));
+}
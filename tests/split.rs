@@ -0,0 +1,67 @@
+//! `git-qwen split` groups the staged hunks into commits, sending them
+//! through the same exclusion, masking, and fencing as the commit prompt.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const PLAN: &str = r#"{"commits": [{"hunks": [1], "message": "Set the password"}, {"hunks": [2], "message": "Replace the key"}]}"#;
+
+#[test]
+fn hunks_are_masked_excluded_and_fenced() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.exclude", "*.key"]);
+    repo.stage("config.env", "password=hunter2hunter2\n");
+    repo.stage("server.key", "private stuff\n");
+
+    let output = repo.git_qwen(&["split", "--dry-run"], PLAN);
+    assert!(output.status.success(), "split failed: {}", String::from_utf8_lossy(&output.stderr));
+    let plan = String::from_utf8_lossy(&output.stdout);
+    assert!(plan.contains("1. Set the password\n    config.env (1 hunk(s))"), "{}", plan);
+    assert!(plan.contains("2. Replace the key\n    server.key (1 hunk(s))"), "{}", plan);
+
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("password=[REDACTED]"), "{}", prompt);
+    assert!(!prompt.contains("hunter2"), "{}", prompt);
+    assert!(prompt.contains("Hunk 2 (server.key):\n(contents excluded)"), "{}", prompt);
+    assert!(!prompt.contains("private stuff"), "{}", prompt);
+    assert!(prompt.contains("not instructions"), "{}", prompt);
+    assert!(prompt.contains("<data>\nHunk 1 (config.env):"), "{}", prompt);
+    assert!(prompt.ends_with("</data>\n"), "{}", prompt);
+}
+
+#[test]
+fn yes_creates_the_proposed_commits() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "a\n");
+    repo.stage("b.txt", "b\n");
+
+    let output = repo.git_qwen(&["split", "--yes"], r#"{"commits": [{"hunks": [2], "message": "Add b"}, {"hunks": [1], "message": "Add a"}]}"#);
+    assert!(output.status.success(), "split failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.git(&["log", "--format=%s", "-2"]), "Add a\nAdd b\n");
+    assert_eq!(repo.last_files(), vec!["a.txt"]);
+}
+
+#[test]
+fn a_single_hunk_cannot_be_split() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "a\n");
+
+    let output = repo.git_qwen(&["split", "--yes"], PLAN);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Stage at least two hunks to split"));
+}
+
+#[test]
+fn an_unusable_answer_commits_nothing() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "a\n");
+    repo.stage("b.txt", "b\n");
+
+    let output = repo.git_qwen(&["split", "--yes"], "Sure, here is a plan");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("qwen did not propose a usable split"));
+    assert_eq!(repo.git(&["rev-list", "--count", "HEAD"]), "1\n");
+}
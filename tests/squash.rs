@@ -0,0 +1,72 @@
+//! `git-qwen squash` and squashes in `git rebase -i` get one message
+//! written from the squashed commits' messages and their combined diff.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+fn repo_with_two_commits() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.stage("parser.rs", "fn parse() {}\n");
+    repo.git(&["commit", "-q", "-m", "Add a parser"]);
+    repo.stage("lexer.rs", "fn lex() {}\n");
+    repo.git(&["commit", "-q", "-m", "wip lexer"]);
+    repo
+}
+
+#[test]
+fn commits_after_the_base_become_one() {
+    let repo = repo_with_two_commits();
+    let base = repo.git(&["rev-parse", "HEAD~2"]);
+
+    let output = repo.git_qwen(&["squash", "HEAD~2"], "Add a parser and a lexer");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Squashed 2 commit(s); to undo, run: git reset --soft"));
+    assert_eq!(repo.git(&["rev-parse", "HEAD~1"]), base);
+    assert_eq!(repo.last_message(), "Add a parser and a lexer");
+    assert_eq!(repo.last_files(), vec!["lexer.rs", "parser.rs"]);
+
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("This commit squashes the commits below into one."), "{}", prompt);
+    assert!(prompt.contains("Add a parser\n---\nwip lexer"), "{}", prompt);
+    assert!(prompt.contains("+fn parse() {}") && prompt.contains("+fn lex() {}"), "{}", prompt);
+}
+
+#[test]
+fn branch_is_not_reset_when_the_message_is_emptied() {
+    let repo = repo_with_two_commits();
+    let head = repo.git(&["rev-parse", "HEAD"]);
+
+    let output = repo.git_qwen_with_editor(&["squash", "HEAD~2"], "Add a parser and a lexer", ": > \"$1\"");
+    assert_eq!(output.status.code(), Some(8));
+    assert_eq!(repo.git(&["rev-parse", "HEAD"]), head);
+}
+
+#[test]
+fn base_must_be_an_ancestor_with_commits_after_it() {
+    let repo = repo_with_two_commits();
+
+    let output = repo.git_qwen(&["squash", "HEAD"], "unused");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No commits to squash after HEAD"));
+
+    let output = repo.git_qwen(&["squash", "no-such-commit"], "unused");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Not a commit: no-such-commit"));
+}
+
+#[test]
+fn interactive_rebase_squash_gets_a_synthesized_message() {
+    let repo = repo_with_two_commits();
+    repo.install_program("git-qwen", &format!("exec '{}' \"$@\"", env!("CARGO_BIN_EXE_git-qwen")));
+    // git runs the hook without the stub's answer in its environment
+    repo.install_program("qwen", "cat > \"$QWEN_STUB_PROMPT\"\necho 'Add a parser and a lexer'");
+    assert!(repo.git_qwen(&["hook", "install"], "").status.success());
+
+    repo.git(&["-c", "sequence.editor=sed -i '2s/^pick/squash/'", "rebase", "-q", "-i", "HEAD~2"]);
+    assert_eq!(repo.last_message(), "Add a parser and a lexer");
+    assert_eq!(repo.git(&["rev-list", "--count", "HEAD"]), "2\n");
+    assert!(repo.last_prompt().contains("Add a parser\n---\nwip lexer"), "{}", repo.last_prompt());
+}
@@ -0,0 +1,67 @@
+//! `git-qwen stash` stashes the changes with a message describing them.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+fn stash_list(repo: &TestRepo) -> String {
+    repo.git(&["stash", "list", "--format=%gs"])
+}
+
+#[test]
+fn changes_are_stashed_with_a_generated_message() {
+    let repo = TestRepo::new();
+    repo.write("README", "half-done retry\n");
+
+    let output = repo.git_qwen(&["stash"], "Half-done retry backoff for HTTP backend.");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stash_list(&repo), "On main: Half-done retry backoff for HTTP backend\n");
+    assert_eq!(repo.git(&["status", "--porcelain"]), "");
+    assert!(repo.last_prompt().contains("+half-done retry"));
+}
+
+#[test]
+fn arguments_pass_through_and_untracked_files_are_named() {
+    let repo = TestRepo::new();
+    repo.write("README", "tracked change\n");
+    repo.write("notes.txt", "untracked\n");
+
+    let output = repo.git_qwen(&["stash", "--include-untracked", "--quiet"], "Retry notes");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(repo.last_prompt().contains("Untracked file: notes.txt"), "{}", repo.last_prompt());
+    assert_eq!(repo.git(&["status", "--porcelain"]), "", "the untracked file is stashed too");
+}
+
+#[test]
+fn given_message_is_used_without_asking_the_model() {
+    let repo = TestRepo::new();
+    repo.write("README", "change\n");
+
+    assert!(repo.git_qwen(&["stash", "-m", "my own message"], "unused").status.success());
+    assert_eq!(stash_list(&repo), "On main: my own message\n");
+    assert!(!repo.path().with_file_name("prompt").exists(), "the model was asked");
+}
+
+#[test]
+fn backend_failure_falls_back_to_git_default_message() {
+    let repo = TestRepo::new();
+    repo.install_program("qwen", "cat > /dev/null; echo 'quota exceeded' >&2; exit 1");
+    repo.write("README", "change\n");
+
+    let output = repo.git_qwen(&["stash"], "unused");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("stashing with git's default message"));
+    assert!(stash_list(&repo).starts_with("WIP on main: "), "{}", stash_list(&repo));
+}
+
+#[test]
+fn nothing_to_stash_is_an_error() {
+    let repo = TestRepo::new();
+
+    let output = repo.git_qwen(&["stash"], "unused");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No local changes to stash"));
+    assert_eq!(stash_list(&repo), "");
+}
@@ -0,0 +1,72 @@
+//! Generated subjects are put in the usual shape before they are shown.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+/// Commits a change with the stub answering `answer`, after applying the
+/// given `qwen.*` settings, and returns the resulting commit message.
+fn commit_with_answer(settings: &[(&str, &str)], answer: &str) -> String {
+    let repo = TestRepo::new();
+    for (key, value) in settings {
+        repo.git(&["config", key, value]);
+    }
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], answer);
+    assert!(
+        output.status.success(),
+        "git-qwen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    repo.last_message()
+}
+
+#[test]
+fn subject_is_capitalized_and_made_imperative() {
+    assert_eq!(commit_with_answer(&[], "added file."), "Add file");
+    assert_eq!(commit_with_answer(&[], "Fixes crash on empty input"), "Fix crash on empty input");
+}
+
+#[test]
+fn prefixed_subject_keeps_its_case() {
+    assert_eq!(commit_with_answer(&[], "feat: added file"), "feat: add file");
+}
+
+#[test]
+fn unknown_first_word_is_not_capitalized() {
+    assert_eq!(commit_with_answer(&[], "npm install the file"), "npm install the file");
+}
+
+#[test]
+fn normalization_can_be_disabled() {
+    assert_eq!(
+        commit_with_answer(&[("qwen.normalizeSubject", "false"), ("qwen.lint", "off")], "added file."),
+        "added file."
+    );
+}
+
+#[test]
+fn subject_is_normalized_before_truncation() {
+    assert_eq!(
+        commit_with_answer(&[("qwen.truncateSubject", "20")], "Added support for reading the file."),
+        "Add support for"
+    );
+}
+
+#[test]
+fn emoji_is_mapped_from_type_or_verb() {
+    let map = [("qwen.emoji", "map")];
+    assert_eq!(commit_with_answer(&map, "feat: added parser"), "✨ feat: add parser");
+    assert_eq!(commit_with_answer(&map, "Fixed crash on empty input"), "🐛 Fix crash on empty input");
+    assert_eq!(commit_with_answer(&map, "🔥 Remove file"), "🔥 Remove file");
+}
+
+#[test]
+fn emoji_picked_by_model_is_kept() {
+    let model = [("qwen.emoji", "model")];
+    assert_eq!(commit_with_answer(&model, "♻️ Moved file"), "♻️ Move file");
+    assert_eq!(commit_with_answer(&model, "Add file"), "✨ Add file");
+}
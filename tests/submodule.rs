@@ -0,0 +1,31 @@
+//! A submodule update is described with the submodule's new commits.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+#[test]
+fn submodule_update_lists_its_commits() {
+    let library = TestRepo::new();
+    let repo = TestRepo::new();
+    let url = library.path();
+    repo.git(&["-c", "protocol.file.allow=always", "submodule", "add", "-q", url.to_str().unwrap(), "library"]);
+    repo.git(&["commit", "-q", "-m", "Add the library"]);
+
+    library.stage("parser.c", "parse\n");
+    library.git(&["commit", "-q", "-m", "Add a parser"]);
+    library.stage("parser.c", "parse faster\n");
+    library.git(&["commit", "-q", "-m", "Speed up the parser"]);
+    let submodule = repo.path().join("library");
+    repo.git_in(&submodule, &["pull", "-q", "origin", "main"]);
+    repo.git(&["add", "library"]);
+
+    let output = repo.git_qwen(&[], "Update the library");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Submodule library moves from"), "{}", prompt);
+    assert!(prompt.contains("bringing in these commits:"), "{}", prompt);
+    assert!(prompt.contains("Add a parser") && prompt.contains("Speed up the parser"), "{}", prompt);
+}
@@ -0,0 +1,62 @@
+//! The prompt names the functions and types the changes fall in.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+const RUST: &str = "use std::io;
+
+pub struct HttpClient {
+    retries: u32,
+}
+
+impl HttpClient {
+    pub fn new() -> Self {
+        HttpClient { retries: 3 }
+    }
+
+    pub fn send(&self) -> io::Result<()> {
+        for _ in 0..self.retries {
+            self.try_once()?;
+        }
+        Ok(())
+    }
+}
+";
+
+const PYTHON: &str = "class Client:
+    def connect(self):
+        return True
+
+    def send(self, data):
+        if data:
+            return len(data)
+        return 0
+";
+
+#[test]
+fn enclosing_symbols_are_listed() {
+    let repo = TestRepo::new();
+    repo.stage("http.rs", RUST);
+    repo.stage("client.py", PYTHON);
+    repo.git(&["commit", "-q", "-m", "Add the clients"]);
+    repo.stage("http.rs", &RUST.replace("self.try_once()?;", "self.try_once().or_else(|_| self.wait())?;"));
+    repo.stage("client.py", &PYTHON.replace("return len(data)", "return len(data) + 1"));
+
+    assert!(repo.git_qwen(&[], "Retry after waiting").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("- http.rs: HttpClient::send\n"), "{}", prompt);
+    assert!(prompt.contains("- client.py: Client.send\n"), "{}", prompt);
+}
+
+#[test]
+fn symbols_can_be_turned_off() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.symbols", "false"]);
+    repo.stage("http.rs", RUST);
+
+    assert!(repo.git_qwen(&[], "Add the HTTP client").status.success());
+    assert!(!repo.last_prompt().contains("HttpClient::send"));
+}
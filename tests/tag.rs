@@ -0,0 +1,81 @@
+//! `git-qwen tag` creates an annotated tag summarizing the commits since
+//! the previous tag.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+fn repo_with_release() -> TestRepo {
+    let repo = TestRepo::new();
+    repo.stage("old.txt", "old\n");
+    repo.git(&["commit", "-q", "-m", "Add the old feature"]);
+    repo.git(&["tag", "v1.0"]);
+    repo.stage("retry.txt", "retry\n");
+    repo.git(&["commit", "-q", "-m", "Retry failed requests"]);
+    repo.stage("log.txt", "log\n");
+    repo.git(&["commit", "-q", "-m", "Log slow requests"]);
+    repo
+}
+
+#[test]
+fn tag_summarizes_the_commits_since_the_latest_tag() {
+    let repo = repo_with_release();
+
+    let output = repo.git_qwen(&["tag", "v1.1"], "Release 1.1\n\nRequests are retried and slow ones logged.");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Summarizing 2 commit(s) in v1.0..HEAD"));
+    assert_eq!(repo.git(&["cat-file", "-t", "v1.1"]), "tag\n");
+    assert_eq!(
+        repo.git(&["tag", "-l", "--format=%(contents)", "v1.1"]),
+        "Release 1.1\n\nRequests are retried and slow ones logged.\n\n"
+    );
+    assert_eq!(repo.git(&["rev-parse", "v1.1^{commit}"]), repo.git(&["rev-parse", "HEAD"]));
+
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("Tag: v1.1\nPrevious release: v1.0\n"), "{}", prompt);
+    assert!(prompt.contains("Retry failed requests") && prompt.contains("Log slow requests"), "{}", prompt);
+    assert!(!prompt.contains("Add the old feature"), "{}", prompt);
+}
+
+#[test]
+fn tag_goes_on_the_end_of_the_range() {
+    let repo = repo_with_release();
+
+    let output = repo.git_qwen(&["tag", "v1.0.1", "v1.0..HEAD~1"], "Release 1.0.1");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.git(&["rev-parse", "v1.0.1^{commit}"]), repo.git(&["rev-parse", "HEAD~1"]));
+    assert!(!repo.last_prompt().contains("Log slow requests"));
+}
+
+#[test]
+fn existing_tag_is_not_replaced() {
+    let repo = repo_with_release();
+
+    let output = repo.git_qwen(&["tag", "v1.0"], "Release 1.0");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Tag v1.0 already exists"));
+    assert!(!repo.path().with_file_name("prompt").exists(), "the model was asked");
+}
+
+#[test]
+fn no_tag_is_created_when_the_backend_fails() {
+    let repo = repo_with_release();
+    repo.install_program("qwen", "cat > /dev/null; echo 'quota exceeded' >&2; exit 1");
+
+    let output = repo.git_qwen(&["tag", "v1.1"], "unused");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to generate tag message"));
+    assert_eq!(repo.git(&["tag", "-l", "v1.1"]), "");
+}
+
+#[test]
+fn empty_answer_creates_no_tag() {
+    let repo = repo_with_release();
+
+    let output = repo.git_qwen(&["tag", "v1.1"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("qwen returned an empty tag message"));
+    assert_eq!(repo.git(&["tag", "-l", "v1.1"]), "");
+}
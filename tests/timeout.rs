@@ -0,0 +1,94 @@
+//! `qwen.timeout` stops a qwen that does not answer, and Ctrl+C stops it or
+//! the editor without leaving anything behind.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A qwen that records its pid and then never answers.
+const HANGING_QWEN: &str = "echo $$ > \"$QWEN_STUB_PROMPT.pid\"\ncat > \"$QWEN_STUB_PROMPT\"\nsleep 60";
+
+fn wait_for(path: &Path) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !path.exists() {
+        assert!(Instant::now() < deadline, "{} never appeared", path.display());
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn is_running(pid: &str) -> bool {
+    Command::new("kill").args(["-0", pid]).stderr(Stdio::null()).status().unwrap().success()
+}
+
+#[test]
+fn qwen_is_stopped_after_the_timeout() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.timeout", "1"]);
+    repo.install_program("qwen", HANGING_QWEN);
+    repo.stage("file.txt", "content\n");
+
+    let started = Instant::now();
+    let output = repo.git_qwen(&[], "unused");
+    assert!(started.elapsed() < Duration::from_secs(30));
+    assert_eq!(output.status.code(), Some(5), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("timed out after 1 seconds (see qwen.timeout)"));
+    assert_eq!(repo.last_message(), "Initial commit");
+
+    let pid = fs::read_to_string(repo.path().with_file_name("prompt.pid")).unwrap();
+    assert!(!is_running(pid.trim()), "qwen was left running");
+}
+
+#[test]
+fn zero_timeout_waits_for_the_answer() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.timeout", "0"]);
+    repo.install_program("qwen", "cat > /dev/null; sleep 2; echo 'Add file'");
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen(&[], "unused");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add file");
+}
+
+#[test]
+fn interrupt_during_generation_stops_qwen_and_aborts() {
+    let repo = TestRepo::new();
+    repo.install_program("qwen", HANGING_QWEN);
+    repo.stage("file.txt", "content\n");
+
+    let git_qwen = repo.spawn_git_qwen(&[], "unused");
+    let pid_file = repo.path().with_file_name("prompt.pid");
+    wait_for(&pid_file);
+    assert!(Command::new("kill").args(["-INT", &git_qwen.id().to_string()]).status().unwrap().success());
+
+    let output = git_qwen.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(130), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Aborting commit."));
+    assert_eq!(repo.last_message(), "Initial commit");
+    let pid = fs::read_to_string(&pid_file).unwrap();
+    assert!(!is_running(pid.trim()), "qwen was left running");
+}
+
+#[test]
+fn interrupt_in_the_editor_removes_the_message_file() {
+    let repo = TestRepo::new();
+    // Interrupts git-qwen, its parent, then waits for the file to go
+    repo.install_program(
+        "interrupting-editor",
+        "echo \"$1\" > \"$QWEN_STUB_PROMPT.message\"\nkill -INT $PPID\nfor _ in $(seq 100); do [ -e \"$1\" ] || exit 0; sleep 0.1; done",
+    );
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen_with_editor_command(&[], "Add file", Some("exec interrupting-editor"));
+    assert_eq!(output.status.code(), Some(130), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Initial commit");
+    let message_file = fs::read_to_string(repo.path().with_file_name("prompt.message")).unwrap();
+    assert!(!Path::new(message_file.trim()).exists(), "{} was left behind", message_file.trim());
+}
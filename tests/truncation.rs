@@ -0,0 +1,46 @@
+//! With `qwen.mapReduce=false`, a diff over `qwen.maxPromptTokens` is
+//! truncated: the overview and the file and hunk headers stay, and the
+//! largest hunks are trimmed first.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+
+fn large_change(repo: &TestRepo) {
+    let large: String = (0..400).map(|n| format!("generated line number {}\n", n)).collect();
+    repo.stage("large.txt", &large);
+    repo.stage("small.txt", "the real change\n");
+}
+
+#[test]
+fn largest_hunks_are_trimmed_first() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.mapReduce", "false"]);
+    repo.git(&["config", "qwen.maxPromptTokens", "1000"]);
+    large_change(&repo);
+
+    let output = repo.git_qwen(&[], "Add files");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let prompt = repo.last_prompt();
+    assert!(prompt.len() <= 1000 * 4, "prompt of {} characters", prompt.len());
+    assert!(prompt.contains("Overview of the changes:"), "{}", prompt);
+    assert!(prompt.contains(" large.txt |   400"), "{}", prompt);
+    assert!(prompt.contains("@@ -0,0 +1,400 @@\n[... 400 lines omitted to fit the prompt size limit ...]\n"), "{}", prompt);
+    assert!(!prompt.contains("generated line number 200"), "{}", prompt);
+    assert!(prompt.contains("+the real change\n"), "{}", prompt);
+}
+
+#[test]
+fn no_limit_keeps_the_whole_diff() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.mapReduce", "false"]);
+    repo.git(&["config", "qwen.maxPromptTokens", "0"]);
+    large_change(&repo);
+
+    assert!(repo.git_qwen(&[], "Add files").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("+generated line number 399\n"), "{}", prompt);
+    assert!(!prompt.contains("omitted to fit the prompt size limit"), "{}", prompt);
+}
@@ -0,0 +1,85 @@
+//! Untracked files are left out of the commit, so the model and the user
+//! are told about them rather than left to wonder.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+
+#[test]
+fn untracked_files_are_context_but_not_committed() {
+    let repo = TestRepo::new();
+    repo.stage("a.txt", "a\n");
+    repo.write("new.txt", "hello\n");
+
+    let output = repo.git_qwen_with_editor(&[], "Add a", "cp \"$1\" ../edited");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_files(), vec!["a.txt"]);
+
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("untracked, so they are not part of this commit"));
+    assert!(prompt.contains("- new.txt\n"));
+    assert!(!prompt.contains("hello"), "content is only shown with qwen.untracked=content");
+
+    let edited = fs::read_to_string(repo.path().with_file_name("edited")).unwrap();
+    assert!(edited.contains("# Untracked files, not included in this commit:\n#    new.txt\n"), "{}", edited);
+}
+
+#[test]
+fn content_of_small_untracked_files_can_be_shown() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.untracked", "content"]);
+    repo.stage("a.txt", "a\n");
+    repo.write("new.txt", "hello\n");
+
+    assert!(repo.git_qwen(&[], "Add a").status.success());
+    assert!(repo.last_prompt().contains("- new.txt\n    hello\n"));
+}
+
+#[test]
+fn all_warns_that_untracked_files_are_left_out() {
+    let repo = TestRepo::new();
+    repo.write("README", "changed\n");
+    repo.write("new.txt", "hello\n");
+
+    let output = repo.git_qwen(&["-a"], "Change README");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("-a does not include untracked files (new.txt)"));
+    assert_eq!(repo.last_files(), vec!["README"]);
+}
+
+#[test]
+fn excluded_untracked_files_are_left_out_of_the_prompt() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.untracked", "content"]);
+    repo.git(&["config", "qwen.exclude", "*.secret"]);
+    repo.write(".gitqwenignore", "*.log\n");
+    repo.stage("a.txt", "a\n");
+    repo.write("debug.log", "noise\n");
+    repo.write("token.secret", "hunter2\n");
+    repo.write("new.txt", "hello\n");
+
+    assert!(repo.git_qwen(&[], "Add a").status.success());
+    let prompt = repo.last_prompt();
+    assert!(prompt.contains("- new.txt\n    hello\n"));
+    assert!(!prompt.contains("debug.log") && !prompt.contains("noise"), "{}", prompt);
+    assert!(!prompt.contains("token.secret") && !prompt.contains("hunter2"), "{}", prompt);
+}
+
+#[test]
+fn excluded_untracked_files_are_left_out_of_the_editor_template() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.exclude", "*.secret"]);
+    repo.stage("a.txt", "a\n");
+    repo.write("token.secret", "hunter2\n");
+    repo.write("new.txt", "hello\n");
+
+    let output = repo.git_qwen_with_editor(&[], "Add a", "cp \"$1\" ../edited");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let edited = fs::read_to_string(repo.path().with_file_name("edited")).unwrap();
+    assert!(edited.contains("# Untracked files, not included in this commit:\n#    new.txt\n"), "{}", edited);
+    assert!(!edited.contains("token.secret"), "{}", edited);
+}
@@ -0,0 +1,55 @@
+//! `-v` shows the diff below a scissors line in the editor, and everything
+//! below that line is left out of the message.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+
+const SCISSORS: &str = "# ------------------------ >8 ------------------------\n";
+
+#[test]
+fn verbose_shows_the_diff_below_the_scissors() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "staged line\n");
+
+    // The editor keeps a copy and adds text below the diff, which is ignored
+    let output = repo.git_qwen_with_editor(&["-v"], "Add file", "cp \"$1\" ../edited; echo 'Not part of it' >> \"$1\"");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let edited = fs::read_to_string(repo.path().with_file_name("edited")).unwrap();
+    let (above, below) = edited.split_once(SCISSORS).expect("scissors line");
+    assert!(above.starts_with("Add file\n"), "{}", edited);
+    assert!(below.contains("+staged line\n"), "{}", edited);
+    assert_eq!(repo.last_message(), "Add file");
+}
+
+#[test]
+fn double_verbose_also_shows_unstaged_changes() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "staged line\n");
+    repo.write("README", "unstaged line\n");
+
+    let output = repo.git_qwen_with_editor(&["-vv"], "Add file", "cp \"$1\" ../edited");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let edited = fs::read_to_string(repo.path().with_file_name("edited")).unwrap();
+    let (_, below) = edited.split_once(SCISSORS).expect("scissors line");
+    let (staged, unstaged) = below.split_once("# Changes not staged for commit:\n").expect("unstaged heading");
+    assert!(staged.contains("# Changes to be committed:\n") && staged.contains("+staged line\n"), "{}", edited);
+    assert!(unstaged.contains("+unstaged line\n"), "{}", edited);
+    assert_eq!(repo.last_message(), "Add file");
+    assert_eq!(repo.last_files(), vec!["file.txt"]);
+}
+
+#[test]
+fn without_verbose_there_is_no_diff_in_the_editor() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "staged line\n");
+
+    assert!(repo.git_qwen_with_editor(&[], "Add file", "cp \"$1\" ../edited").status.success());
+    let edited = fs::read_to_string(repo.path().with_file_name("edited")).unwrap();
+    assert!(!edited.contains(SCISSORS) && !edited.contains("+staged line"), "{}", edited);
+}
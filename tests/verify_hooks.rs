@@ -0,0 +1,80 @@
+//! With `qwen.verifyHooks`, the repository's `commit-msg` hook sees the
+//! generated message before the editor opens, and a rejection can be
+//! answered by regenerating it.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+fn install_hook(repo: &TestRepo, script: &str) {
+    let hook = repo.path().join(".git/hooks/commit-msg");
+    fs::write(&hook, format!("#!/bin/sh\n{}\n", script)).expect("write hook");
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).expect("make hook executable");
+}
+
+/// A hook that rejects only the first message it sees.
+const REJECTS_ONCE: &str = "[ -e ../rejected ] && exit 0\ntouch ../rejected\necho 'subject is too vague' >&2\nexit 1";
+
+#[test]
+fn rejection_is_reported_before_the_editor_opens() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.verifyHooks", "true"]);
+    install_hook(&repo, "grep -q vague \"$1\" && { echo 'subject is too vague' >&2; exit 1; }; exit 0");
+    repo.stage("file.txt", "content\n");
+
+    // Without a terminal to ask on, the message is opened in the editor anyway
+    let output = repo.git_qwen_with_editor(&[], "Change something vague", "echo 'Add the file' > \"$1\"");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("The commit-msg hook rejected the generated message:\nsubject is too vague"), "{}", stderr);
+    assert_eq!(repo.last_message(), "Add the file");
+}
+
+#[test]
+fn rejected_message_can_be_regenerated() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.verifyHooks", "true"]);
+    install_hook(&repo, REJECTS_ONCE);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen_in_terminal(&[], "Add file", "r\n");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stdout));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("[r]egenerate, [e]dit anyway, or [a]bort?"));
+    assert_eq!(repo.last_message(), "Add file");
+}
+
+#[test]
+fn rejected_message_can_be_aborted() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "qwen.verifyHooks", "true"]);
+    install_hook(&repo, REJECTS_ONCE);
+    repo.stage("file.txt", "content\n");
+
+    let output = repo.git_qwen_in_terminal(&[], "Add file", "a\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Aborting commit."));
+    assert_eq!(repo.last_message(), "Initial commit");
+}
+
+#[test]
+fn hooks_are_not_run_early_without_the_setting_or_with_no_verify() {
+    let repo = TestRepo::new();
+    install_hook(&repo, "echo run >> ../hook-runs");
+    repo.stage("file.txt", "content\n");
+
+    assert!(repo.git_qwen(&[], "Add file").status.success());
+    let runs = fs::read_to_string(repo.path().with_file_name("hook-runs")).unwrap();
+    assert_eq!(runs.lines().count(), 1, "only git's own run of the hook");
+
+    repo.git(&["config", "qwen.verifyHooks", "true"]);
+    install_hook(&repo, "exit 1");
+    repo.stage("file.txt", "changed\n");
+    let output = repo.git_qwen(&["--no-verify"], "Change file");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("rejected"));
+    assert_eq!(repo.last_message(), "Change file");
+}
@@ -0,0 +1,33 @@
+//! `git-qwen watch` drafts the message before the commit asks for it.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn commit_reuses_the_drafted_message() {
+    let repo = TestRepo::new();
+    repo.stage("file.txt", "content\n");
+
+    let mut watch = repo.spawn_git_qwen(&["watch"], "Add the drafted message");
+    let messages = repo.path().join(".git/qwen-cache/messages");
+    let started = Instant::now();
+    while fs::read_dir(&messages).map_or(true, |mut entries| entries.next().is_none()) {
+        if started.elapsed() > Duration::from_secs(10) {
+            let _ = watch.kill();
+            panic!("no message was drafted");
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    let _ = watch.kill();
+    let _ = watch.wait();
+
+    let output = repo.git_qwen(&[], "Add a fresh message");
+    assert!(output.status.success(), "git-qwen failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.last_message(), "Add the drafted message");
+}
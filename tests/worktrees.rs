@@ -0,0 +1,80 @@
+//! git-qwen commits where git would from a subdirectory, a linked
+//! worktree, or a submodule, keeping its files in that git directory.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::TestRepo;
+use std::fs;
+use std::process::Output;
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "git-qwen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn commits_from_a_subdirectory() {
+    let repo = TestRepo::new();
+    let subdir = repo.path().join("src");
+    fs::create_dir(&subdir).unwrap();
+    repo.stage("src/lib.rs", "fn main() {}\n");
+
+    assert_success(&repo.git_qwen_in(&subdir, &[], "Add lib.rs"));
+    assert_eq!(repo.last_message(), "Add lib.rs");
+    assert!(repo.path().join(".git/qwen-history.jsonl").exists());
+    assert!(!subdir.join(".git").exists());
+}
+
+#[test]
+fn commits_in_a_linked_worktree() {
+    let repo = TestRepo::new();
+    let worktree = repo.path().with_file_name("linked");
+    repo.git(&["worktree", "add", "-q", "-b", "feature", worktree.to_str().unwrap()]);
+    fs::write(worktree.join("feature.txt"), "feature\n").unwrap();
+    repo.git_in(&worktree, &["add", "feature.txt"]);
+
+    assert_success(&repo.git_qwen_in(&worktree, &[], "Add the feature"));
+    assert_eq!(repo.git(&["log", "-1", "--format=%s", "feature"]).trim(), "Add the feature");
+    assert_eq!(repo.last_message(), "Initial commit");
+    assert!(repo.path().join(".git/worktrees/linked/qwen-history.jsonl").exists());
+}
+
+#[test]
+fn commits_in_a_submodule() {
+    let library = TestRepo::new();
+    let repo = TestRepo::new();
+    repo.git(&[
+        "-c",
+        "protocol.file.allow=always",
+        "submodule",
+        "add",
+        "-q",
+        library.path().to_str().unwrap(),
+        "library",
+    ]);
+    repo.git(&["commit", "-q", "-m", "Add the library"]);
+    let submodule = repo.path().join("library");
+    // A clone has none of the test repository's settings
+    for (key, value) in [
+        ("user.name", "Test User"),
+        ("user.email", "test@example.com"),
+        ("qwen.formatRetries", "0"),
+        ("qwen.retries", "0"),
+        ("qwen.recentCommits", "0"),
+        ("qwen.remoteConfirmed", "true"),
+    ] {
+        repo.git_in(&submodule, &["config", key, value]);
+    }
+    fs::write(submodule.join("lib.txt"), "lib\n").unwrap();
+    repo.git_in(&submodule, &["add", "lib.txt"]);
+
+    assert_success(&repo.git_qwen_in(&submodule, &[], "Add lib.txt"));
+    assert_eq!(repo.git_in(&submodule, &["log", "-1", "--format=%s"]).trim(), "Add lib.txt");
+    assert_eq!(repo.last_message(), "Add the library");
+    assert!(repo.path().join(".git/modules/library/qwen-history.jsonl").exists());
+}